@@ -0,0 +1,603 @@
+use crate::util::inline::split_args;
+use bytes::{Buf, BytesMut};
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp2;
+use redis_protocol::resp2::types::{OwnedFrame as Resp2Frame, Resp2Frame as _};
+use redis_protocol::resp3::types::{FrameMap, OwnedFrame, Resp3Frame};
+use redis_protocol::resp3::{decode, encode};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Longest inline command accepted, matching Redis' `PROTO_INLINE_MAX_SIZE` of 64KB.
+pub const MAX_INLINE_SIZE: usize = 64 * 1024;
+
+/// Largest frame accepted by default, matching Redis' `proto-max-bulk-len` of 512MB.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 512 * 1024 * 1024;
+
+/// Protocol version spoken on a connection, negotiated with `HELLO`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Protocol {
+    /// Spoken by clients that never send `HELLO`, like in Redis
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl Protocol {
+    /// Version number of the protocol, as sent and reported by `HELLO`
+    pub fn version(&self) -> i64 {
+        match self {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        }
+    }
+}
+
+/// Streaming RESP3 codec for use with [`tokio_util::codec::Framed`].
+///
+/// Incoming bytes are buffered until a complete frame is available, so commands may
+/// arrive split over several reads. If a single read contains multiple pipelined commands,
+/// every frame is yielded in the order it was received.
+///
+/// Like Redis, input that does not start with `*` is treated as an inline command, e.g.
+/// `SET key "some value"\r\n` typed into `nc`. Its arguments are split with
+/// [`split_args`](crate::util::inline::split_args) and returned as an array of blob strings,
+/// exactly like the equivalent RESP array.
+///
+/// Replies are always built as RESP3 frames. Like a new Redis connection, the codec starts out
+/// speaking [`Protocol::Resp2`] and converts them with [`downgrade`] before encoding, until it
+/// is switched to [`Protocol::Resp3`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use futures::{SinkExt, StreamExt};
+/// use redis_protocol::resp3::types::OwnedFrame;
+/// use redis_protocol_bridge::codec::RespCodec;
+/// use tokio::net::TcpStream;
+/// use tokio_util::codec::Framed;
+///
+/// async fn echo(stream: TcpStream) {
+///     let mut framed = Framed::new(stream, RespCodec::default());
+///     while let Some(Ok(frame)) = framed.next().await {
+///         framed.send(frame).await.unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RespCodec {
+    max_frame_size: usize,
+    protocol: Protocol,
+    progress: Progress,
+}
+
+impl RespCodec {
+    /// Construct a codec that rejects frames larger than `max_frame_size` bytes.
+    pub fn new(max_frame_size: usize) -> Self {
+        Self {
+            max_frame_size,
+            protocol: Protocol::default(),
+            progress: Progress::default(),
+        }
+    }
+
+    /// Maximum size of a single encoded frame in bytes.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Protocol replies are encoded in
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Encode all following replies using `protocol`
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Decode an inline command terminated by `\n`, skipping empty lines.
+    fn decode_inline(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<OwnedFrame>, RedisProtocolError> {
+        loop {
+            let Some(end) = src.iter().position(|c| *c == b'\n') else {
+                if src.len() > MAX_INLINE_SIZE {
+                    return Err(error_protocol("Protocol error: too big inline request"));
+                }
+                return Ok(None);
+            };
+
+            if end > MAX_INLINE_SIZE {
+                return Err(error_protocol("Protocol error: too big inline request"));
+            }
+
+            let line = src.split_to(end + 1);
+            let args = split_args(&line[..end])
+                .ok_or_else(|| error_protocol("Protocol error: unbalanced quotes in request"))?;
+
+            if !args.is_empty() {
+                let data = args
+                    .into_iter()
+                    .map(|data| OwnedFrame::BlobString {
+                        data,
+                        attributes: None,
+                    })
+                    .collect();
+                return Ok(Some(OwnedFrame::Array {
+                    data,
+                    attributes: None,
+                }));
+            }
+
+            if src.is_empty() || src[0] == b'*' {
+                return self.decode(src);
+            }
+        }
+    }
+
+    fn error_frame_too_large(&self) -> RedisProtocolError {
+        RedisProtocolError::new(
+            RedisProtocolErrorKind::DecodeError,
+            format!(
+                "Protocol error: frame exceeds maximum size of {} bytes",
+                self.max_frame_size
+            ),
+        )
+    }
+}
+
+/// How much of an incomplete frame at the start of the buffer has been read already.
+///
+/// Without this, a large frame arriving over many reads would be parsed again from the start
+/// every time more data is available. Only the boundaries of the elements are found here; the
+/// frame is decoded once it is complete.
+#[derive(Debug, Clone, Default)]
+struct Progress {
+    /// Start of the first element that is not complete yet
+    offset: usize,
+    /// Where to continue searching for the end of the line at `offset`
+    searched: usize,
+    /// Number of elements still missing in each open aggregate, innermost last
+    pending: Vec<usize>,
+}
+
+impl Progress {
+    /// Continue reading `src` where the last call stopped.
+    ///
+    /// # Returns
+    /// The size of the frame at the start of `src` once it is complete
+    fn scan(
+        &mut self,
+        src: &[u8],
+        max_frame_size: usize,
+    ) -> Result<Option<usize>, RedisProtocolError> {
+        loop {
+            let from = self.searched.max(self.offset);
+            let Some(end) = src[from..]
+                .windows(2)
+                .position(|window| window == b"\r\n")
+                .map(|position| from + position)
+            else {
+                /* The `\r` may already be buffered without its `\n` */
+                self.searched = src.len().saturating_sub(1);
+                return Ok(None);
+            };
+
+            let header = &src[self.offset..end];
+            let mut next = end + 2;
+            let complete = match header.first() {
+                Some(b'*' | b'~' | b'>' | b'%') => match parse_length(&header[1..])? {
+                    Some(0) | None => true,
+                    Some(count) if header[0] == b'%' => {
+                        self.pending.push(count * 2);
+                        false
+                    }
+                    Some(count) => {
+                        self.pending.push(count);
+                        false
+                    }
+                },
+                Some(b'$' | b'=' | b'!') => {
+                    if let Some(length) = parse_length(&header[1..])? {
+                        if length > max_frame_size {
+                            return Err(error_protocol("Protocol error: invalid bulk length"));
+                        }
+                        if src.len() < next + length + 2 {
+                            return Ok(None);
+                        }
+                        next += length + 2;
+                    }
+                    true
+                }
+                Some(b'+' | b'-' | b':' | b'_' | b',' | b'#' | b'(') => true,
+                _ => return Err(error_protocol("Protocol error: unsupported frame type")),
+            };
+
+            self.offset = next;
+            self.searched = next;
+            if complete && self.complete_element() {
+                return Ok(Some(next));
+            }
+        }
+    }
+
+    /// Count an element as complete in its aggregate, and that aggregate in its parent if it
+    /// has no elements left.
+    ///
+    /// # Returns
+    /// `true` if the whole frame is complete
+    fn complete_element(&mut self) -> bool {
+        while let Some(remaining) = self.pending.last_mut() {
+            *remaining -= 1;
+            if *remaining > 0 {
+                return false;
+            }
+            self.pending.pop();
+        }
+        true
+    }
+}
+
+/// Parse the length in an aggregate or blob header, `None` for `-1`
+fn parse_length(data: &[u8]) -> Result<Option<usize>, RedisProtocolError> {
+    let length = std::str::from_utf8(data)
+        .ok()
+        .and_then(|data| data.parse::<i64>().ok())
+        .ok_or_else(|| error_protocol("Protocol error: invalid length"))?;
+    match length {
+        -1 => Ok(None),
+        length => usize::try_from(length)
+            .map(Some)
+            .map_err(|_| error_protocol("Protocol error: invalid length")),
+    }
+}
+
+fn error_protocol(message: &'static str) -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, message)
+}
+
+impl Default for RespCodec {
+    /// Return a [`RespCodec`] accepting frames up to [`DEFAULT_MAX_FRAME_SIZE`].
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_SIZE)
+    }
+}
+
+impl Decoder for RespCodec {
+    type Item = OwnedFrame;
+    type Error = RedisProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        if src[0] != b'*' {
+            return self.decode_inline(src);
+        }
+
+        let scanned = match self.progress.scan(src, self.max_frame_size) {
+            Ok(Some(size)) if size > self.max_frame_size => Err(self.error_frame_too_large()),
+            /* Frame is incomplete, wait for more data unless we already buffered too much */
+            Ok(None) if src.len() > self.max_frame_size => Err(self.error_frame_too_large()),
+            scanned => scanned,
+        };
+        let size = match scanned {
+            Ok(Some(size)) => size,
+            Ok(None) => return Ok(None),
+            Err(error) => {
+                self.progress = Progress::default();
+                return Err(error);
+            }
+        };
+        self.progress = Progress::default();
+
+        match decode::complete::decode(&src[..size])? {
+            Some((frame, _)) => {
+                src.advance(size);
+                Ok(Some(frame))
+            }
+            None => Err(error_protocol("Protocol error: incomplete frame")),
+        }
+    }
+}
+
+impl Encoder<OwnedFrame> for RespCodec {
+    type Error = RedisProtocolError;
+
+    fn encode(&mut self, item: OwnedFrame, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let item = match (is_null_array(&item), self.protocol) {
+            (true, Protocol::Resp2) => {
+                dst.extend_from_slice(b"*-1\r\n");
+                return Ok(());
+            }
+            (true, Protocol::Resp3) => OwnedFrame::Null,
+            (false, _) => item,
+        };
+
+        let offset = dst.len();
+        match self.protocol {
+            Protocol::Resp3 => {
+                dst.resize(offset + item.encode_len(false), 0);
+                encode::complete::encode(&mut dst[offset..], &item, false)?;
+            }
+            Protocol::Resp2 => {
+                let item = downgrade(item);
+                dst.resize(offset + item.encode_len(false), 0);
+                resp2::encode::encode(&mut dst[offset..], &item, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reply of commands that send RESP2 clients a nil array instead of a nil bulk string, like
+/// `LPOP key count` on a missing key.
+///
+/// RESP3 has a single null type, so [`RespCodec`] encodes this as `_` for RESP3 clients and as
+/// `*-1` for RESP2 clients. The frame itself is an empty array marked with an attribute that is
+/// never sent, so it has to be the whole reply.
+pub fn null_array() -> OwnedFrame {
+    OwnedFrame::Array {
+        data: Vec::new(),
+        attributes: Some(null_array_marker()),
+    }
+}
+
+/// Whether `frame` was created by [`null_array`]
+pub fn is_null_array(frame: &OwnedFrame) -> bool {
+    matches!(
+        frame,
+        OwnedFrame::Array { data, attributes: Some(attributes) }
+            if data.is_empty() && *attributes == null_array_marker()
+    )
+}
+
+fn null_array_marker() -> FrameMap<OwnedFrame, OwnedFrame> {
+    FrameMap::from([(
+        OwnedFrame::SimpleString {
+            data: b"null-array".to_vec(),
+            attributes: None,
+        },
+        OwnedFrame::Boolean {
+            data: true,
+            attributes: None,
+        },
+    )])
+}
+
+/// Convert a RESP3 reply into its RESP2 equivalent, the way Redis replies to RESP2 clients.
+///
+///  * Maps become flat arrays of alternating keys and values
+///  * Sets and pushes become arrays
+///  * Null becomes the nil bulk string, which RESP2 also uses for a [`null_array`] that is
+///    not the whole reply
+///  * Doubles and big numbers become bulk strings
+///  * Booleans become the integers `1` and `0`
+pub fn downgrade(frame: OwnedFrame) -> Resp2Frame {
+    if is_null_array(&frame) {
+        return Resp2Frame::Null;
+    }
+    match frame {
+        OwnedFrame::BlobString { data, .. }
+        | OwnedFrame::VerbatimString { data, .. }
+        | OwnedFrame::BigNumber { data, .. }
+        | OwnedFrame::ChunkedString(data) => Resp2Frame::BulkString(data),
+        OwnedFrame::SimpleString { data, .. } => Resp2Frame::SimpleString(data),
+        OwnedFrame::SimpleError { data, .. } => Resp2Frame::Error(data),
+        OwnedFrame::BlobError { data, .. } => {
+            Resp2Frame::Error(String::from_utf8_lossy(&data).into_owned())
+        }
+        OwnedFrame::Number { data, .. } => Resp2Frame::Integer(data),
+        OwnedFrame::Boolean { data, .. } => Resp2Frame::Integer(data as i64),
+        OwnedFrame::Double { data, .. } => Resp2Frame::BulkString(format_double(data).into_bytes()),
+        OwnedFrame::Null | OwnedFrame::Hello { .. } => Resp2Frame::Null,
+        OwnedFrame::Array { data, .. } | OwnedFrame::Push { data, .. } => {
+            Resp2Frame::Array(data.into_iter().map(downgrade).collect())
+        }
+        OwnedFrame::Set { data, .. } => {
+            Resp2Frame::Array(data.into_iter().map(downgrade).collect())
+        }
+        OwnedFrame::Map { data, .. } => Resp2Frame::Array(flatten(data)),
+    }
+}
+
+fn flatten(map: FrameMap<OwnedFrame, OwnedFrame>) -> Vec<Resp2Frame> {
+    map.into_iter()
+        .flat_map(|(key, value)| [downgrade(key), downgrade(value)])
+        .collect()
+}
+
+/// Format a double like Redis does in bulk strings: laid out like `%.17g`, using the shortest
+/// digits that read back as the same value, so `1e20` becomes `1e+20` and `0.1` stays `0.1`.
+/// Special values become `inf`, `-inf` and `nan`.
+pub fn format_double(value: f64) -> String {
+    if value.is_nan() {
+        return "nan".into();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "inf" } else { "-inf" }.into();
+    }
+
+    let scientific = format!("{value:e}");
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("Finite doubles have an exponent");
+    let exponent: i32 = exponent.parse().expect("Exponent is an integer");
+    /* `%g` switches to exponent notation outside of this range, given a precision of 17 */
+    if (-4..17).contains(&exponent) {
+        value.to_string()
+    } else {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{mantissa}e{sign}{:02}", exponent.abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_owned_frame;
+
+    #[test]
+    fn decode_partial_frame() {
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nGET\r\n$3\r\nk"[..]);
+
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        buf.extend_from_slice(b"ey\r\n");
+        let frame = codec
+            .decode(&mut buf)
+            .unwrap()
+            .expect("Frame should be complete");
+        assert_eq!(parse_owned_frame(frame), vec!["GET", "key"]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decode_frame_byte_by_byte() {
+        let mut codec = RespCodec::default();
+        let input =
+            b"*2\r\n*2\r\n$3\r\nGET\r\n%1\r\n+a\r\n:1\r\n$5\r\nhello\r\n*1\r\n$4\r\nPING\r\n";
+        let mut buf = BytesMut::new();
+        let mut frames = Vec::new();
+
+        for byte in input {
+            buf.extend_from_slice(&[*byte]);
+            if let Some(frame) = codec.decode(&mut buf).unwrap() {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(parse_owned_frame(frames.remove(1)), vec!["PING"]);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decode_pipelined_frames() {
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::from(&b"*1\r\n$4\r\nPING\r\n*2\r\n$4\r\nECHO\r\n$2\r\nhi\r\n"[..]);
+
+        let first = codec.decode(&mut buf).unwrap().unwrap();
+        let second = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(parse_owned_frame(first), vec!["PING"]);
+        assert_eq!(parse_owned_frame(second), vec!["ECHO", "hi"]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn decode_inline_commands() {
+        let mut codec = RespCodec::default();
+        let mut buf =
+            BytesMut::from(&b"\r\nSET key \"a value\"\r\n*1\r\n$4\r\nPING\r\nHELLO 3\n"[..]);
+
+        let set = codec.decode(&mut buf).unwrap().unwrap();
+        let ping = codec.decode(&mut buf).unwrap().unwrap();
+        let hello = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(parse_owned_frame(set), vec!["SET", "key", "a value"]);
+        assert_eq!(parse_owned_frame(ping), vec!["PING"]);
+        assert_eq!(parse_owned_frame(hello), vec!["HELLO", "3"]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        let mut partial = BytesMut::from(&b"GET ke"[..]);
+        assert_eq!(codec.decode(&mut partial).unwrap(), None);
+
+        let mut unbalanced = BytesMut::from(&b"GET \"key\r\n"[..]);
+        assert!(codec.decode(&mut unbalanced).is_err());
+
+        let mut long = BytesMut::from(&b"GET "[..]);
+        long.extend_from_slice(&vec![b'a'; MAX_INLINE_SIZE]);
+        long.extend_from_slice(b"\r\n");
+        assert!(codec.decode(&mut long).is_err());
+    }
+
+    #[test]
+    fn decode_rejects_oversized_frames() {
+        let mut codec = RespCodec::new(16);
+
+        let mut complete = BytesMut::from(&b"*1\r\n$20\r\naaaaaaaaaaaaaaaaaaaa\r\n"[..]);
+        assert!(codec.decode(&mut complete).is_err());
+
+        let mut partial = BytesMut::from(&b"*1\r\n$20\r\naaaaaaaaaaaaa"[..]);
+        assert!(codec.decode(&mut partial).is_err());
+    }
+
+    #[test]
+    fn format_doubles() {
+        assert_eq!(format_double(1.5), "1.5");
+        assert_eq!(format_double(0.1), "0.1");
+        assert_eq!(format_double(1e20), "1e+20");
+        assert_eq!(format_double(1e16), "10000000000000000");
+        assert_eq!(format_double(-2.5e-7), "-2.5e-07");
+        assert_eq!(format_double(0.0001), "0.0001");
+        assert_eq!(format_double(1.5e300), "1.5e+300");
+        assert_eq!(format_double(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn encode_appends_frames() {
+        let mut codec = RespCodec::default();
+        codec.set_protocol(Protocol::Resp3);
+        let mut buf = BytesMut::new();
+
+        codec
+            .encode(
+                OwnedFrame::Number {
+                    data: 1,
+                    attributes: None,
+                },
+                &mut buf,
+            )
+            .unwrap();
+        codec.encode(OwnedFrame::Null, &mut buf).unwrap();
+
+        assert_eq!(&buf[..], b":1\r\n_\r\n");
+    }
+
+    #[test]
+    fn encode_downgrades_to_resp2() {
+        let mut codec = RespCodec::default();
+        assert_eq!(codec.protocol(), Protocol::Resp2);
+        let mut buf = BytesMut::new();
+
+        let map = OwnedFrame::Map {
+            data: FrameMap::from([(
+                OwnedFrame::SimpleString {
+                    data: b"score".to_vec(),
+                    attributes: None,
+                },
+                OwnedFrame::Double {
+                    data: 1.5,
+                    attributes: None,
+                },
+            )]),
+            attributes: None,
+        };
+        codec.encode(map, &mut buf).unwrap();
+        codec.encode(OwnedFrame::Null, &mut buf).unwrap();
+
+        assert_eq!(&buf[..], b"*2\r\n+score\r\n$3\r\n1.5\r\n$-1\r\n");
+    }
+
+    #[test]
+    fn encode_null_arrays() {
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::new();
+
+        codec.encode(null_array(), &mut buf).unwrap();
+        codec.set_protocol(Protocol::Resp3);
+        codec.encode(null_array(), &mut buf).unwrap();
+        assert_eq!(&buf[..], b"*-1\r\n_\r\n");
+
+        let empty = OwnedFrame::Array {
+            data: Vec::new(),
+            attributes: None,
+        };
+        assert!(!is_null_array(&empty));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::commands::meta::{CATEGORIES, COMMANDS};
+use crate::commands::parse::Request;
+use crate::server::acl::AccessControl;
+use crate::server::auth::DEFAULT_USER;
+use crate::server::session::Session;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{error_too_few_arguments, error_too_many_arguments};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Acl {
+    SetUser {
+        username: String,
+        rules: Vec<String>,
+    },
+    GetUser(String),
+    DelUser(Vec<String>),
+    List,
+    WhoAmI,
+    Cat(Option<String>),
+}
+
+/// Parse ACL subcommands
+///
+/// # Syntax
+/// ```text
+/// ACL SETUSER username [rule [rule ...]]
+/// ACL GETUSER username
+/// ACL DELUSER username [username ...]
+/// ACL LIST
+/// ACL WHOAMI
+/// ACL CAT [category]
+/// ```
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut args: Vec<String> = args.iter().map(|arg| bytes_to_string(arg)).collect();
+    if args.is_empty() {
+        return Err(error_too_few_arguments("ACL", Some(1)));
+    }
+
+    let subcommand = args.remove(0).to_uppercase();
+    let acl = match (subcommand.as_str(), args.len()) {
+        ("SETUSER", 1..) => Acl::SetUser {
+            username: args.remove(0),
+            rules: args,
+        },
+        ("GETUSER", 1) => Acl::GetUser(args.remove(0)),
+        ("DELUSER", 1..) => Acl::DelUser(args),
+        ("LIST", 0) => Acl::List,
+        ("WHOAMI", 0) => Acl::WhoAmI,
+        ("CAT", 0) => Acl::Cat(None),
+        ("CAT", 1) => Acl::Cat(args.pop()),
+        ("SETUSER" | "GETUSER" | "DELUSER", 0) => {
+            return Err(error_too_few_arguments(
+                &format!("ACL {subcommand}"),
+                Some(1),
+            ))
+        }
+        ("GETUSER" | "LIST" | "WHOAMI" | "CAT", _) => {
+            return Err(error_too_many_arguments(&format!("ACL {subcommand}")))
+        }
+        (unknown, _) => {
+            return Err(RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                format!("Unsupported command: ACL {unknown}"),
+            ))
+        }
+    };
+
+    Ok(Request::ACL(acl))
+}
+
+/// Handle ACL subcommands.
+///
+/// Without an [`AccessControl`] list, every session is the [`DEFAULT_USER`] with all
+/// permissions and only `WHOAMI` and `CAT` are available.
+pub fn handle(
+    acl: Option<&AccessControl>,
+    session: &Session,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let Request::ACL(subcommand) = args else {
+        panic!("Expected enum variant ACL, but got {:?}", args.type_id())
+    };
+
+    match (subcommand, acl) {
+        (Acl::WhoAmI, _) => Ok(session.user().unwrap_or(DEFAULT_USER).as_frame()),
+        (Acl::Cat(None), _) => Ok(CATEGORIES.to_vec().as_frame()),
+        (Acl::Cat(Some(category)), _) => {
+            let category = category.to_lowercase();
+            if !CATEGORIES.contains(&category.as_str()) {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    format!("ERR Unknown category '{category}'"),
+                ));
+            }
+
+            let commands: Vec<&str> = COMMANDS
+                .iter()
+                .filter(|info| info.categories.contains(&category.as_str()))
+                .map(|info| info.name)
+                .collect();
+            Ok(commands.as_frame())
+        }
+        (_, None) => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR ACL users are not enabled on this server",
+        )),
+        (Acl::SetUser { username, rules }, Some(acl)) => {
+            acl.set_user(username, rules)?;
+            Ok("OK".as_frame())
+        }
+        (Acl::GetUser(username), Some(acl)) => Ok(match acl.user(username) {
+            Some(user) => HashMap::from([
+                ("flags", user.flags().as_frame()),
+                ("commands", user.command_rules().as_frame()),
+                ("keys", user.key_rules().as_frame()),
+            ])
+            .as_frame(),
+            None => OwnedFrame::Null,
+        }),
+        (Acl::DelUser(usernames), Some(acl)) => {
+            let mut deleted = 0;
+            for username in usernames {
+                deleted += acl.del_user(username)? as i64;
+            }
+            Ok(deleted.as_frame())
+        }
+        (Acl::List, Some(acl)) => {
+            let users: Vec<String> = acl.users().iter().map(|user| user.describe()).collect();
+            Ok(users.as_frame())
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::commands::parse::Request;
+use crate::util::convert::bytes_to_string;
+use crate::util::errors::{error_too_few_arguments, error_too_many_arguments};
+use bytes::Bytes;
+use redis_protocol::error::RedisProtocolError;
+use std::fmt;
+
+/// Password sent by a client.
+///
+/// The [`Debug`] representation is redacted, so requests containing credentials can be logged
+/// safely.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Password(String);
+
+impl Password {
+    pub fn new(password: impl Into<String>) -> Self {
+        Self(password.into())
+    }
+
+    /// Return the password in clear text
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(<redacted>)")
+    }
+}
+
+/// Parse arguments for AUTH command
+///
+/// # Syntax
+/// ```text
+/// AUTH [username] password
+/// ```
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut args: Vec<String> = args.iter().map(|arg| bytes_to_string(arg)).collect();
+    let password = match args.pop() {
+        Some(password) => Password(password),
+        None => return Err(error_too_few_arguments("AUTH", Some(1))),
+    };
+
+    if args.len() > 1 {
+        return Err(error_too_many_arguments("AUTH"));
+    }
+
+    Ok(Request::AUTH {
+        username: args.pop(),
+        password,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_is_redacted() {
+        let request = parse(vec![
+            Bytes::from_static(b"admin"),
+            Bytes::from_static(b"secret"),
+        ])
+        .unwrap();
+
+        let logged = format!("{:?}", request);
+        assert!(logged.contains("admin"));
+        assert!(!logged.contains("secret"));
+    }
+}
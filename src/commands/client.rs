@@ -0,0 +1,504 @@
+use crate::codec::Protocol;
+use crate::commands::parse::Request;
+use crate::server::auth::DEFAULT_USER;
+use crate::server::clients::ClientRegistry;
+use crate::server::session::Session;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_negative_timeout, error_not_integer, error_syntax, error_too_few_arguments,
+    error_too_many_arguments, error_unsupported_command,
+};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Client {
+    SetName(String),
+    GetName,
+    Id,
+    List {
+        kind: Option<ClientType>,
+        ids: Vec<u64>,
+    },
+    Info,
+    /// `CLIENT KILL ip:port`, replying with `OK`
+    KillAddr(String),
+    /// `CLIENT KILL <filter> <value> ...`, replying with the number of killed clients
+    Kill(Vec<KillFilter>),
+    Pause {
+        timeout: u64,
+        mode: PauseMode,
+    },
+    Unpause,
+    NoEvict(bool),
+    SetInfo {
+        attribute: LibAttribute,
+        value: String,
+    },
+}
+
+/// Kinds of clients Redis distinguishes. Every connection of this server is a normal client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClientType {
+    Normal,
+    Master,
+    Replica,
+    PubSub,
+}
+
+/// Filters of `CLIENT KILL`, a client is only killed if it matches all of them
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KillFilter {
+    Id(u64),
+    Addr(String),
+    User(String),
+    Type(ClientType),
+    /// Whether the client sending the command is spared, `true` by default
+    SkipMe(bool),
+    /// Only kill clients connected for at least this many seconds
+    MaxAge(u64),
+}
+
+/// Commands held back by `CLIENT PAUSE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PauseMode {
+    /// Only commands that may modify data
+    Write,
+    /// Every command
+    All,
+}
+
+/// Attributes set by `CLIENT SETINFO`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LibAttribute {
+    LibName,
+    LibVer,
+}
+
+/// Parse CLIENT subcommands
+///
+/// # Syntax
+/// ```text
+/// CLIENT SETNAME connection-name
+/// CLIENT GETNAME
+/// CLIENT ID
+/// CLIENT LIST [TYPE <NORMAL | MASTER | REPLICA | PUBSUB>] [ID client-id [client-id ...]]
+/// CLIENT INFO
+/// CLIENT KILL <ip:port | <[ID client-id] [ADDR ip:port] [USER username]
+///     [TYPE <NORMAL | MASTER | REPLICA | PUBSUB>] [SKIPME <YES | NO>] [MAXAGE maxage]
+///     [[ID client-id] ...]>>
+/// CLIENT PAUSE timeout [WRITE | ALL]
+/// CLIENT UNPAUSE
+/// CLIENT NO-EVICT <ON | OFF>
+/// CLIENT SETINFO <LIB-NAME libname | LIB-VER libver>
+/// ```
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut args: Vec<String> = args.iter().map(|arg| bytes_to_string(arg)).collect();
+    if args.is_empty() {
+        return Err(error_too_few_arguments("CLIENT", Some(1)));
+    }
+
+    let subcommand = args.remove(0).to_uppercase();
+    let client = match (subcommand.as_str(), args.len()) {
+        ("SETNAME", 1) => Client::SetName(args.remove(0)),
+        ("GETNAME", 0) => Client::GetName,
+        ("ID", 0) => Client::Id,
+        ("LIST", _) => parse_list(&args)?,
+        ("INFO", 0) => Client::Info,
+        ("KILL", 1) => Client::KillAddr(args.remove(0)),
+        ("KILL", 2..) => Client::Kill(parse_kill_filters(&args)?),
+        ("PAUSE", 1 | 2) => Client::Pause {
+            timeout: parse_timeout(&args[0])?,
+            mode: match args.get(1).map(|mode| mode.to_uppercase()).as_deref() {
+                None | Some("ALL") => PauseMode::All,
+                Some("WRITE") => PauseMode::Write,
+                Some(_) => return Err(error_syntax()),
+            },
+        },
+        ("UNPAUSE", 0) => Client::Unpause,
+        ("NO-EVICT", 1) => Client::NoEvict(match args[0].to_uppercase().as_str() {
+            "ON" => true,
+            "OFF" => false,
+            _ => return Err(error_syntax()),
+        }),
+        ("SETINFO", 2) => Client::SetInfo {
+            attribute: match args[0].to_uppercase().as_str() {
+                "LIB-NAME" => LibAttribute::LibName,
+                "LIB-VER" => LibAttribute::LibVer,
+                unknown => {
+                    return Err(RedisProtocolError::new(
+                        RedisProtocolErrorKind::Parse,
+                        format!("ERR Unrecognized option '{unknown}'"),
+                    ))
+                }
+            },
+            value: args.remove(1),
+        },
+        ("SETNAME" | "KILL" | "PAUSE" | "NO-EVICT" | "SETINFO", 0) => {
+            return Err(error_too_few_arguments(
+                &format!("CLIENT {subcommand}"),
+                Some(1),
+            ))
+        }
+        ("SETNAME" | "GETNAME" | "ID" | "INFO" | "PAUSE" | "UNPAUSE" | "NO-EVICT", _) => {
+            return Err(error_too_many_arguments(&format!("CLIENT {subcommand}")))
+        }
+        ("SETINFO", _) => return Err(error_syntax()),
+        (unknown, _) => return Err(error_unsupported_command(&format!("CLIENT {unknown}"))),
+    };
+
+    Ok(Request::CLIENT(client))
+}
+
+fn parse_list(args: &[String]) -> Result<Client, RedisProtocolError> {
+    let mut kind = None;
+    let mut ids = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.to_uppercase().as_str() {
+            "TYPE" => kind = Some(parse_type(iter.next().ok_or_else(error_syntax)?)?),
+            "ID" => {
+                /* IDs continue until the end of the arguments */
+                for id in iter.by_ref() {
+                    ids.push(parse_id(id)?);
+                }
+                if ids.is_empty() {
+                    return Err(error_syntax());
+                }
+            }
+            _ => return Err(error_syntax()),
+        }
+    }
+
+    Ok(Client::List { kind, ids })
+}
+
+fn parse_kill_filters(args: &[String]) -> Result<Vec<KillFilter>, RedisProtocolError> {
+    if !args.len().is_multiple_of(2) {
+        return Err(error_syntax());
+    }
+
+    args.chunks(2)
+        .map(|pair| {
+            let value = &pair[1];
+            Ok(match pair[0].to_uppercase().as_str() {
+                "ID" => KillFilter::Id(parse_id(value)?),
+                "ADDR" => KillFilter::Addr(value.clone()),
+                "USER" => KillFilter::User(value.clone()),
+                "TYPE" => KillFilter::Type(parse_type(value)?),
+                "SKIPME" => KillFilter::SkipMe(match value.to_uppercase().as_str() {
+                    "YES" => true,
+                    "NO" => false,
+                    _ => return Err(error_syntax()),
+                }),
+                "MAXAGE" => KillFilter::MaxAge(value.parse().map_err(|_| error_not_integer())?),
+                _ => return Err(error_syntax()),
+            })
+        })
+        .collect()
+}
+
+fn parse_id(id: &str) -> Result<u64, RedisProtocolError> {
+    match id.parse::<u64>() {
+        Ok(id) if id > 0 => Ok(id),
+        _ => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR client-id should be greater than 0",
+        )),
+    }
+}
+
+fn parse_type(kind: &str) -> Result<ClientType, RedisProtocolError> {
+    match kind.to_lowercase().as_str() {
+        "normal" => Ok(ClientType::Normal),
+        "master" => Ok(ClientType::Master),
+        "replica" | "slave" => Ok(ClientType::Replica),
+        "pubsub" => Ok(ClientType::PubSub),
+        _ => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            format!("ERR Unknown client type '{kind}'"),
+        )),
+    }
+}
+
+fn parse_timeout(timeout: &str) -> Result<u64, RedisProtocolError> {
+    match timeout.parse::<i64>() {
+        Ok(timeout) if timeout < 0 => Err(error_negative_timeout()),
+        Ok(timeout) => Ok(timeout as u64),
+        Err(_) => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR timeout is not an integer or out of range",
+        )),
+    }
+}
+
+/// Check that `name` can be used as a connection name, like Redis it may not contain spaces,
+/// newlines or other special characters
+pub fn validate_name(name: &str) -> Result<(), RedisProtocolError> {
+    validate(name, "Client names")
+}
+
+fn validate(value: &str, what: &str) -> Result<(), RedisProtocolError> {
+    if value.bytes().all(|c| (b'!'..=b'~').contains(&c)) {
+        Ok(())
+    } else {
+        Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            format!("ERR {what} cannot contain spaces, newlines or special characters."),
+        ))
+    }
+}
+
+/// Describe `session` in the format of `CLIENT LIST` and `CLIENT INFO`
+pub fn describe(session: &Session, now: Instant) -> String {
+    let resp = match session.protocol() {
+        Protocol::Resp2 => 2,
+        Protocol::Resp3 => 3,
+    };
+
+    format!(
+        "id={} addr={} name={} age={} idle={} flags={} db={} sub=0 psub=0 multi=-1 cmd={} \
+         user={} resp={} lib-name={} lib-ver={}",
+        session.id(),
+        session.addr(),
+        session.name().unwrap_or_default(),
+        now.saturating_duration_since(session.created()).as_secs(),
+        now.saturating_duration_since(session.last_interaction())
+            .as_secs(),
+        if session.no_evict() { "e" } else { "N" },
+        session.db(),
+        session.last_command().unwrap_or("NULL"),
+        session.user().unwrap_or(DEFAULT_USER),
+        resp,
+        session.lib_name().unwrap_or_default(),
+        session.lib_version().unwrap_or_default(),
+    )
+}
+
+/// Whether `client` matches `filter`. `session` is the client sending `CLIENT KILL`.
+fn filter_matches(filter: &KillFilter, client: &Session, session: &Session, now: Instant) -> bool {
+    match filter {
+        KillFilter::Id(id) => client.id() == *id,
+        KillFilter::Addr(addr) => client.addr().to_string() == *addr,
+        KillFilter::User(user) => client.user().unwrap_or(DEFAULT_USER) == user,
+        KillFilter::Type(kind) => *kind == ClientType::Normal,
+        KillFilter::SkipMe(skip) => !skip || client.id() != session.id(),
+        KillFilter::MaxAge(age) => {
+            now.saturating_duration_since(client.created()).as_secs() >= *age
+        }
+    }
+}
+
+/// Handle CLIENT subcommands using the connections in `clients`.
+///
+/// `session` is used instead of its own registered copy, so changes made by the current
+/// command are visible immediately.
+pub fn handle(
+    clients: &ClientRegistry,
+    session: &mut Session,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let Request::CLIENT(subcommand) = args else {
+        panic!("Expected enum variant CLIENT, but got {:?}", args.type_id())
+    };
+
+    let now = Instant::now();
+
+    match subcommand {
+        Client::SetName(name) => {
+            validate_name(name)?;
+            session.set_name(Some(name.clone()).filter(|name| !name.is_empty()));
+            Ok("OK".as_frame())
+        }
+        Client::GetName => Ok(session
+            .name()
+            .map_or(OwnedFrame::Null, |name| name.as_frame())),
+        Client::Id => Ok((session.id() as i64).as_frame()),
+        Client::List { kind, ids } => {
+            let mut list = String::new();
+            if kind.is_none_or(|kind| kind == ClientType::Normal) {
+                let listed = sessions(clients, session)
+                    .into_iter()
+                    .filter(|client| ids.is_empty() || ids.contains(&client.id()));
+                for client in listed {
+                    list.push_str(&describe(&client, now));
+                    list.push('\n');
+                }
+            }
+            Ok(list.as_frame())
+        }
+        Client::Info => Ok(format!("{}\n", describe(session, now)).as_frame()),
+        Client::KillAddr(addr) => {
+            let target = sessions(clients, session)
+                .into_iter()
+                .find(|client| client.addr().to_string() == *addr);
+            match target {
+                Some(client) => {
+                    kill(clients, session, client.id());
+                    Ok("OK".as_frame())
+                }
+                None => Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR No such client",
+                )),
+            }
+        }
+        Client::Kill(filters) => {
+            let skip_me = KillFilter::SkipMe(true);
+            let filters: Vec<&KillFilter> = match filters
+                .iter()
+                .any(|filter| matches!(filter, KillFilter::SkipMe(_)))
+            {
+                true => filters.iter().collect(),
+                false => filters.iter().chain([&skip_me]).collect(),
+            };
+
+            let targets: Vec<u64> = sessions(clients, session)
+                .into_iter()
+                .filter(|client| {
+                    filters
+                        .iter()
+                        .all(|filter| filter_matches(filter, client, session, now))
+                })
+                .map(|client| client.id())
+                .collect();
+            for id in &targets {
+                kill(clients, session, *id);
+            }
+            Ok((targets.len() as i64).as_frame())
+        }
+        Client::Pause { timeout, mode } => {
+            clients.pause(Duration::from_millis(*timeout), *mode);
+            Ok("OK".as_frame())
+        }
+        Client::Unpause => {
+            clients.unpause();
+            Ok("OK".as_frame())
+        }
+        Client::NoEvict(no_evict) => {
+            session.set_no_evict(*no_evict);
+            Ok("OK".as_frame())
+        }
+        Client::SetInfo { attribute, value } => {
+            let value = Some(value.clone()).filter(|value| !value.is_empty());
+            match attribute {
+                LibAttribute::LibName => {
+                    if let Some(name) = &value {
+                        validate(name, "lib-name")?;
+                    }
+                    session.set_lib_name(value)
+                }
+                LibAttribute::LibVer => {
+                    if let Some(version) = &value {
+                        validate(version, "lib-ver")?;
+                    }
+                    session.set_lib_version(value)
+                }
+            }
+            Ok("OK".as_frame())
+        }
+    }
+}
+
+/// Every registered session, with `session` in place of its own registered copy. The current
+/// connection is included even if it is not registered, e.g. in tests.
+fn sessions(clients: &ClientRegistry, session: &Session) -> Vec<Session> {
+    let mut sessions: Vec<Session> = clients
+        .sessions()
+        .into_iter()
+        .filter(|client| client.id() != session.id())
+        .collect();
+    sessions.push(session.clone());
+    sessions.sort_by_key(Session::id);
+    sessions
+}
+
+/// Kill the connection `id`. The current connection is closed after the reply was sent.
+fn kill(clients: &ClientRegistry, session: &mut Session, id: u64) {
+    if id == session.id() {
+        session.close();
+    }
+    clients.kill(id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(args: &[&str]) -> Result<Request, RedisProtocolError> {
+        parse(
+            args.iter()
+                .map(|arg| Bytes::from(arg.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_client() {
+        assert!(matches!(
+            client(&["pause", "100", "write"]),
+            Ok(Request::CLIENT(Client::Pause {
+                timeout: 100,
+                mode: PauseMode::Write
+            }))
+        ));
+        assert!(matches!(
+            client(&["LIST", "ID", "1", "2"]),
+            Ok(Request::CLIENT(Client::List { kind: None, ids })) if ids == [1, 2]
+        ));
+        assert!(matches!(
+            client(&["KILL", "127.0.0.1:6379"]),
+            Ok(Request::CLIENT(Client::KillAddr(_)))
+        ));
+        assert_eq!(
+            client(&["PAUSE", "-1"]).unwrap_err().details(),
+            error_negative_timeout().details()
+        );
+        assert_eq!(
+            client(&["KILL", "ID", "1", "SKIPME"])
+                .unwrap_err()
+                .details(),
+            "ERR syntax error"
+        );
+    }
+
+    #[test]
+    fn kill_and_list_clients() {
+        let clients = ClientRegistry::new();
+        let mut me = Session::new(1, "127.0.0.1:5001".parse().unwrap());
+        let other = Session::new(2, "127.0.0.1:5002".parse().unwrap());
+        clients.register(&me);
+        let mut killed = clients.register(&other);
+
+        let setname = Request::CLIENT(Client::SetName("me".into()));
+        handle(&clients, &mut me, &setname).unwrap();
+        let invalid = Request::CLIENT(Client::SetName("with space".into()));
+        assert!(handle(&clients, &mut me, &invalid).is_err());
+
+        let list = Request::CLIENT(Client::List {
+            kind: None,
+            ids: Vec::new(),
+        });
+        let OwnedFrame::BlobString { data, .. } = handle(&clients, &mut me, &list).unwrap() else {
+            panic!("CLIENT LIST should return a string")
+        };
+        let data = String::from_utf8(data).unwrap();
+        assert_eq!(data.lines().count(), 2);
+        assert!(data.starts_with("id=1 addr=127.0.0.1:5001 name=me "));
+
+        /* SKIPME is on by default */
+        let kill = Request::CLIENT(Client::Kill(vec![KillFilter::MaxAge(0)]));
+        assert_eq!(handle(&clients, &mut me, &kill).unwrap(), 1.as_frame());
+        assert!(*killed.borrow_and_update());
+        assert!(!me.is_closing());
+    }
+}
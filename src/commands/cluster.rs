@@ -1,7 +1,8 @@
 use crate::commands::parse::Request;
 use crate::util::convert::map_to_array;
-use crate::util::convert::AsFrame;
+use crate::util::convert::{bytes_to_string, AsFrame};
 use crate::util::errors;
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use redis_protocol::types::REDIS_CLUSTER_SLOTS;
@@ -17,8 +18,8 @@ pub enum Cluster {
     SLOTS,
 }
 
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
-    let mut iter = args.iter();
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut iter = args.iter().map(|arg| bytes_to_string(arg));
     let subcommand = iter.next();
     if subcommand.is_none() {
         return Err(RedisProtocolError::new(
@@ -80,7 +81,7 @@ pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError>
 /// A list of shards with each shard being a list of nodes and
 /// a list of HashSlots they serve.
 ///
-/// ```
+/// ```text
 /// |-----------------------------------------------|
 /// | 1)    |-----------------------------------|   |
 /// |       |1) "slots"                         |   |
@@ -110,7 +111,7 @@ fn default_handle_shards() -> Result<OwnedFrame, RedisProtocolError> {
 /// List of (start, end) tuples of the cluster ranges
 ///
 /// # Returns
-/// ```
+/// ```text
 /// 1) "slots"
 /// 2)  1)  (integer) slots.first.0
 ///     2)  (integer) slots.first.1
@@ -1,7 +1,8 @@
 use crate::commands::command::Command::*;
 use crate::commands::parse::Request;
 use crate::commands::parse::Request::COMMAND;
-use crate::util::convert::AsFrame;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
@@ -23,7 +24,8 @@ pub enum Command {
     LIST,
 }
 
-pub fn parse(mut args: Vec<String>) -> Result<Request, RedisProtocolError> {
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut args: Vec<String> = args.iter().map(|arg| bytes_to_string(arg)).collect();
     let mut iter = args.iter();
     let subcommand = iter.next();
     let ret;
@@ -89,7 +91,7 @@ fn handle_cmd(
 /// Return documentary information about commands
 ///
 /// # Syntax
-/// ```
+/// ```text
 /// COMMAND DOCS [command-name [command-name ...]]
 /// ```
 fn handle_docs(
@@ -1,6 +1,8 @@
 use crate::commands::parse::Request;
-use crate::util::convert::map_to_array;
+use crate::util::convert::{bytes_to_string, map_to_array};
 use crate::util::errors::{error_too_few_arguments, error_unsupported_command};
+use crate::util::glob;
+use bytes::Bytes;
 use redis_protocol::error::RedisProtocolError;
 use redis_protocol::resp3::types::OwnedFrame;
 use std::collections::HashMap;
@@ -29,7 +31,14 @@ impl ConfigGet {
     }
 }
 
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+impl Default for ConfigGet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let args: Vec<String> = args.iter().map(|arg| bytes_to_string(arg)).collect();
     if args.is_empty() {
         return Err(error_too_few_arguments("CONFIG", Some(1)));
     }
@@ -45,17 +54,13 @@ fn parse_config_get(args: &[String]) -> Result<Request, RedisProtocolError> {
         return Err(error_too_few_arguments("CONFIG GET", Some(1)));
     }
 
+    /* Like Redis, parameters are glob-style patterns matched regardless of case, and patterns
+     * matching no parameter are ignored */
     let mut config = ConfigGet::new();
     for arg in args {
-        match arg.to_lowercase().as_ref() {
-            "save" => config.save = true,
-            "appendonly" => config.appendonly = true,
-            unknown => {
-                return Err(error_unsupported_command(
-                    &("CONFIG GET ".to_owned() + unknown),
-                ))
-            }
-        }
+        let pattern = arg.as_bytes();
+        config.save |= glob::matches_nocase(pattern, b"save");
+        config.appendonly |= glob::matches_nocase(pattern, b"appendonly");
     }
 
     Ok(Request::CONFIG(Config::Get(config)))
@@ -73,14 +78,38 @@ pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError>
 
 pub fn default_handle_config_get(args: &ConfigGet) -> Result<OwnedFrame, RedisProtocolError> {
     let mut config_map: HashMap<&str, &str> = HashMap::new();
-    
+
     if args.save {
         config_map.insert("save", "");
     }
-    
+
     if args.appendonly {
         config_map.insert("appendonly", "no");
     }
-    
+
     Ok(map_to_array(config_map))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_get(args: &[&'static str]) -> OwnedFrame {
+        let args = std::iter::once("GET")
+            .chain(args.iter().copied())
+            .map(Bytes::from)
+            .collect();
+        default_handle(&parse(args).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn get_unknown_parameters() {
+        let empty = OwnedFrame::Array {
+            data: Vec::new(),
+            attributes: None,
+        };
+        assert_eq!(config_get(&["maxmem*"]), empty);
+        assert_eq!(config_get(&["nosuchparameter"]), empty);
+        assert_ne!(config_get(&["maxmem*", "SAVE"]), empty);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::commands::parse::Request;
+use crate::server::session::Session;
+use crate::storage::databases::Databases;
+use crate::storage::KeyValueStore;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_db_index_out_of_range, error_not_integer, error_same_object, error_syntax,
+    error_unsupported_command, error_wrong_arity,
+};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+
+/// Whether `FLUSHDB` and `FLUSHALL` return before or after the keys are removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlushMode {
+    #[default]
+    Sync,
+    Async,
+}
+
+/// Parse MOVE, SWAPDB, FLUSHDB, FLUSHALL and DBSIZE
+///
+/// # Syntax
+/// ```text
+/// MOVE key db
+/// SWAPDB index1 index2
+/// FLUSHDB [ASYNC | SYNC]
+/// FLUSHALL [ASYNC | SYNC]
+/// DBSIZE
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let arity = match command {
+        "MOVE" | "SWAPDB" => 2..=2,
+        "FLUSHDB" | "FLUSHALL" => 0..=1,
+        "DBSIZE" => 0..=0,
+        _ => return Err(error_unsupported_command(command)),
+    };
+    if !arity.contains(&args.len()) {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut args = args.into_iter();
+    Ok(match command {
+        "MOVE" => Request::MOVE {
+            key: args.next().unwrap(),
+            db: parse_index(&args.next().unwrap()).ok_or_else(error_not_integer)?,
+        },
+        "SWAPDB" => Request::SWAPDB {
+            index1: parse_index(&args.next().unwrap()).ok_or_else(|| invalid_index("first"))?,
+            index2: parse_index(&args.next().unwrap()).ok_or_else(|| invalid_index("second"))?,
+        },
+        "FLUSHDB" => Request::FLUSHDB(parse_flush_mode(args.next())?),
+        "FLUSHALL" => Request::FLUSHALL(parse_flush_mode(args.next())?),
+        _ => Request::DBSIZE,
+    })
+}
+
+/// Parse a database index. Negative indices parse as well, so they fail with the same error
+/// as indices that are too large.
+fn parse_index(index: &[u8]) -> Option<i64> {
+    bytes_to_string(index).parse().ok()
+}
+
+fn invalid_index(which: &str) -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        format!("ERR invalid {which} DB index"),
+    )
+}
+
+fn parse_flush_mode(mode: Option<Bytes>) -> Result<FlushMode, RedisProtocolError> {
+    match mode
+        .map(|mode| bytes_to_string(&mode).to_uppercase())
+        .as_deref()
+    {
+        None | Some("SYNC") => Ok(FlushMode::Sync),
+        Some("ASYNC") => Ok(FlushMode::Async),
+        Some(_) => Err(error_syntax()),
+    }
+}
+
+/// Handle database commands for a server with a single, empty database 0
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::MOVE { db, .. } if *db == 0 => Err(error_same_object()),
+        Request::MOVE { .. } => Err(error_db_index_out_of_range()),
+        Request::SWAPDB { index1, index2 } if *index1 == 0 && *index2 == 0 => Ok("OK".as_frame()),
+        Request::SWAPDB { .. } => Err(error_db_index_out_of_range()),
+        Request::FLUSHDB(_) | Request::FLUSHALL(_) => Ok("OK".as_frame()),
+        Request::DBSIZE => Ok(0.as_frame()),
+        _ => panic!(
+            "Expected enum variant MOVE, SWAPDB, FLUSHDB, FLUSHALL or DBSIZE, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Handle MOVE, SWAPDB, FLUSHDB, FLUSHALL and DBSIZE.
+///
+/// # Arguments
+///
+///  * `databases` - All databases of the server
+///  * `session` - The connection issuing the request, whose selected database is used
+///  * `args` - The parsed request
+pub fn handle(
+    databases: &Databases,
+    session: &Session,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let database = |index: i64| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| databases.get(index))
+            .ok_or_else(error_db_index_out_of_range)
+    };
+    let current = database(session.db() as i64)?;
+
+    match args {
+        Request::MOVE { key, db } => {
+            let target = database(*db)?;
+            if *db as usize == session.db() {
+                return Err(error_same_object());
+            }
+            Ok((move_key(&mut current.as_ref(), &mut target.as_ref(), key) as i64).as_frame())
+        }
+        Request::SWAPDB { index1, index2 } => {
+            let (index1, index2) = (database_index(*index1)?, database_index(*index2)?);
+            if databases.swap(index1, index2) {
+                Ok("OK".as_frame())
+            } else {
+                Err(error_db_index_out_of_range())
+            }
+        }
+        Request::FLUSHDB(_) => {
+            current.clear();
+            Ok("OK".as_frame())
+        }
+        Request::FLUSHALL(_) => {
+            databases.clear();
+            Ok("OK".as_frame())
+        }
+        Request::DBSIZE => Ok((current.len() as i64).as_frame()),
+        _ => panic!(
+            "Expected enum variant MOVE, SWAPDB, FLUSHDB, FLUSHALL or DBSIZE, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+fn database_index(index: i64) -> Result<usize, RedisProtocolError> {
+    usize::try_from(index).map_err(|_| error_db_index_out_of_range())
+}
+
+/// Move `key` with its time to live from `source` to `target`, unless `target` already
+/// holds it.
+///
+/// # Returns
+/// Whether the key was moved
+pub fn move_key<S: KeyValueStore, T: KeyValueStore>(
+    source: &mut S,
+    target: &mut T,
+    key: &Bytes,
+) -> bool {
+    if target.exists(key) {
+        return false;
+    }
+    let Some(entry) = source.update(key.clone(), Option::take) else {
+        return false;
+    };
+
+    /* The key might have been created in the meantime, in that case the move is undone */
+    let rejected = target.update(key.clone(), |existing| match existing {
+        Some(_) => Some(entry),
+        None => {
+            *existing = Some(entry);
+            None
+        }
+    });
+    match rejected {
+        Some(entry) => {
+            source.update(key.clone(), |restored| {
+                restored.get_or_insert(entry);
+            });
+            false
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::parse::parse;
+
+    fn query(args: &[&str]) -> Vec<Bytes> {
+        args.iter()
+            .map(|arg| Bytes::from(arg.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn move_swap_and_flush() {
+        let databases = Databases::new(4);
+        let mut session = Session::new(1, "127.0.0.1:6379".parse().unwrap());
+        databases
+            .get(0)
+            .unwrap()
+            .set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+
+        let run = |session: &Session, args: &[&str]| {
+            handle(&databases, session, &parse(query(args)).unwrap())
+        };
+
+        assert_eq!(run(&session, &["MOVE", "key", "2"]).unwrap(), 1.as_frame());
+        assert_eq!(run(&session, &["MOVE", "key", "2"]).unwrap(), 0.as_frame());
+        assert!(run(&session, &["MOVE", "key", "4"]).is_err());
+        assert!(run(&session, &["MOVE", "key", "0"]).is_err());
+
+        assert_eq!(
+            run(&session, &["SWAPDB", "0", "2"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert_eq!(run(&session, &["DBSIZE"]).unwrap(), 1.as_frame());
+
+        session.select(3);
+        databases
+            .get(3)
+            .unwrap()
+            .set(Bytes::from_static(b"other"), Bytes::new());
+        assert_eq!(run(&session, &["FLUSHDB"]).unwrap(), "OK".as_frame());
+        assert_eq!(run(&session, &["DBSIZE"]).unwrap(), 0.as_frame());
+        assert!(databases.get(0).unwrap().exists(b"key"));
+
+        run(&session, &["FLUSHALL", "ASYNC"]).unwrap();
+        assert!(databases.get(0).unwrap().is_empty());
+
+        assert_eq!(
+            parse(query(&["SWAPDB", "a", "1"])).unwrap_err().details(),
+            "ERR invalid first DB index"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::KeyValueStore;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{error_invalid_expire, error_not_integer, error_wrong_arity};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Only change the time to live if the condition holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExpireCondition {
+    /// The key has no time to live
+    Nx,
+    /// The key has a time to live
+    Xx,
+    /// The new deadline is later than the current one
+    Gt,
+    /// The new deadline is earlier than the current one
+    Lt,
+}
+
+/// Parse one of the commands related to key expiration
+///
+/// # Syntax
+/// ```text
+/// EXPIRE key seconds [NX | XX | GT | LT]
+/// PEXPIRE key milliseconds [NX | XX | GT | LT]
+/// EXPIREAT key unix-time-seconds [NX | XX | GT | LT]
+/// PEXPIREAT key unix-time-milliseconds [NX | XX | GT | LT]
+/// TTL key
+/// PTTL key
+/// PERSIST key
+/// EXPIRETIME key
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "TTL" | "PTTL" | "PERSIST" | "EXPIRETIME" => args.len() == 1,
+        _ => args.len() >= 2,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().expect("Arity was checked");
+
+    let request = match command {
+        "TTL" => Request::TTL { key },
+        "PTTL" => Request::PTTL { key },
+        "PERSIST" => Request::PERSIST { key },
+        "EXPIRETIME" => Request::EXPIRETIME { key },
+        _ => {
+            let time = iter.next().expect("Arity was checked");
+            let time: i64 = bytes_to_string(&time)
+                .parse()
+                .map_err(|_| error_not_integer())?;
+            let condition = parse_condition(iter.by_ref())?;
+
+            match command {
+                "EXPIRE" => Request::EXPIRE {
+                    key,
+                    seconds: time,
+                    condition,
+                },
+                "PEXPIRE" => Request::PEXPIRE {
+                    key,
+                    milliseconds: time,
+                    condition,
+                },
+                "EXPIREAT" => Request::EXPIREAT {
+                    key,
+                    timestamp: time,
+                    condition,
+                },
+                "PEXPIREAT" => Request::PEXPIREAT {
+                    key,
+                    timestamp: time,
+                    condition,
+                },
+                _ => panic!("Expected expiration command, but got {command}"),
+            }
+        }
+    };
+
+    Ok(request)
+}
+
+fn parse_condition(
+    args: impl Iterator<Item = Bytes>,
+) -> Result<Option<ExpireCondition>, RedisProtocolError> {
+    let (mut nx, mut xx, mut gt, mut lt) = (false, false, false, false);
+    for arg in args {
+        match bytes_to_string(&arg).to_uppercase().as_str() {
+            "NX" => nx = true,
+            "XX" => xx = true,
+            "GT" => gt = true,
+            "LT" => lt = true,
+            option => {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    format!("ERR Unsupported option {option}"),
+                ))
+            }
+        }
+    }
+
+    if nx && (xx || gt || lt) {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR NX and XX, GT or LT options at the same time are not compatible",
+        ));
+    }
+    if gt && lt {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR GT and LT options at the same time are not compatible",
+        ));
+    }
+
+    Ok(match (nx, xx, gt, lt) {
+        (true, ..) => Some(ExpireCondition::Nx),
+        (_, _, true, _) => Some(ExpireCondition::Gt),
+        (_, _, _, true) => Some(ExpireCondition::Lt),
+        (_, true, ..) => Some(ExpireCondition::Xx),
+        _ => None,
+    })
+}
+
+/// Handle expiration commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle `EXPIRE`, `PEXPIRE`, `EXPIREAT`, `PEXPIREAT`, `TTL`, `PTTL`, `PERSIST` and
+/// `EXPIRETIME`.
+///
+/// # Returns
+///  * `EXPIRE` and variants: `1` if the time to live was set, `0` if the key does not exist or
+///    the condition was not met. Deadlines in the past delete the key.
+///  * `TTL`, `PTTL`, `EXPIRETIME`: the remaining time or the deadline, `-1` if the key has no
+///    time to live and `-2` if the key does not exist
+///  * `PERSIST`: `1` if the time to live was removed, `0` otherwise
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let now = unix_millis(SystemTime::now());
+
+    match args {
+        Request::EXPIRE {
+            key,
+            seconds: time,
+            condition,
+        }
+        | Request::EXPIREAT {
+            key,
+            timestamp: time,
+            condition,
+        } => {
+            let (command, base) = match args {
+                Request::EXPIRE { .. } => ("expire", Some(now)),
+                _ => ("expireat", None),
+            };
+            let millis = time
+                .checked_mul(1000)
+                .ok_or_else(|| error_invalid_expire(command))?;
+            expire(values, command, key, base, millis, *condition, now)
+        }
+        Request::PEXPIRE {
+            key,
+            milliseconds: millis,
+            condition,
+        } => expire(values, "pexpire", key, Some(now), *millis, *condition, now),
+        Request::PEXPIREAT {
+            key,
+            timestamp: millis,
+            condition,
+        } => expire(values, "pexpireat", key, None, *millis, *condition, now),
+        Request::TTL { key } | Request::PTTL { key } | Request::EXPIRETIME { key } => {
+            let Some(entry) = values.entry(key) else {
+                return Ok((-2).as_frame());
+            };
+            let Some(deadline) = entry.expires.map(unix_millis) else {
+                return Ok((-1).as_frame());
+            };
+
+            let remaining = (deadline - now).max(0);
+            let reply = match args {
+                Request::TTL { .. } => (remaining + 500) / 1000,
+                Request::PTTL { .. } => remaining,
+                _ => deadline / 1000,
+            };
+            Ok((reply as i64).as_frame())
+        }
+        Request::PERSIST { key } => {
+            let persisted = values.update(key.clone(), |entry| match entry {
+                Some(entry) => entry.expires.take().is_some(),
+                None => false,
+            });
+            Ok((persisted as i64).as_frame())
+        }
+        _ => panic!(
+            "Expected expiration enum variant, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Set the deadline of `key` to `base + millis`, or to `millis` since the unix epoch if there is
+/// no `base`.
+fn expire<S: KeyValueStore>(
+    values: &mut S,
+    command: &str,
+    key: &Bytes,
+    base: Option<i128>,
+    millis: i64,
+    condition: Option<ExpireCondition>,
+    now: i128,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let deadline = base.unwrap_or(0) + millis as i128;
+    if deadline > i64::MAX as i128 {
+        return Err(error_invalid_expire(command));
+    }
+
+    let updated = values.update(key.clone(), |entry| {
+        let Some(current) = entry.as_mut() else {
+            return false;
+        };
+
+        let allowed = condition_met(condition, current.expires.map(unix_millis), deadline);
+        if allowed {
+            if deadline <= now {
+                *entry = None;
+            } else {
+                current.expires = Some(UNIX_EPOCH + Duration::from_millis(deadline as u64));
+            }
+        }
+        allowed
+    });
+
+    Ok((updated as i64).as_frame())
+}
+
+/// Whether `condition` allows replacing the `current` deadline with `deadline`, both in
+/// milliseconds since the unix epoch
+pub(crate) fn condition_met(
+    condition: Option<ExpireCondition>,
+    current: Option<i128>,
+    deadline: i128,
+) -> bool {
+    match condition {
+        None => true,
+        Some(ExpireCondition::Nx) => current.is_none(),
+        Some(ExpireCondition::Xx) => current.is_some(),
+        /* Without time to live, the deadline counts as infinitely late */
+        Some(ExpireCondition::Gt) => current.is_some_and(|c| deadline > c),
+        Some(ExpireCondition::Lt) => current.is_none_or(|c| deadline < c),
+    }
+}
+
+/// Milliseconds between the unix epoch and `time`, negative for times before the epoch
+pub(crate) fn unix_millis(time: SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as i128,
+        Err(e) => -(e.duration().as_millis() as i128),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(command: &str, args: &[&'static str]) -> Request {
+        parse(
+            command,
+            args.iter()
+                .map(|arg| Bytes::from_static(arg.as_bytes()))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_conflicting_conditions() {
+        let args = |args: &[&'static str]| {
+            parse(
+                "EXPIRE",
+                args.iter()
+                    .map(|arg| Bytes::from_static(arg.as_bytes()))
+                    .collect(),
+            )
+        };
+
+        assert!(args(&["key", "10", "GT", "XX"]).is_ok());
+        assert!(args(&["key", "10", "NX", "XX"]).is_err());
+        assert!(args(&["key", "10", "GT", "LT"]).is_err());
+        assert!(args(&["key", "ten"]).is_err());
+        assert!(args(&["key"]).is_err());
+    }
+
+    #[test]
+    fn errors_name_the_command() {
+        let arity = parse("TTL", vec![]).unwrap_err();
+        assert_eq!(arity.details(), error_wrong_arity("ttl").details());
+        let arity = parse(
+            "PTTL",
+            vec![Bytes::from_static(b"a"), Bytes::from_static(b"b")],
+        );
+        assert_eq!(
+            arity.unwrap_err().details(),
+            error_wrong_arity("pttl").details()
+        );
+
+        let mut store = MemoryStore::new();
+        store.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+        for (command, time) in [
+            ("EXPIRE", "9223372036854775"),
+            ("EXPIREAT", "9223372036854776"),
+            ("PEXPIRE", "9223372036854775807"),
+        ] {
+            let error = handle(&mut store, &request(command, &["key", time])).unwrap_err();
+            assert_eq!(error.details(), error_invalid_expire(command).details());
+        }
+    }
+
+    #[test]
+    fn expire_ttl_and_persist() {
+        let mut store = MemoryStore::new();
+        store.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+
+        let ttl = request("TTL", &["key"]);
+        assert_eq!(handle(&mut store, &ttl).unwrap(), (-1).as_frame());
+        assert_eq!(
+            handle(&mut store, &request("TTL", &["missing"])).unwrap(),
+            (-2).as_frame()
+        );
+
+        let expire = request("EXPIRE", &["key", "100"]);
+        assert_eq!(handle(&mut store, &expire).unwrap(), 1.as_frame());
+        assert_eq!(handle(&mut store, &ttl).unwrap(), 100.as_frame());
+
+        let nx = request("EXPIRE", &["key", "200", "NX"]);
+        assert_eq!(handle(&mut store, &nx).unwrap(), 0.as_frame());
+        let lt = request("PEXPIRE", &["key", "50000", "LT"]);
+        assert_eq!(handle(&mut store, &lt).unwrap(), 1.as_frame());
+        assert_eq!(handle(&mut store, &ttl).unwrap(), 50.as_frame());
+
+        let persist = request("PERSIST", &["key"]);
+        assert_eq!(handle(&mut store, &persist).unwrap(), 1.as_frame());
+        assert_eq!(handle(&mut store, &persist).unwrap(), 0.as_frame());
+        assert_eq!(handle(&mut store, &ttl).unwrap(), (-1).as_frame());
+
+        let gt = request("EXPIRE", &["key", "100", "GT"]);
+        assert_eq!(handle(&mut store, &gt).unwrap(), 0.as_frame());
+    }
+
+    #[test]
+    fn expire_in_the_past_deletes() {
+        let mut store = MemoryStore::new();
+        store.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+
+        let expire = request("EXPIREAT", &["key", "1"]);
+        assert_eq!(handle(&mut store, &expire).unwrap(), 1.as_frame());
+        assert!(!store.exists(b"key"));
+        assert_eq!(handle(&mut store, &expire).unwrap(), 0.as_frame());
+    }
+
+    #[test]
+    fn expiretime() {
+        let mut store = MemoryStore::new();
+        store.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+
+        handle(&mut store, &request("EXPIREAT", &["key", "33177117420"])).unwrap();
+        assert_eq!(
+            handle(&mut store, &request("EXPIRETIME", &["key"])).unwrap(),
+            33177117420i64.as_frame()
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::KeyValueStore;
 use crate::util::convert::AsFrame;
+use crate::util::errors::error_wrong_type;
+use bytes::Bytes;
 use log::debug;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
 use std::collections::HashMap;
 
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
     if args.len() != 1 {
         return Err(RedisProtocolError::new(
             RedisProtocolErrorKind::Parse,
@@ -15,45 +20,46 @@ pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
     }
 
     let key = args
-        .first()
+        .into_iter()
+        .next()
         .expect("Failed to fetch first element of array");
-    Ok(Request::GET {
-        key: String::from(key),
-    })
+    Ok(Request::GET { key })
 }
 
 /// Handle `GET` requests with a default set of key/value-pairs.
 /// See [`handle`]
-#[allow(dead_code)]
-pub fn default_handle(args: Request) -> Result<OwnedFrame, RedisProtocolError> {
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
     debug!("Providing default values to handle GET");
 
-    let default_values: HashMap<String, String> =
-        HashMap::from([("mykey".into(), "myvalue".into())]);
+    let default_values = MemoryStore::from(HashMap::from([(
+        Bytes::from_static(b"mykey"),
+        Bytes::from_static(b"myvalue"),
+    )]));
 
-    handle(&default_values, &args)
+    handle(&default_values, args)
 }
 
 /// Handle redis GET requests.
 ///
 /// # Arguments
 ///
-///  * `values` - [`HashMap`] storing keys and values
+///  * `values` - [`KeyValueStore`] storing keys and values
 ///  * `args` - The requested key
 ///
 /// # Returns
 ///  * [`OwnedFrame`] containing the value for the given key or [`OwnedFrame::Null`] if the key is
-///     not inside `values`
-pub fn handle<V: AsFrame + Clone>(
-    values: &HashMap<String, V>,
+///    not inside `values`
+///  * A `WRONGTYPE` error if the key does not hold a string
+pub fn handle<S: KeyValueStore>(
+    values: &S,
     args: &Request,
 ) -> Result<OwnedFrame, RedisProtocolError> {
     if let Request::GET { ref key } = args {
-        if let Some(value) = values.get(key) {
-            Ok(value.as_frame())
-        } else {
-            Ok(OwnedFrame::Null)
-        }
+        values.read(key, |value| match value {
+            Some(Value::String(value)) => Ok(value.as_frame()),
+            Some(_) => Err(error_wrong_type()),
+            None => Ok(OwnedFrame::Null),
+        })
     } else {
         panic!("Expected enum variant GET, but got {:?}", args.type_id())
     }
@@ -0,0 +1,960 @@
+use crate::commands::expire::{condition_met, unix_millis, ExpireCondition};
+use crate::commands::incr::error_not_float;
+use crate::commands::parse::Request;
+use crate::commands::scan::{matches, DEFAULT_COUNT};
+use crate::storage::hash::Hash;
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_f64, bytes_to_i64, bytes_to_string, f64_to_string, AsFrame};
+use crate::util::errors::{
+    error_increment_not_finite, error_increment_overflow, error_invalid_cursor,
+    error_invalid_expire, error_not_integer, error_syntax, error_wrong_arity, error_wrong_type,
+};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Latest deadline of a field in milliseconds since the unix epoch, like in Redis
+const MAX_FIELD_DEADLINE: i128 = (1 << 48) - 1;
+
+/// Parse one of the hash commands
+///
+/// # Syntax
+/// ```text
+/// HSET key field value [field value ...]
+/// HMSET key field value [field value ...]
+/// HSETNX key field value
+/// HGET key field
+/// HMGET key field [field ...]
+/// HDEL key field [field ...]
+/// HLEN key
+/// HSTRLEN key field
+/// HEXISTS key field
+/// HKEYS key
+/// HVALS key
+/// HGETALL key
+/// HINCRBY key field increment
+/// HINCRBYFLOAT key field increment
+/// HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+/// HEXPIRE key seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+/// HPEXPIRE key milliseconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+/// HEXPIREAT key unix-time-seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+/// HPEXPIREAT key unix-time-milliseconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+/// HTTL key FIELDS numfields field [field ...]
+/// HPTTL key FIELDS numfields field [field ...]
+/// HEXPIRETIME key FIELDS numfields field [field ...]
+/// HPEXPIRETIME key FIELDS numfields field [field ...]
+/// HPERSIST key FIELDS numfields field [field ...]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "HSET" | "HMSET" => args.len() >= 3 && !args.len().is_multiple_of(2),
+        "HSETNX" | "HINCRBY" | "HINCRBYFLOAT" => args.len() == 3,
+        "HGET" | "HSTRLEN" | "HEXISTS" => args.len() == 2,
+        "HLEN" | "HKEYS" | "HVALS" | "HGETALL" => args.len() == 1,
+        "HMGET" | "HDEL" | "HSCAN" => args.len() >= 2,
+        "HEXPIRE" | "HPEXPIRE" | "HEXPIREAT" | "HPEXPIREAT" => args.len() >= 5,
+        _ => args.len() >= 4,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().unwrap();
+    let request = match command {
+        "HSET" | "HMSET" => {
+            let mut pairs = Vec::new();
+            while let (Some(field), Some(value)) = (iter.next(), iter.next()) {
+                pairs.push((field, value));
+            }
+            match command {
+                "HSET" => Request::HSET { key, pairs },
+                _ => Request::HMSET { key, pairs },
+            }
+        }
+        "HSETNX" => Request::HSETNX {
+            key,
+            field: iter.next().unwrap(),
+            value: iter.next().unwrap(),
+        },
+        "HGET" => Request::HGET {
+            key,
+            field: iter.next().unwrap(),
+        },
+        "HMGET" => Request::HMGET {
+            key,
+            fields: iter.collect(),
+        },
+        "HDEL" => Request::HDEL {
+            key,
+            fields: iter.collect(),
+        },
+        "HLEN" => Request::HLEN { key },
+        "HSTRLEN" => Request::HSTRLEN {
+            key,
+            field: iter.next().unwrap(),
+        },
+        "HEXISTS" => Request::HEXISTS {
+            key,
+            field: iter.next().unwrap(),
+        },
+        "HKEYS" => Request::HKEYS { key },
+        "HVALS" => Request::HVALS { key },
+        "HGETALL" => Request::HGETALL { key },
+        "HINCRBY" => Request::HINCRBY {
+            key,
+            field: iter.next().unwrap(),
+            increment: bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?,
+        },
+        "HINCRBYFLOAT" => Request::HINCRBYFLOAT {
+            key,
+            field: iter.next().unwrap(),
+            increment: bytes_to_f64(&iter.next().unwrap()).ok_or_else(error_not_float)?,
+        },
+        "HSCAN" => {
+            let cursor = bytes_to_string(&iter.next().unwrap())
+                .parse()
+                .map_err(|_| error_invalid_cursor())?;
+
+            let (mut pattern, mut count, mut novalues) = (None, None, false);
+            while let Some(option) = iter.next() {
+                match bytes_to_string(&option).to_uppercase().as_str() {
+                    "MATCH" => pattern = Some(iter.next().ok_or_else(error_syntax)?),
+                    "COUNT" => {
+                        let value = iter.next().ok_or_else(error_syntax)?;
+                        let value = bytes_to_i64(&value).ok_or_else(error_not_integer)?;
+                        if value < 1 {
+                            return Err(error_syntax());
+                        }
+                        count = Some(value as usize);
+                    }
+                    "NOVALUES" => novalues = true,
+                    _ => return Err(error_syntax()),
+                }
+            }
+
+            Request::HSCAN {
+                key,
+                cursor,
+                pattern,
+                count,
+                novalues,
+            }
+        }
+        "HEXPIRE" | "HPEXPIRE" | "HEXPIREAT" | "HPEXPIREAT" => {
+            let time = bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?;
+            let mut iter = iter.peekable();
+            let condition = match iter.peek().map(|arg| bytes_to_string(arg).to_uppercase()) {
+                Some(option) if option == "NX" => Some(ExpireCondition::Nx),
+                Some(option) if option == "XX" => Some(ExpireCondition::Xx),
+                Some(option) if option == "GT" => Some(ExpireCondition::Gt),
+                Some(option) if option == "LT" => Some(ExpireCondition::Lt),
+                _ => None,
+            };
+            if condition.is_some() {
+                iter.next();
+            }
+            let fields = parse_fields(iter)?;
+
+            match command {
+                "HEXPIRE" => Request::HEXPIRE {
+                    key,
+                    seconds: time,
+                    condition,
+                    fields,
+                },
+                "HPEXPIRE" => Request::HPEXPIRE {
+                    key,
+                    milliseconds: time,
+                    condition,
+                    fields,
+                },
+                "HEXPIREAT" => Request::HEXPIREAT {
+                    key,
+                    timestamp: time,
+                    condition,
+                    fields,
+                },
+                _ => Request::HPEXPIREAT {
+                    key,
+                    timestamp: time,
+                    condition,
+                    fields,
+                },
+            }
+        }
+        "HTTL" => Request::HTTL {
+            key,
+            fields: parse_fields(iter)?,
+        },
+        "HPTTL" => Request::HPTTL {
+            key,
+            fields: parse_fields(iter)?,
+        },
+        "HEXPIRETIME" => Request::HEXPIRETIME {
+            key,
+            fields: parse_fields(iter)?,
+        },
+        "HPEXPIRETIME" => Request::HPEXPIRETIME {
+            key,
+            fields: parse_fields(iter)?,
+        },
+        "HPERSIST" => Request::HPERSIST {
+            key,
+            fields: parse_fields(iter)?,
+        },
+        _ => panic!("Expected hash command, but got {command}"),
+    };
+
+    Ok(request)
+}
+
+/// Parse `FIELDS numfields field [field ...]`
+fn parse_fields(mut args: impl Iterator<Item = Bytes>) -> Result<Vec<Bytes>, RedisProtocolError> {
+    if !args
+        .next()
+        .is_some_and(|arg| arg.eq_ignore_ascii_case(b"FIELDS"))
+    {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR Mandatory argument FIELDS is missing or not at the right position",
+        ));
+    }
+
+    let count = args
+        .next()
+        .and_then(|count| bytes_to_i64(&count))
+        .filter(|count| *count > 0)
+        .ok_or_else(|| {
+            RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                "ERR Parameter `numFields` should be greater than 0",
+            )
+        })?;
+
+    let fields: Vec<Bytes> = args.collect();
+    if fields.len() as i64 != count {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR The `numfields` parameter must match the number of arguments",
+        ));
+    }
+    Ok(fields)
+}
+
+/// Handle hash commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle the hash commands.
+///
+/// Keys that don't exist count as empty hashes, and a hash is removed with its last field.
+/// Fields can have a time to live of their own, which `HSET` and `HSETNX` remove and
+/// `HINCRBY` and `HINCRBYFLOAT` keep.
+///
+/// # Returns
+///  * `HSET`: the number of fields added
+///  * `HMSET`: `OK`
+///  * `HSETNX`, `HEXISTS`: `1` if the field was set or exists, `0` otherwise
+///  * `HGET`, `HMGET`: the value of every field, [`OwnedFrame::Null`] for missing fields
+///  * `HDEL`: the number of fields removed
+///  * `HLEN`, `HSTRLEN`: the number of fields, the length of the value
+///  * `HKEYS`, `HVALS`, `HGETALL`: every field, every value, or a map of both
+///  * `HINCRBY`, `HINCRBYFLOAT`: the new value
+///  * `HSCAN`: the cursor to continue with, `0` once the iteration is complete, and the fields
+///    found in this batch followed by their values unless `NOVALUES` is given
+///  * `HEXPIRE` and variants: for every field `1` if the time to live was set, `2` if the field
+///    was removed because the deadline has passed, `0` if the condition was not met and `-2`
+///    if the field does not exist
+///  * `HTTL`, `HPTTL`, `HEXPIRETIME`, `HPEXPIRETIME`: for every field the remaining time or
+///    the deadline, `-1` if the field has no time to live and `-2` if it does not exist
+///  * `HPERSIST`: for every field `1` if the time to live was removed, `-1` if the field has
+///    none and `-2` if it does not exist
+///
+/// All of them fail with `WRONGTYPE` if the key holds a value other than a hash.
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::HSET { key, pairs } | Request::HMSET { key, pairs } => {
+            let added = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let added = pairs
+                    .iter()
+                    .map(|(field, value)| hash.insert(field.clone(), value.clone()) as i64)
+                    .sum::<i64>();
+                Ok::<_, RedisProtocolError>(added)
+            })?;
+            match args {
+                Request::HSET { .. } => Ok(added.as_frame()),
+                _ => Ok("OK".as_frame()),
+            }
+        }
+        Request::HSETNX { key, field, value } => {
+            let added = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let added = !hash.contains(field) && hash.insert(field.clone(), value.clone());
+                Ok::<_, RedisProtocolError>(added)
+            })?;
+            Ok((added as i64).as_frame())
+        }
+        Request::HGET { key, field } => values.read(key, |value| {
+            Ok(hash(value)?
+                .and_then(|hash| hash.get(field))
+                .cloned()
+                .as_frame())
+        }),
+        Request::HMGET { key, fields } => values.read(key, |value| {
+            let hash = hash(value)?;
+            let found: Vec<Option<Bytes>> = fields
+                .iter()
+                .map(|field| hash.and_then(|hash| hash.get(field)).cloned())
+                .collect();
+            Ok(found.as_frame())
+        }),
+        Request::HDEL { key, fields } => {
+            let removed = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let removed = fields
+                    .iter()
+                    .map(|field| hash.remove(field) as i64)
+                    .sum::<i64>();
+                Ok::<_, RedisProtocolError>(removed)
+            })?;
+            Ok(removed.as_frame())
+        }
+        Request::HLEN { key } => values.read(key, |value| {
+            let length = hash(value)?.map_or(0, Hash::len);
+            Ok((length as i64).as_frame())
+        }),
+        Request::HSTRLEN { key, field } => values.read(key, |value| {
+            let length = hash(value)?
+                .and_then(|hash| hash.get(field))
+                .map_or(0, Bytes::len);
+            Ok((length as i64).as_frame())
+        }),
+        Request::HEXISTS { key, field } => values.read(key, |value| {
+            let exists = hash(value)?.is_some_and(|hash| hash.contains(field));
+            Ok((exists as i64).as_frame())
+        }),
+        Request::HKEYS { key } | Request::HVALS { key } => values.read(key, |value| {
+            let Some(hash) = hash(value)? else {
+                return Ok(Vec::<Bytes>::new().as_frame());
+            };
+            let items: Vec<Bytes> = match args {
+                Request::HKEYS { .. } => hash.iter().map(|(field, _)| field.clone()).collect(),
+                _ => hash.iter().map(|(_, value)| value.clone()).collect(),
+            };
+            Ok(items.as_frame())
+        }),
+        Request::HGETALL { key } => values.read(key, |value| {
+            let data = hash(value)?
+                .into_iter()
+                .flat_map(Hash::iter)
+                .map(|(field, value)| (field.as_frame(), value.as_frame()))
+                .collect();
+            Ok(OwnedFrame::Map {
+                data,
+                attributes: None,
+            })
+        }),
+        Request::HINCRBY {
+            key,
+            field,
+            increment,
+        } => {
+            let value = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let current = match hash.get(field) {
+                    Some(value) => bytes_to_i64(value).ok_or_else(|| {
+                        RedisProtocolError::new(
+                            RedisProtocolErrorKind::Parse,
+                            "ERR hash value is not an integer",
+                        )
+                    })?,
+                    None => 0,
+                };
+                let value = current
+                    .checked_add(*increment)
+                    .ok_or_else(error_increment_overflow)?;
+                store(hash, field, value.to_string());
+                Ok::<_, RedisProtocolError>(value)
+            })?;
+            Ok(value.as_frame())
+        }
+        Request::HINCRBYFLOAT {
+            key,
+            field,
+            increment,
+        } => {
+            let value = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let current = match hash.get(field) {
+                    Some(value) => bytes_to_f64(value).ok_or_else(|| {
+                        RedisProtocolError::new(
+                            RedisProtocolErrorKind::Parse,
+                            "ERR hash value is not a float",
+                        )
+                    })?,
+                    None => 0.0,
+                };
+                let value = current + increment;
+                if !value.is_finite() {
+                    return Err(error_increment_not_finite());
+                }
+                Ok(store(hash, field, f64_to_string(value)))
+            })?;
+            Ok(value.as_frame())
+        }
+        Request::HSCAN {
+            key,
+            cursor,
+            pattern,
+            count,
+            novalues,
+        } => values.read(key, |value| {
+            let (next, batch) = match hash(value)? {
+                Some(hash) => hash.scan(*cursor, count.unwrap_or(DEFAULT_COUNT)),
+                None => (0, Vec::new()),
+            };
+
+            let mut items = Vec::new();
+            for (field, value) in batch {
+                if pattern.as_ref().is_none_or(|p| matches(p, &field)) {
+                    items.push(field);
+                    if !novalues {
+                        items.push(value);
+                    }
+                }
+            }
+
+            Ok(OwnedFrame::Array {
+                data: vec![next.to_string().as_frame(), items.as_frame()],
+                attributes: None,
+            })
+        }),
+        Request::HEXPIRE {
+            key,
+            seconds: time,
+            condition,
+            fields,
+        }
+        | Request::HPEXPIRE {
+            key,
+            milliseconds: time,
+            condition,
+            fields,
+        }
+        | Request::HEXPIREAT {
+            key,
+            timestamp: time,
+            condition,
+            fields,
+        }
+        | Request::HPEXPIREAT {
+            key,
+            timestamp: time,
+            condition,
+            fields,
+        } => {
+            let now = unix_millis(SystemTime::now());
+            let seconds = matches!(args, Request::HEXPIRE { .. } | Request::HEXPIREAT { .. });
+            let base = match args {
+                Request::HEXPIRE { .. } | Request::HPEXPIRE { .. } => now,
+                _ => 0,
+            };
+            let deadline = deadline(args.name(), *time, seconds, base)?;
+
+            let replies = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let replies: Vec<i64> = fields
+                    .iter()
+                    .map(|field| expire_field(hash, field, *condition, deadline, now))
+                    .collect();
+                Ok::<_, RedisProtocolError>(replies)
+            })?;
+            Ok(replies.as_frame())
+        }
+        Request::HTTL { key, fields }
+        | Request::HPTTL { key, fields }
+        | Request::HEXPIRETIME { key, fields }
+        | Request::HPEXPIRETIME { key, fields } => values.read(key, |value| {
+            let hash = hash(value)?;
+            let now = unix_millis(SystemTime::now());
+            let replies: Vec<i64> = fields
+                .iter()
+                .map(|field| match hash.and_then(|hash| hash.expires(field)) {
+                    None => -2,
+                    Some(None) => -1,
+                    Some(Some(deadline)) => {
+                        let deadline = unix_millis(deadline);
+                        /* Like in Redis, seconds are rounded up */
+                        let reply = match args {
+                            Request::HTTL { .. } => (deadline - now + 999) / 1000,
+                            Request::HPTTL { .. } => deadline - now,
+                            Request::HEXPIRETIME { .. } => (deadline + 999) / 1000,
+                            _ => deadline,
+                        };
+                        reply as i64
+                    }
+                })
+                .collect();
+            Ok(replies.as_frame())
+        }),
+        Request::HPERSIST { key, fields } => {
+            let replies = values.update(key.clone(), |entry| {
+                let hash = hash_mut(entry)?;
+                let replies: Vec<i64> = fields
+                    .iter()
+                    .map(|field| match hash.expires(field) {
+                        None => -2,
+                        Some(None) => -1,
+                        Some(Some(_)) => {
+                            hash.expire(field, None);
+                            1
+                        }
+                    })
+                    .collect();
+                Ok::<_, RedisProtocolError>(replies)
+            })?;
+            Ok(replies.as_frame())
+        }
+        _ => panic!("Expected hash enum variant, but got {:?}", args.type_id()),
+    }
+}
+
+/// Return the hash `value`, `None` if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a hash
+pub(crate) fn hash(value: Option<&Value>) -> Result<Option<&Hash>, RedisProtocolError> {
+    match value {
+        Some(value) => value.as_hash().map(Some).ok_or_else(error_wrong_type),
+        None => Ok(None),
+    }
+}
+
+/// Return the hash in `entry` for modification, creating an empty one if there is no value.
+/// Fields whose time to live has passed are removed first.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a hash
+fn hash_mut(entry: &mut Option<Entry>) -> Result<&mut Hash, RedisProtocolError> {
+    let entry = entry.get_or_insert_with(|| Entry::new(Hash::new()));
+    let hash = entry.value.as_hash_mut().ok_or_else(error_wrong_type)?;
+    hash.evict_expired();
+    Ok(hash)
+}
+
+/// Set `field` to `value`, keeping its time to live
+fn store(hash: &mut Hash, field: &Bytes, value: String) -> Bytes {
+    let value = Bytes::from(value);
+    match hash.get_mut(field) {
+        Some(current) => *current = value.clone(),
+        None => {
+            hash.insert(field.clone(), value.clone());
+        }
+    }
+    value
+}
+
+/// Validate the deadline of a field `time` seconds or milliseconds after `base`, both
+/// in milliseconds since the unix epoch.
+fn deadline(
+    command: &str,
+    time: i64,
+    seconds: bool,
+    base: i128,
+) -> Result<i128, RedisProtocolError> {
+    if time < 0 {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR invalid expire time, must be >= 0",
+        ));
+    }
+
+    let millis = if seconds {
+        time as i128 * 1000
+    } else {
+        time as i128
+    };
+    if base + millis > MAX_FIELD_DEADLINE {
+        return Err(error_invalid_expire(command));
+    }
+    Ok(base + millis)
+}
+
+/// Set the deadline of `field` if `condition` allows it, see [`handle`] for the replies
+fn expire_field(
+    hash: &mut Hash,
+    field: &[u8],
+    condition: Option<ExpireCondition>,
+    deadline: i128,
+    now: i128,
+) -> i64 {
+    let Some(current) = hash.expires(field) else {
+        return -2;
+    };
+    if !condition_met(condition, current.map(unix_millis), deadline) {
+        return 0;
+    }
+
+    if deadline <= now {
+        hash.remove(field);
+        2
+    } else {
+        hash.expire(
+            field,
+            Some(UNIX_EPOCH + Duration::from_millis(deadline as u64)),
+        );
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    #[test]
+    fn fields() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HSET", "user", "name", "ada", "age", "36"]
+            )
+            .unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HSET", "user", "name", "grace"]).unwrap(),
+            0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HSETNX", "user", "name", "ada"]).unwrap(),
+            0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HMGET", "user", "name", "missing"]).unwrap(),
+            vec!["grace".as_frame(), OwnedFrame::Null].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HINCRBY", "user", "age", "-1"]).unwrap(),
+            35.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HINCRBYFLOAT", "user", "score", "1.5"]
+            )
+            .unwrap(),
+            "1.5".as_frame()
+        );
+        assert!(run(handle, &mut values, &["HINCRBY", "user", "name", "1"]).is_err());
+        assert_eq!(
+            run(handle, &mut values, &["HGETALL", "user"]).unwrap(),
+            OwnedFrame::Map {
+                data: [("name", "grace"), ("age", "35"), ("score", "1.5")]
+                    .into_iter()
+                    .map(|(field, value)| (field.as_frame(), value.as_frame()))
+                    .collect(),
+                attributes: None,
+            }
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HSCAN", "user", "0", "MATCH", "a*", "NOVALUES"]
+            )
+            .unwrap(),
+            vec!["0".as_frame(), vec!["age"].as_frame()].as_frame()
+        );
+
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HDEL", "user", "name", "age", "score"]
+            )
+            .unwrap(),
+            3.as_frame()
+        );
+        assert!(!values.exists(b"user"));
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        let error = run(handle, &mut values, &["HGET", "text", "field"]).unwrap_err();
+        assert!(error.details().starts_with("WRONGTYPE"));
+    }
+
+    #[test]
+    fn field_expiration() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["HSET", "session", "a", "1", "b", "2"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HEXPIRE", "session", "100", "FIELDS", "2", "a", "missing"]
+            )
+            .unwrap(),
+            vec![1, -2].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HEXPIRE", "session", "50", "GT", "FIELDS", "2", "a", "b"]
+            )
+            .unwrap(),
+            vec![0, 0].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HTTL", "session", "FIELDS", "2", "a", "b"]
+            )
+            .unwrap(),
+            vec![100, -1].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HPERSIST", "session", "FIELDS", "2", "a", "b"]
+            )
+            .unwrap(),
+            vec![1, -1].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HPEXPIREAT", "session", "0", "FIELDS", "1", "b"]
+            )
+            .unwrap(),
+            vec![2].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HLEN", "session"]).unwrap(),
+            1.as_frame()
+        );
+
+        assert!(run(handle, &mut values, &["HTTL", "session", "FIELDS", "0"]).is_err());
+        assert!(run(
+            handle,
+            &mut values,
+            &["HTTL", "session", "FIELDS", "2", "a"]
+        )
+        .is_err());
+        assert!(run(
+            handle,
+            &mut values,
+            &["HEXPIRE", "session", "-1", "FIELDS", "1", "a"]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn field_expiration_conditions() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["HSET", "h", "a", "1", "b", "2", "c", "3"],
+        )
+        .unwrap();
+        let fields = |command: &'static str| vec![command, "h", "FIELDS", "3", "a", "b", "c"];
+
+        let mut hexpire = |args: &[&str]| {
+            let mut query = vec!["HEXPIRE", "h"];
+            query.extend_from_slice(args);
+            run(handle, &mut values, &query).unwrap()
+        };
+        assert_eq!(
+            hexpire(&["100", "NX", "FIELDS", "1", "a"]),
+            vec![1].as_frame()
+        );
+        assert_eq!(
+            hexpire(&["200", "NX", "FIELDS", "2", "a", "b"]),
+            vec![0, 1].as_frame()
+        );
+        assert_eq!(
+            hexpire(&["300", "XX", "FIELDS", "2", "a", "c"]),
+            vec![1, 0].as_frame()
+        );
+        /* Without a time to live, a field counts as expiring later than any deadline */
+        assert_eq!(
+            hexpire(&["50", "LT", "FIELDS", "3", "a", "b", "c"]),
+            vec![1, 1, 1].as_frame()
+        );
+        assert_eq!(
+            hexpire(&["60", "GT", "FIELDS", "2", "a", "missing"]),
+            vec![1, -2].as_frame()
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &fields("HTTL")).unwrap(),
+            vec![60, 50, 50].as_frame()
+        );
+        let OwnedFrame::Array { data, .. } = run(handle, &mut values, &fields("HPTTL")).unwrap()
+        else {
+            panic!("HPTTL should return an array")
+        };
+        assert!(data.iter().all(
+            |ttl| matches!(ttl, OwnedFrame::Number { data, .. } if (49_000..=60_000).contains(data))
+        ));
+
+        run(
+            handle,
+            &mut values,
+            &["HPEXPIREAT", "h", "33177117420123", "FIELDS", "1", "c"],
+        )
+        .unwrap();
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HPEXPIRETIME", "h", "FIELDS", "1", "c"]
+            )
+            .unwrap(),
+            vec![33177117420123i64].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HEXPIRETIME", "h", "FIELDS", "1", "c"]
+            )
+            .unwrap(),
+            vec![33177117421i64].as_frame()
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &fields("HPERSIST")).unwrap(),
+            vec![1, 1, 1].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &fields("HPERSIST")).unwrap(),
+            vec![-1, -1, -1].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HTTL", "nohash", "FIELDS", "1", "a"]).unwrap(),
+            vec![-2].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["HPERSIST", "nohash", "FIELDS", "1", "a"]
+            )
+            .unwrap(),
+            vec![-2].as_frame()
+        );
+        assert!(!values.exists(b"nohash"));
+    }
+
+    #[test]
+    fn key_disappears_once_all_fields_expire() {
+        let mut values = MemoryStore::new();
+        run(handle, &mut values, &["HSET", "h", "a", "1", "b", "2"]).unwrap();
+        run(
+            handle,
+            &mut values,
+            &["HPEXPIRE", "h", "200", "FIELDS", "1", "a"],
+        )
+        .unwrap();
+        run(
+            handle,
+            &mut values,
+            &["HPEXPIRE", "h", "1", "FIELDS", "1", "b"],
+        )
+        .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(
+            run(handle, &mut values, &["HLEN", "h"]).unwrap(),
+            1.as_frame()
+        );
+        assert!(values.exists(b"h"));
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(
+            run(handle, &mut values, &["HLEN", "h"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"h"));
+        assert_eq!(
+            run(handle, &mut values, &["HSET", "h", "c", "3"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["HGETALL", "h"]).unwrap(),
+            OwnedFrame::Map {
+                data: [("c".as_frame(), "3".as_frame())].into_iter().collect(),
+                attributes: None,
+            }
+        );
+    }
+
+    #[test]
+    fn hscan_iterates_every_field() {
+        let mut values = MemoryStore::new();
+        let mut query = vec!["HSET".to_string(), "h".to_string()];
+        for i in 0..50 {
+            query.push(format!("field:{i}"));
+            query.push(format!("{i}"));
+        }
+        let query: Vec<&str> = query.iter().map(String::as_str).collect();
+        run(handle, &mut values, &query).unwrap();
+
+        let mut cursor = "0".to_string();
+        let mut pairs = Vec::new();
+        loop {
+            let reply = run(handle, &mut values, &["HSCAN", "h", &cursor, "COUNT", "7"]).unwrap();
+            let OwnedFrame::Array { mut data, .. } = reply else {
+                panic!("HSCAN should return an array")
+            };
+            let Some(OwnedFrame::Array { data: items, .. }) = data.pop() else {
+                panic!("HSCAN should return the items last")
+            };
+            let Some(OwnedFrame::BlobString { data: next, .. }) = data.pop() else {
+                panic!("HSCAN should return the cursor first")
+            };
+            assert!(items.len() <= 2 * 8);
+            pairs.extend(items.chunks(2).map(|pair| pair.to_vec()));
+            cursor = String::from_utf8(next).unwrap();
+            if cursor == "0" {
+                break;
+            }
+        }
+
+        pairs.sort_by_key(|pair| format!("{:?}", pair[0]));
+        pairs.dedup();
+        assert_eq!(pairs.len(), 50);
+        assert!(pairs.contains(&vec!["field:7".as_frame(), "7".as_frame()]));
+
+        assert_eq!(
+            run(handle, &mut values, &["HSCAN", "missing", "0"]).unwrap(),
+            vec!["0".as_frame(), Vec::<Bytes>::new().as_frame()].as_frame()
+        );
+        assert!(run(handle, &mut values, &["HSCAN", "h", "abc"]).is_err());
+    }
+}
@@ -4,8 +4,13 @@ use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
 use std::collections::HashMap;
 
+use crate::codec::Protocol;
+use crate::commands::auth::Password;
 use crate::commands::parse::Request;
+use crate::server::session::Session;
 use crate::util;
+use crate::util::errors::error_syntax;
+use bytes::Bytes;
 use util::convert::*;
 
 /// Valid property types returned by HELLO.
@@ -25,47 +30,65 @@ impl AsFrame for Property {
     }
 }
 
-/// Handle `HELLO` requests with a default set of properties.
-/// See [`handle`]
-pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+/// Handle `HELLO` requests with a default set of properties, reporting the protocol of
+/// `session` unless another version is requested. See [`handle`]
+pub fn default_handle(session: &Session, args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
     debug!("Providing default properties to handle HELLO");
     let default_values = HashMap::from([
         ("server".into(), Property::String("RRedis".into())),
-        ("proto".into(), Property::Integer(3)),
+        (
+            "proto".into(),
+            Property::Integer(session.protocol().version()),
+        ),
         ("modules".into(), Property::Array(Vec::new())),
     ]);
 
     handle(&default_values, args)
 }
 
+/// Return the [`Protocol`] requested by a `HELLO` version argument, if supported
+pub fn protocol(version: &str) -> Option<Protocol> {
+    match version {
+        "2" => Some(Protocol::Resp2),
+        "3" => Some(Protocol::Resp3),
+        _ => None,
+    }
+}
+
+/// Shorthand for the error Redis returns if `HELLO` asks for an unsupported protocol version
+pub fn error_noproto() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "NOPROTO sorry, this protocol version is not supported",
+    )
+}
+
 /// Parse arguments for HELLO command
 ///
 /// # Returns
 /// [`Request`]
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
-    let mut iter = args.into_iter();
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut iter = args.iter().map(|arg| bytes_to_string(arg));
 
     /* Optional arguments */
     let version: Option<String> = iter.next();
-    let mut auth: Option<(String, String)> = None;
+    let mut auth: Option<(String, Password)> = None;
     let mut clientname: Option<String> = None;
 
     /* Parse arguments */
     while let Some(arg) = iter.next() {
         match arg.to_uppercase().as_str() {
             "AUTH" => {
-                if let Some(username) = iter.next() {
-                    if let Some(password) = iter.next() {
-                        debug!("Received auth: ({}, {})", username, password);
-                        auth = Some((username, password));
-                    }
-                }
+                let (Some(username), Some(password)) = (iter.next(), iter.next()) else {
+                    return Err(error_syntax());
+                };
+                debug!("Received auth for user {}", username);
+                auth = Some((username, Password::new(password)));
             }
             "SETNAME" => {
-                if let Some(name) = iter.next() {
-                    debug!("Client set name: {}", name);
-                    clientname = Some(name);
-                }
+                let name = iter.next().ok_or_else(error_syntax)?;
+                debug!("Client set name: {}", name);
+                clientname = Some(name);
             }
             _ => {
                 error!("Unknown argument passed to HELLO: {}", arg);
@@ -94,6 +117,7 @@ pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
 /// # Returns
 ///
 /// The map of properties in `values` as a [`OwnedFrame::Map`] or a [`RedisProtocolError`].
+/// If a protocol version was requested, `proto` is set to it.
 ///
 /// # TODO
 ///
@@ -106,21 +130,89 @@ pub fn handle(
     args: &Request,
 ) -> Result<OwnedFrame, RedisProtocolError> {
     debug!("Handling HELLO with provided properties");
-    let default_version = String::from("3");
     match args {
         Request::HELLO { version, .. } => {
-            // TODO: Use other parsed args
-            let v: &String = version.as_ref().unwrap_or(&default_version);
-            if "3".eq(v) {
-                Ok(values.as_frame())
-            } else {
-                error!("Client asked for unsupported protocol version {}", v);
-                Err(RedisProtocolError::new(
-                    RedisProtocolErrorKind::Parse,
-                    format!("Unknown protocol version: {}", v),
-                ))
+            /* AUTH and SETNAME are applied by the dispatcher */
+            let mut reply = values.as_frame();
+            if let Some(v) = version {
+                let Some(protocol) = protocol(v) else {
+                    error!("Client asked for unsupported protocol version {}", v);
+                    return Err(error_noproto());
+                };
+
+                if let OwnedFrame::Map { data, .. } = &mut reply {
+                    data.insert("proto".as_frame(), protocol.version().as_frame());
+                }
             }
+            Ok(reply)
         }
         _ => panic!("Expected enum variant HELLO, but got {:?}", args.type_id()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(args: &[&str]) -> Result<Request, RedisProtocolError> {
+        parse(
+            args.iter()
+                .map(|arg| Bytes::from(arg.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_options() {
+        let Request::HELLO {
+            version,
+            clientname,
+            auth,
+        } = request(&["3", "auth", "user", "secret", "SETNAME", "app"]).unwrap()
+        else {
+            panic!("Expected HELLO")
+        };
+        assert_eq!(version.as_deref(), Some("3"));
+        assert_eq!(clientname.as_deref(), Some("app"));
+        assert_eq!(auth.map(|(user, _)| user).as_deref(), Some("user"));
+    }
+
+    #[test]
+    fn parse_missing_option_arguments() {
+        let syntax = error_syntax().details().to_string();
+        for args in [
+            &["3", "AUTH", "user"][..],
+            &["3", "AUTH"],
+            &["3", "SETNAME"],
+            &["3", "SETNAME", "app", "AUTH", "user"],
+        ] {
+            assert_eq!(request(args).unwrap_err().details(), syntax, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn report_session_protocol() {
+        let proto = |reply: OwnedFrame| match reply {
+            OwnedFrame::Map { data, .. } => data[&"proto".as_frame()].clone(),
+            reply => panic!("Expected a map, but got {reply:?}"),
+        };
+        let mut session = Session::new(1, "127.0.0.1:6379".parse().unwrap());
+
+        let hello = request(&[]).unwrap();
+        assert_eq!(
+            proto(default_handle(&session, &hello).unwrap()),
+            2.as_frame()
+        );
+        session.set_protocol(Protocol::Resp3);
+        assert_eq!(
+            proto(default_handle(&session, &hello).unwrap()),
+            3.as_frame()
+        );
+
+        let hello = request(&["2"]).unwrap();
+        assert_eq!(
+            proto(default_handle(&session, &hello).unwrap()),
+            2.as_frame()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::commands::parse::Request;
+use crate::commands::string::string;
+use crate::storage::memory::MemoryStore;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_f64, bytes_to_i64, f64_to_string, AsFrame};
+use crate::util::errors::{
+    error_increment_not_finite, error_increment_overflow, error_not_integer, error_wrong_arity,
+};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+
+/// Parse INCR, DECR, INCRBY, DECRBY and INCRBYFLOAT
+///
+/// # Syntax
+/// ```text
+/// INCR key
+/// DECR key
+/// INCRBY key increment
+/// DECRBY key decrement
+/// INCRBYFLOAT key increment
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let expected = match command {
+        "INCR" | "DECR" => 1,
+        _ => 2,
+    };
+    if args.len() != expected {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().unwrap();
+    let request = match command {
+        "INCR" => Request::INCR { key },
+        "DECR" => Request::DECR { key },
+        "INCRBY" => Request::INCRBY {
+            key,
+            increment: bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?,
+        },
+        "DECRBY" => {
+            let decrement = bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?;
+            /* Negating the smallest integer would overflow */
+            if decrement == i64::MIN {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR decrement would overflow",
+                ));
+            }
+            Request::DECRBY { key, decrement }
+        }
+        _ => Request::INCRBYFLOAT {
+            key,
+            increment: bytes_to_f64(&iter.next().unwrap()).ok_or_else(error_not_float)?,
+        },
+    };
+
+    Ok(request)
+}
+
+pub(crate) fn error_not_float() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR value is not a valid float",
+    )
+}
+
+/// Handle counter commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle `INCR`, `DECR`, `INCRBY`, `DECRBY` and `INCRBYFLOAT`.
+///
+/// Keys that don't exist count as `0`. The new value replaces the old one atomically and keeps
+/// its time to live.
+///
+/// # Returns
+///  * `INCRBYFLOAT`: the new value, formatted like it is stored, see [`f64_to_string`]
+///  * Otherwise: the new value, or an error if the stored value is not an integer or the
+///    result would overflow
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let (key, delta) = match args {
+        Request::INCR { key } => (key, 1),
+        Request::DECR { key } => (key, -1),
+        Request::INCRBY { key, increment } => (key, *increment),
+        Request::DECRBY { key, decrement } => (key, -*decrement),
+        Request::INCRBYFLOAT { key, increment } => {
+            let value = values.update(key.clone(), |entry| {
+                let current = match string(entry.as_ref().map(|e| &e.value))? {
+                    Some(value) => bytes_to_f64(value).ok_or_else(error_not_float)?,
+                    None => 0.0,
+                };
+                let value = current + increment;
+                if !value.is_finite() {
+                    return Err(error_increment_not_finite());
+                }
+                Ok(store(entry, f64_to_string(value)))
+            })?;
+            return Ok(value.as_frame());
+        }
+        _ => panic!(
+            "Expected enum variant INCR, DECR, INCRBY, DECRBY or INCRBYFLOAT, but got {:?}",
+            args.type_id()
+        ),
+    };
+
+    let value = values.update(key.clone(), |entry| {
+        let current = match string(entry.as_ref().map(|e| &e.value))? {
+            Some(value) => bytes_to_i64(value).ok_or_else(error_not_integer)?,
+            None => 0,
+        };
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(error_increment_overflow)?;
+        store(entry, value.to_string());
+        Ok::<_, RedisProtocolError>(value)
+    })?;
+    Ok(value.as_frame())
+}
+
+/// Store `value` in `entry`, keeping its time to live
+fn store(entry: &mut Option<Entry>, value: String) -> Bytes {
+    let value = Bytes::from(value);
+    let expires = entry.as_ref().and_then(|entry| entry.expires);
+    *entry = Some(Entry {
+        value: value.clone().into(),
+        expires,
+    });
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    #[test]
+    fn integer_counters() {
+        let mut values = MemoryStore::new();
+
+        assert_eq!(
+            run(handle, &mut values, &["INCR", "hits"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["INCRBY", "hits", "41"]).unwrap(),
+            42.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["DECRBY", "hits", "50"]).unwrap(),
+            (-8).as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["DECR", "hits"]).unwrap(),
+            (-9).as_frame()
+        );
+        assert_eq!(values.get(b"hits"), Some(Bytes::from_static(b"-9").into()));
+
+        values.set(
+            Bytes::from_static(b"max"),
+            Bytes::from(i64::MAX.to_string()),
+        );
+        assert_eq!(
+            run(handle, &mut values, &["INCR", "max"])
+                .unwrap_err()
+                .details(),
+            error_increment_overflow().details()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["DECRBY", "hits", "-9223372036854775808"]
+            )
+            .unwrap_err()
+            .details(),
+            "ERR decrement would overflow"
+        );
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b" 1"));
+        assert_eq!(
+            run(handle, &mut values, &["INCR", "text"])
+                .unwrap_err()
+                .details(),
+            "ERR value is not an integer or out of range"
+        );
+        assert!(run(handle, &mut values, &["INCRBY", "hits", "1.5"]).is_err());
+        assert!(run(handle, &mut values, &["INCR"]).is_err());
+    }
+
+    #[test]
+    fn float_counter() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"key"), Bytes::from_static(b"10.50"));
+
+        assert_eq!(
+            run(handle, &mut values, &["INCRBYFLOAT", "key", "0.1"]).unwrap(),
+            "10.6".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["INCRBYFLOAT", "key", "-5"]).unwrap(),
+            "5.6".as_frame()
+        );
+
+        values.set(Bytes::from_static(b"key"), Bytes::from_static(b"5.0e3"));
+        assert_eq!(
+            run(handle, &mut values, &["INCRBYFLOAT", "key", "2.0e2"]).unwrap(),
+            "5200".as_frame()
+        );
+        assert_eq!(values.get(b"key"), Some(Bytes::from_static(b"5200").into()));
+
+        assert_eq!(
+            run(handle, &mut values, &["INCRBYFLOAT", "key", "inf"])
+                .unwrap_err()
+                .details(),
+            error_increment_not_finite().details()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["INCRBYFLOAT", "key", "abc"])
+                .unwrap_err()
+                .details(),
+            "ERR value is not a valid float"
+        );
+    }
+
+    #[test]
+    fn float_formatting() {
+        let mut values = MemoryStore::new();
+
+        for (increment, expected) in [
+            ("1e-20", "0"),
+            ("-1e-20", "0"),
+            ("1e-17", "0.00000000000000001"),
+            ("-2e-17", "-0.00000000000000001"),
+            ("1e20", "100000000000000000000"),
+            ("-1e20", "0"),
+            ("-0", "0"),
+            ("-0.5", "-0.5"),
+            ("0.5", "0"),
+            ("2.5e-3", "0.0025"),
+        ] {
+            assert_eq!(
+                run(handle, &mut values, &["INCRBYFLOAT", "tiny", increment]).unwrap(),
+                expected.as_frame(),
+                "INCRBYFLOAT tiny {increment}"
+            );
+        }
+        assert_eq!(
+            values.get(b"tiny"),
+            Some(Bytes::from_static(b"0.0025").into())
+        );
+
+        values.set(Bytes::from_static(b"huge"), Bytes::from_static(b"1.5e300"));
+        let Ok(OwnedFrame::BlobString { data, .. }) =
+            run(handle, &mut values, &["INCRBYFLOAT", "huge", "1"])
+        else {
+            panic!("INCRBYFLOAT should reply with a string")
+        };
+        assert_eq!(data.len(), 301);
+        assert!(data.starts_with(b"15000") && !data.contains(&b'e'));
+    }
+}
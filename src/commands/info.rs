@@ -1,5 +1,6 @@
 use crate::commands::parse::Request;
-use crate::util::convert::AsFrame;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
@@ -169,20 +170,20 @@ impl BitOrAssign for Info {
 /// # Implementation Details:
 ///
 ///  * If `args` contains `all` or `everything`, this function will immediately return an Info object
-/// with the respective sections set to true, without continuing parsing.
+///    with the respective sections set to true, without continuing parsing.
 ///  * If `args` contains `default`, parsing will continue and allow additional flag to be set.
 ///
 /// ## `All` vs `Everything`
 ///
 ///  * Aligning with redis naming, `all` will set all *but* `modules` to true, while `everything`
-/// will set all flags, *including* `modules` to true.
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+///    will set all flags, *including* `modules` to true.
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
     let mut ret = Info::new();
     if args.is_empty() {
         Ok(Request::INFO(Info::default()))
     } else {
         for arg in args {
-            match arg.to_lowercase().as_ref() {
+            match bytes_to_string(&arg).to_lowercase().as_ref() {
                 "all" => ret |= Info::all(),
                 "everything" => return Ok(Request::INFO(Info::everything())),
                 "default" => ret |= Info::default(),
@@ -0,0 +1,307 @@
+use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_no_such_key, error_not_integer, error_same_object, error_syntax, error_wrong_arity,
+};
+use bytes::Bytes;
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+
+/// Parse one of the generic key commands
+///
+/// # Syntax
+/// ```text
+/// DEL key [key ...]
+/// UNLINK key [key ...]
+/// EXISTS key [key ...]
+/// TOUCH key [key ...]
+/// TYPE key
+/// RENAME key newkey
+/// RENAMENX key newkey
+/// COPY source destination [DB destination-db] [REPLACE]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "DEL" | "UNLINK" | "EXISTS" | "TOUCH" => !args.is_empty(),
+        "TYPE" => args.len() == 1,
+        "RENAME" | "RENAMENX" => args.len() == 2,
+        "COPY" => args.len() >= 2,
+        _ => false,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let request = match command {
+        "DEL" => Request::DEL {
+            keys: iter.collect(),
+        },
+        "UNLINK" => Request::UNLINK {
+            keys: iter.collect(),
+        },
+        "EXISTS" => Request::EXISTS {
+            keys: iter.collect(),
+        },
+        "TOUCH" => Request::TOUCH {
+            keys: iter.collect(),
+        },
+        "TYPE" => Request::TYPE {
+            key: iter.next().unwrap(),
+        },
+        "RENAME" => Request::RENAME {
+            key: iter.next().unwrap(),
+            newkey: iter.next().unwrap(),
+        },
+        "RENAMENX" => Request::RENAMENX {
+            key: iter.next().unwrap(),
+            newkey: iter.next().unwrap(),
+        },
+        _ => {
+            let source = iter.next().unwrap();
+            let destination = iter.next().unwrap();
+            let (mut db, mut replace) = (None, false);
+            while let Some(option) = iter.next() {
+                match bytes_to_string(&option).to_uppercase().as_str() {
+                    "REPLACE" => replace = true,
+                    "DB" => {
+                        let index = iter.next().ok_or_else(error_syntax)?;
+                        let index = bytes_to_string(&index)
+                            .parse()
+                            .map_err(|_| error_not_integer())?;
+                        db = Some(index);
+                    }
+                    _ => return Err(error_syntax()),
+                }
+            }
+            Request::COPY {
+                source,
+                destination,
+                db,
+                replace,
+            }
+        }
+    };
+
+    Ok(request)
+}
+
+/// Handle generic key commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle `DEL`, `UNLINK`, `EXISTS`, `TOUCH`, `TYPE`, `RENAME`, `RENAMENX` and `COPY`.
+///
+/// `COPY` copies within `values`, its `DB` option is expected to be resolved by the caller
+/// already. See [`copy`] to copy between two databases.
+///
+/// # Returns
+///  * `DEL`, `UNLINK`: the number of keys removed
+///  * `EXISTS`, `TOUCH`: the number of keys that exist, keys given multiple times are counted
+///    multiple times
+///  * `TYPE`: the type of the value stored at `key`, `none` if the key does not exist
+///  * `RENAME`: `OK`, or an error if `key` does not exist
+///  * `RENAMENX`, `COPY`: `1` if the key was renamed or copied, `0` if the destination exists
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::DEL { keys } | Request::UNLINK { keys } => {
+            let removed = keys.iter().filter(|key| values.del(key)).count();
+            Ok((removed as i64).as_frame())
+        }
+        Request::EXISTS { keys } | Request::TOUCH { keys } => {
+            let existing = keys.iter().filter(|key| values.exists(key)).count();
+            Ok((existing as i64).as_frame())
+        }
+        Request::TYPE { key } => Ok(OwnedFrame::SimpleString {
+            data: type_name(values, key).unwrap_or("none").into(),
+            attributes: None,
+        }),
+        Request::RENAME { key, newkey } | Request::RENAMENX { key, newkey } => {
+            let replace = matches!(args, Request::RENAME { .. });
+            let renamed = if key == newkey {
+                values.exists(key).then_some(replace)
+            } else {
+                /* Take the old key and write the new one at once, so no client ever sees
+                 * neither of them */
+                values.update_many(&[key.clone(), newkey.clone()], |entries| {
+                    let [source, target] = entries else {
+                        panic!("Expected one entry per key");
+                    };
+                    source.as_ref()?;
+                    if target.is_some() && !replace {
+                        return Some(false);
+                    }
+                    *target = source.take();
+                    Some(true)
+                })
+            };
+
+            match renamed.ok_or_else(error_no_such_key)? {
+                _ if replace => Ok("OK".as_frame()),
+                renamed => Ok((renamed as i64).as_frame()),
+            }
+        }
+        Request::COPY {
+            source,
+            destination,
+            replace,
+            ..
+        } => {
+            if source == destination {
+                return Err(error_same_object());
+            }
+            let copied = values
+                .entry(source)
+                .is_some_and(|entry| store(values, destination, entry, *replace).is_ok());
+            Ok((copied as i64).as_frame())
+        }
+        _ => panic!(
+            "Expected enum variant DEL, UNLINK, EXISTS, TOUCH, TYPE, RENAME, RENAMENX or COPY, \
+             but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Name of the type of the value stored at `key`, as reported by `TYPE`
+pub fn type_name<S: KeyValueStore>(values: &S, key: &[u8]) -> Option<&'static str> {
+    values.read(key, |value| value.map(Value::type_name))
+}
+
+/// Handle `COPY` from `source` into another database `destination`.
+///
+/// # Returns
+/// `1` if the key was copied, `0` if it does not exist or the destination exists and
+/// `REPLACE` was not given
+pub fn copy<S: KeyValueStore, T: KeyValueStore>(
+    source: &S,
+    destination: &mut T,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let Request::COPY {
+        source: key,
+        destination: target,
+        replace,
+        ..
+    } = args
+    else {
+        panic!("Expected enum variant COPY, but got {:?}", args.type_id())
+    };
+
+    let copied = source
+        .entry(key)
+        .is_some_and(|entry| store(destination, target, entry, *replace).is_ok());
+    Ok((copied as i64).as_frame())
+}
+
+/// Store `entry` at `key` unless the key exists and `replace` is `false`.
+///
+/// # Returns
+/// The entry if it was not stored
+fn store<S: KeyValueStore>(
+    values: &mut S,
+    key: &Bytes,
+    entry: Entry,
+    replace: bool,
+) -> Result<(), Entry> {
+    values.update(key.clone(), |existing| {
+        if existing.is_some() && !replace {
+            return Err(entry);
+        }
+        *existing = Some(entry);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn key_commands() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"a"), Bytes::from_static(b"1"));
+        values.set(Bytes::from_static(b"b"), Bytes::from_static(b"2"));
+        values.expire(b"a", Some(SystemTime::now() + Duration::from_secs(60)));
+
+        assert_eq!(
+            run(handle, &mut values, &["EXISTS", "a", "a", "c"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["TYPE", "a"]).unwrap(),
+            OwnedFrame::SimpleString {
+                data: b"string".to_vec(),
+                attributes: None
+            }
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &["RENAMENX", "a", "b"]).unwrap(),
+            0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["RENAME", "a", "c"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert!(values.entry(b"c").unwrap().expires.is_some());
+        assert_eq!(
+            run(handle, &mut values, &["RENAME", "a", "d"])
+                .unwrap_err()
+                .details(),
+            error_no_such_key().details()
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &["COPY", "c", "b"]).unwrap(),
+            0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["COPY", "c", "b", "REPLACE"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(values.get(b"b"), Some(Bytes::from_static(b"1").into()));
+
+        assert_eq!(
+            run(handle, &mut values, &["DEL", "b", "b", "c", "x"]).unwrap(),
+            2.as_frame()
+        );
+        assert!(values.is_empty());
+        assert!(run(handle, &mut values, &["DEL"]).is_err());
+    }
+
+    #[test]
+    fn rename_edge_cases() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"a"), Bytes::from_static(b"1"));
+
+        assert_eq!(
+            run(handle, &mut values, &["RENAME", "a", "a"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["RENAMENX", "a", "a"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(run(handle, &mut values, &["RENAMENX", "x", "x"]).is_err());
+        assert!(run(handle, &mut values, &["RENAMENX", "x", "y"]).is_err());
+
+        assert_eq!(
+            run(handle, &mut values, &["RENAMENX", "a", "b"]).unwrap(),
+            1.as_frame()
+        );
+        assert!(!values.exists(b"a"));
+        assert_eq!(values.get(b"b"), Some(Bytes::from_static(b"1").into()));
+    }
+}
@@ -0,0 +1,1077 @@
+use crate::codec::null_array;
+use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_f64, bytes_to_i64, bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_negative_timeout, error_no_such_key, error_not_integer, error_syntax, error_wrong_arity,
+    error_wrong_type,
+};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::Duration;
+
+/// End of a list to push to or pop from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    /// The head, index `0`
+    Left,
+    /// The tail, index `-1`
+    Right,
+}
+
+/// Parse one of the list commands
+///
+/// # Syntax
+/// ```text
+/// LPUSH key element [element ...]
+/// RPUSH key element [element ...]
+/// LPUSHX key element [element ...]
+/// RPUSHX key element [element ...]
+/// LPOP key [count]
+/// RPOP key [count]
+/// LLEN key
+/// LRANGE key start stop
+/// LINDEX key index
+/// LSET key index element
+/// LINSERT key <BEFORE | AFTER> pivot element
+/// LREM key count element
+/// LTRIM key start stop
+/// LMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT>
+/// RPOPLPUSH source destination
+/// LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+/// LMPOP numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+/// BLPOP key [key ...] timeout
+/// BRPOP key [key ...] timeout
+/// BLMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT> timeout
+/// BLMPOP timeout numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "LPUSH" | "RPUSH" | "LPUSHX" | "RPUSHX" | "LPOS" => args.len() >= 2,
+        "LPOP" | "RPOP" => args.len() == 1 || args.len() == 2,
+        "LLEN" => args.len() == 1,
+        "LINDEX" | "RPOPLPUSH" => args.len() == 2,
+        "LRANGE" | "LSET" | "LREM" | "LTRIM" => args.len() == 3,
+        "LINSERT" | "LMOVE" => args.len() == 4,
+        "BLPOP" | "BRPOP" => args.len() >= 2,
+        "BLMOVE" => args.len() == 5,
+        "BLMPOP" => args.len() >= 4,
+        _ => args.len() >= 3,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut args = args;
+    match command {
+        "LMPOP" => {
+            let (keys, side, count) = parse_mpop(args)?;
+            return Ok(Request::LMPOP { keys, side, count });
+        }
+        "BLMPOP" => {
+            let timeout = parse_timeout(&args.remove(0))?;
+            let (keys, side, count) = parse_mpop(args)?;
+            return Ok(Request::BLMPOP {
+                keys,
+                side,
+                count,
+                timeout,
+            });
+        }
+        "BLPOP" | "BRPOP" => {
+            let timeout = parse_timeout(&args.pop().unwrap())?;
+            return Ok(match command {
+                "BLPOP" => Request::BLPOP {
+                    keys: args,
+                    timeout,
+                },
+                _ => Request::BRPOP {
+                    keys: args,
+                    timeout,
+                },
+            });
+        }
+        _ => {}
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().unwrap();
+    let integer = |arg: Option<Bytes>| bytes_to_i64(&arg.unwrap()).ok_or_else(error_not_integer);
+    let request = match command {
+        "LPUSH" => Request::LPUSH {
+            key,
+            elements: iter.collect(),
+        },
+        "RPUSH" => Request::RPUSH {
+            key,
+            elements: iter.collect(),
+        },
+        "LPUSHX" => Request::LPUSHX {
+            key,
+            elements: iter.collect(),
+        },
+        "RPUSHX" => Request::RPUSHX {
+            key,
+            elements: iter.collect(),
+        },
+        "LPOP" | "RPOP" => {
+            let count = match iter.next() {
+                Some(count) => Some(parse_count(&count)?),
+                None => None,
+            };
+            match command {
+                "LPOP" => Request::LPOP { key, count },
+                _ => Request::RPOP { key, count },
+            }
+        }
+        "LLEN" => Request::LLEN { key },
+        "LRANGE" => Request::LRANGE {
+            key,
+            start: integer(iter.next())?,
+            stop: integer(iter.next())?,
+        },
+        "LINDEX" => Request::LINDEX {
+            key,
+            index: integer(iter.next())?,
+        },
+        "LSET" => Request::LSET {
+            key,
+            index: integer(iter.next())?,
+            element: iter.next().unwrap(),
+        },
+        "LINSERT" => {
+            let before = match bytes_to_string(&iter.next().unwrap())
+                .to_uppercase()
+                .as_str()
+            {
+                "BEFORE" => true,
+                "AFTER" => false,
+                _ => return Err(error_syntax()),
+            };
+            Request::LINSERT {
+                key,
+                before,
+                pivot: iter.next().unwrap(),
+                element: iter.next().unwrap(),
+            }
+        }
+        "LREM" => Request::LREM {
+            key,
+            count: integer(iter.next())?,
+            element: iter.next().unwrap(),
+        },
+        "LTRIM" => Request::LTRIM {
+            key,
+            start: integer(iter.next())?,
+            stop: integer(iter.next())?,
+        },
+        "LMOVE" => Request::LMOVE {
+            source: key,
+            destination: iter.next().unwrap(),
+            from: parse_side(&iter.next().unwrap())?,
+            to: parse_side(&iter.next().unwrap())?,
+        },
+        "BLMOVE" => Request::BLMOVE {
+            source: key,
+            destination: iter.next().unwrap(),
+            from: parse_side(&iter.next().unwrap())?,
+            to: parse_side(&iter.next().unwrap())?,
+            timeout: parse_timeout(&iter.next().unwrap())?,
+        },
+        "RPOPLPUSH" => Request::RPOPLPUSH {
+            source: key,
+            destination: iter.next().unwrap(),
+        },
+        "LPOS" => {
+            let element = iter.next().unwrap();
+            let (mut rank, mut count, mut maxlen) = (None, None, None);
+            while let Some(option) = iter.next() {
+                let value = iter.next().ok_or_else(error_syntax)?;
+                let value = bytes_to_i64(&value).ok_or_else(error_not_integer)?;
+                match bytes_to_string(&option).to_uppercase().as_str() {
+                    "RANK" => {
+                        if value == 0 {
+                            return Err(RedisProtocolError::new(
+                                RedisProtocolErrorKind::Parse,
+                                "ERR RANK can't be zero: use 1 to start from the first match, \
+                                 2 from the second ... or use negative to start from the end of \
+                                 the list",
+                            ));
+                        }
+                        /* Negating the smallest integer would overflow */
+                        if value == i64::MIN {
+                            return Err(RedisProtocolError::new(
+                                RedisProtocolErrorKind::Parse,
+                                "ERR value is out of range, value must between \
+                                 -9223372036854775807 and 9223372036854775807",
+                            ));
+                        }
+                        rank = Some(value);
+                    }
+                    "COUNT" | "MAXLEN" => {
+                        let name = bytes_to_string(&option).to_uppercase();
+                        if value < 0 {
+                            return Err(RedisProtocolError::new(
+                                RedisProtocolErrorKind::Parse,
+                                format!("ERR {name} can't be negative"),
+                            ));
+                        }
+                        match name.as_str() {
+                            "COUNT" => count = Some(value as usize),
+                            _ => maxlen = Some(value as usize),
+                        }
+                    }
+                    _ => return Err(error_syntax()),
+                }
+            }
+            Request::LPOS {
+                key,
+                element,
+                rank,
+                count,
+                maxlen,
+            }
+        }
+        _ => panic!("Expected list command, but got {command}"),
+    };
+
+    Ok(request)
+}
+
+/// Parse `LEFT` or `RIGHT`
+fn parse_side(arg: &[u8]) -> Result<Side, RedisProtocolError> {
+    match bytes_to_string(arg).to_uppercase().as_str() {
+        "LEFT" => Ok(Side::Left),
+        "RIGHT" => Ok(Side::Right),
+        _ => Err(error_syntax()),
+    }
+}
+
+/// Parse the `count` of `LPOP`, `RPOP` and `SPOP`, which may be `0`
+pub(crate) fn parse_count(arg: &[u8]) -> Result<usize, RedisProtocolError> {
+    match bytes_to_i64(arg) {
+        Some(count) if count >= 0 => Ok(count as usize),
+        _ => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR value is out of range, must be positive",
+        )),
+    }
+}
+
+/// Parse the timeout of blocking commands, in seconds with an optional fraction
+fn parse_timeout(arg: &[u8]) -> Result<Duration, RedisProtocolError> {
+    let seconds = bytes_to_f64(arg).ok_or_else(|| {
+        RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR timeout is not a float or out of range",
+        )
+    })?;
+    if seconds < 0.0 {
+        return Err(error_negative_timeout());
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR timeout is out of range")
+    })
+}
+
+/// Parse `numkeys key [key ...] <LEFT | RIGHT> [COUNT count]` of `LMPOP`
+fn parse_mpop(args: Vec<Bytes>) -> Result<(Vec<Bytes>, Side, Option<usize>), RedisProtocolError> {
+    let mut iter = args.into_iter();
+    let numkeys = iter
+        .next()
+        .and_then(|numkeys| bytes_to_i64(&numkeys))
+        .filter(|numkeys| *numkeys > 0)
+        .ok_or_else(|| {
+            RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                "ERR numkeys should be greater than 0",
+            )
+        })?;
+
+    let keys: Vec<Bytes> = iter.by_ref().take(numkeys as usize).collect();
+    if keys.len() as i64 != numkeys {
+        return Err(error_syntax());
+    }
+    let side = parse_side(&iter.next().ok_or_else(error_syntax)?)?;
+
+    let count = match (iter.next(), iter.next(), iter.next()) {
+        (None, ..) => None,
+        (Some(option), Some(count), None) if option.eq_ignore_ascii_case(b"COUNT") => {
+            let count = bytes_to_i64(&count)
+                .filter(|count| *count > 0)
+                .ok_or_else(|| {
+                    RedisProtocolError::new(
+                        RedisProtocolErrorKind::Parse,
+                        "ERR count should be greater than 0",
+                    )
+                })?;
+            Some(count as usize)
+        }
+        _ => return Err(error_syntax()),
+    };
+
+    Ok((keys, side, count))
+}
+
+/// Handle list commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle the list commands.
+///
+/// Keys that don't exist count as empty lists, and a list is removed with its last element.
+/// Negative indices count from the end of the list, `-1` being the last element.
+///
+/// # Returns
+///  * `LPUSH`, `RPUSH`, `LPUSHX`, `RPUSHX`: the length of the list afterward, `0` if the `X`
+///    variants found no list
+///  * `LPOP`, `RPOP`: the element removed, or an array of up to `count` elements if `count`
+///    was given. [`OwnedFrame::Null`] if the list does not exist, or a
+///    [`null_array`] if `count` was given.
+///  * `LLEN`: the length of the list
+///  * `LRANGE`: the elements between `start` and `stop`, both inclusive
+///  * `LINDEX`: the element at `index`, or [`OwnedFrame::Null`]
+///  * `LSET`, `LTRIM`: `OK`
+///  * `LINSERT`: the length of the list afterward, `-1` if `pivot` was not found and `0` if the
+///    list does not exist
+///  * `LREM`: the number of elements removed
+///  * `LMOVE`, `RPOPLPUSH`: the element moved, or [`OwnedFrame::Null`] if `source` is empty
+///  * `LPOS`: the index of the matching element, or an array of indices if `COUNT` was given
+///  * `LMPOP`: the name of the first non-empty list and the elements removed from it, or a
+///    [`null_array`] if all lists are empty
+///  * `BLPOP`, `BRPOP`: the name of the first non-empty list and the element removed from it,
+///    or a [`null_array`] if all lists are empty
+///  * `BLMOVE`, `BLMPOP`: like `LMOVE` and `LMPOP`
+///
+/// The blocking commands don't block here. If all lists are empty, they reply with
+/// [`OwnedFrame::Null`] or a nil array and the server runner retries them once one of the keys
+/// is written, see
+/// [`BlockingRegistry`](crate::server::blocking::BlockingRegistry).
+///
+/// All of them fail with `WRONGTYPE` if a key holds a value other than a list.
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::LPUSH { key, elements }
+        | Request::RPUSH { key, elements }
+        | Request::LPUSHX { key, elements }
+        | Request::RPUSHX { key, elements } => {
+            let side = match args {
+                Request::LPUSH { .. } | Request::LPUSHX { .. } => Side::Left,
+                _ => Side::Right,
+            };
+            let create = matches!(args, Request::LPUSH { .. } | Request::RPUSH { .. });
+
+            let length = values.update(key.clone(), |entry| {
+                if entry.is_none() && !create {
+                    return Ok(0);
+                }
+                let list = list_mut(entry)?;
+                for element in elements {
+                    push(list, side, element.clone());
+                }
+                Ok::<_, RedisProtocolError>(list.len())
+            })?;
+            Ok((length as i64).as_frame())
+        }
+        Request::LPOP { key, count } | Request::RPOP { key, count } => {
+            let side = match args {
+                Request::LPOP { .. } => Side::Left,
+                _ => Side::Right,
+            };
+
+            let popped = values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(None);
+                }
+                let list = list_mut(entry)?;
+                Ok::<_, RedisProtocolError>(Some(pop(list, side, count.unwrap_or(1))))
+            })?;
+            Ok(match (popped, count) {
+                (None, Some(_)) => null_array(),
+                (None, None) => OwnedFrame::Null,
+                (Some(elements), Some(_)) => elements.as_frame(),
+                (Some(elements), None) => elements.first().cloned().as_frame(),
+            })
+        }
+        Request::LLEN { key } => values.read(key, |value| {
+            let length = list(value)?.map_or(0, VecDeque::len);
+            Ok((length as i64).as_frame())
+        }),
+        Request::LRANGE { key, start, stop } => values.read(key, |value| {
+            let Some(list) = list(value)? else {
+                return Ok(Vec::<Bytes>::new().as_frame());
+            };
+            let elements: Vec<Bytes> = list
+                .range(range(list.len(), *start, *stop))
+                .cloned()
+                .collect();
+            Ok(elements.as_frame())
+        }),
+        Request::LINDEX {
+            key,
+            index: position,
+        } => values.read(key, |value| {
+            let element = list(value)?
+                .and_then(|list| list.get(index(list.len(), *position)?))
+                .cloned();
+            Ok(element.as_frame())
+        }),
+        Request::LSET {
+            key,
+            index: position,
+            element,
+        } => values.update(key.clone(), |entry| {
+            if entry.is_none() {
+                return Err(error_no_such_key());
+            }
+            let list = list_mut(entry)?;
+            let Some(position) = index(list.len(), *position) else {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR index out of range",
+                ));
+            };
+            list[position] = element.clone();
+            Ok("OK".as_frame())
+        }),
+        Request::LINSERT {
+            key,
+            before,
+            pivot,
+            element,
+        } => {
+            let length = values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(0);
+                }
+                let list = list_mut(entry)?;
+                let Some(position) = list.iter().position(|e| e == pivot) else {
+                    return Ok(-1);
+                };
+                let position = if *before { position } else { position + 1 };
+                list.insert(position, element.clone());
+                Ok::<_, RedisProtocolError>(list.len() as i64)
+            })?;
+            Ok(length.as_frame())
+        }
+        Request::LREM {
+            key,
+            count,
+            element,
+        } => {
+            let removed = values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(0);
+                }
+                let list = list_mut(entry)?;
+                let limit = match count.unsigned_abs() {
+                    0 => usize::MAX,
+                    limit => limit.try_into().unwrap_or(usize::MAX),
+                };
+
+                /* A negative count removes matches starting at the tail */
+                let mut matches: Vec<usize> = list
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| *e == element)
+                    .map(|(position, _)| position)
+                    .collect();
+                if *count < 0 {
+                    matches.reverse();
+                }
+                matches.truncate(limit);
+                matches.sort_unstable();
+                for (removed, position) in matches.iter().enumerate() {
+                    list.remove(position - removed);
+                }
+                Ok::<_, RedisProtocolError>(matches.len() as i64)
+            })?;
+            Ok(removed.as_frame())
+        }
+        Request::LTRIM { key, start, stop } => {
+            values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(());
+                }
+                let list = list_mut(entry)?;
+                let keep = range(list.len(), *start, *stop);
+                list.truncate(keep.end);
+                list.drain(..keep.start);
+                Ok::<_, RedisProtocolError>(())
+            })?;
+            Ok("OK".as_frame())
+        }
+        Request::LMOVE {
+            source,
+            destination,
+            from,
+            to,
+        } => Ok(lmove(values, source, destination, *from, *to)?.as_frame()),
+        Request::RPOPLPUSH {
+            source,
+            destination,
+        } => Ok(lmove(values, source, destination, Side::Right, Side::Left)?.as_frame()),
+        Request::LPOS {
+            key,
+            element,
+            rank,
+            count,
+            maxlen,
+        } => values.read(key, |value| {
+            let positions = match list(value)? {
+                Some(list) => positions(list, element, rank.unwrap_or(1), *count, *maxlen),
+                None => Vec::new(),
+            };
+            Ok(match count {
+                Some(_) => positions
+                    .iter()
+                    .map(|p| *p as i64)
+                    .collect::<Vec<i64>>()
+                    .as_frame(),
+                None => positions.first().map(|p| *p as i64).as_frame(),
+            })
+        }),
+        Request::LMPOP { keys, side, count }
+        | Request::BLMPOP {
+            keys, side, count, ..
+        } => Ok(match mpop(values, keys, *side, count.unwrap_or(1))? {
+            Some((key, elements)) => vec![key.as_frame(), elements.as_frame()].as_frame(),
+            None => null_array(),
+        }),
+        Request::BLPOP { keys, .. } | Request::BRPOP { keys, .. } => {
+            let side = match args {
+                Request::BLPOP { .. } => Side::Left,
+                _ => Side::Right,
+            };
+            Ok(match mpop(values, keys, side, 1)? {
+                Some((key, elements)) => vec![key.as_frame(), elements[0].as_frame()].as_frame(),
+                None => null_array(),
+            })
+        }
+        Request::BLMOVE {
+            source,
+            destination,
+            from,
+            to,
+            ..
+        } => Ok(lmove(values, source, destination, *from, *to)?.as_frame()),
+        _ => panic!("Expected list enum variant, but got {:?}", args.type_id()),
+    }
+}
+
+/// Return the list `value`, `None` if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a list
+pub(crate) fn list(value: Option<&Value>) -> Result<Option<&VecDeque<Bytes>>, RedisProtocolError> {
+    match value {
+        Some(value) => value.as_list().map(Some).ok_or_else(error_wrong_type),
+        None => Ok(None),
+    }
+}
+
+/// Return the list in `entry` for modification, creating an empty one if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a list
+fn list_mut(entry: &mut Option<Entry>) -> Result<&mut VecDeque<Bytes>, RedisProtocolError> {
+    let entry = entry.get_or_insert_with(|| Entry::new(VecDeque::new()));
+    entry.value.as_list_mut().ok_or_else(error_wrong_type)
+}
+
+fn push(list: &mut VecDeque<Bytes>, side: Side, element: Bytes) {
+    match side {
+        Side::Left => list.push_front(element),
+        Side::Right => list.push_back(element),
+    }
+}
+
+/// Remove up to `count` elements from `side` of `list`, in the order they are popped
+fn pop(list: &mut VecDeque<Bytes>, side: Side, count: usize) -> Vec<Bytes> {
+    let count = count.min(list.len());
+    match side {
+        Side::Left => list.drain(..count).collect(),
+        Side::Right => {
+            let length = list.len();
+            list.drain(length - count..).rev().collect()
+        }
+    }
+}
+
+/// Pop up to `count` elements from `side` of the first of `keys` that holds a list.
+///
+/// # Returns
+/// The key popped from and the elements, `None` if all lists are empty
+fn mpop<'a, S: KeyValueStore>(
+    values: &mut S,
+    keys: &'a [Bytes],
+    side: Side,
+    count: usize,
+) -> Result<Option<(&'a Bytes, Vec<Bytes>)>, RedisProtocolError> {
+    for key in keys {
+        let popped = values.update(key.clone(), |entry| {
+            if entry.is_none() {
+                return Ok(None);
+            }
+            let list = list_mut(entry)?;
+            Ok::<_, RedisProtocolError>(Some(pop(list, side, count)))
+        })?;
+        if let Some(elements) = popped {
+            return Ok(Some((key, elements)));
+        }
+    }
+    Ok(None)
+}
+
+/// Pop an element from `from` of `source` and push it to `to` of `destination`.
+///
+/// Both lists are updated at once with [`KeyValueStore::update_many`]. Nothing is popped if
+/// `destination` holds another type.
+fn lmove<S: KeyValueStore>(
+    values: &mut S,
+    source: &Bytes,
+    destination: &Bytes,
+    from: Side,
+    to: Side,
+) -> Result<Option<Bytes>, RedisProtocolError> {
+    if source == destination {
+        return values.update(source.clone(), |entry| {
+            if entry.is_none() {
+                return Ok(None);
+            }
+            let list = list_mut(entry)?;
+            let element = pop(list, from, 1).pop();
+            if let Some(element) = &element {
+                push(list, to, element.clone());
+            }
+            Ok(element)
+        });
+    }
+
+    values.update_many(&[source.clone(), destination.clone()], |entries| {
+        let [source, destination] = entries else {
+            unreachable!("Two keys were given");
+        };
+        if source.is_none() {
+            return Ok(None);
+        }
+        list(destination.as_ref().map(|e| &e.value))?;
+
+        let Some(element) = pop(list_mut(source)?, from, 1).pop() else {
+            return Ok(None);
+        };
+        push(list_mut(destination)?, to, element.clone());
+        Ok(Some(element))
+    })
+}
+
+/// Indices of the elements equal to `element` as selected by the options of `LPOS`
+fn positions(
+    list: &VecDeque<Bytes>,
+    element: &[u8],
+    rank: i64,
+    count: Option<usize>,
+    maxlen: Option<usize>,
+) -> Vec<usize> {
+    let limit = match count {
+        None => 1,
+        Some(0) => usize::MAX,
+        Some(count) => count,
+    };
+    let maxlen = maxlen.filter(|maxlen| *maxlen > 0).unwrap_or(usize::MAX);
+    let skip = (rank.unsigned_abs() - 1).try_into().unwrap_or(usize::MAX);
+
+    let matches = |(_, e): &(usize, &Bytes)| *e == element;
+    let indexed = list.iter().enumerate();
+    if rank > 0 {
+        indexed
+            .take(maxlen)
+            .filter(matches)
+            .skip(skip)
+            .take(limit)
+            .map(|(position, _)| position)
+            .collect()
+    } else {
+        indexed
+            .rev()
+            .take(maxlen)
+            .filter(matches)
+            .skip(skip)
+            .take(limit)
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+/// Index of the element at `index` counting from the head, or from the tail if it is negative
+fn index(length: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 {
+        length as i64 + index
+    } else {
+        index
+    };
+    (0..length as i64)
+        .contains(&index)
+        .then_some(index as usize)
+}
+
+/// Elements selected by the inclusive indices `start` and `stop`, following the clamping rules
+/// of `LRANGE` and `LTRIM`
+pub(crate) fn range(length: usize, start: i64, stop: i64) -> Range<usize> {
+    let length = length as i64;
+    let start = if start < 0 { length + start } else { start }.max(0);
+    let stop = if stop < 0 { length + stop } else { stop }.min(length - 1);
+    if start > stop {
+        return 0..0;
+    }
+
+    start as usize..stop as usize + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    #[test]
+    fn queue() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["RPUSH", "jobs", "a", "b", "c"]).unwrap(),
+            3.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPUSH", "jobs", "y", "z"]).unwrap(),
+            5.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "jobs", "0", "-1"]).unwrap(),
+            vec!["z", "y", "a", "b", "c"].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "jobs", "-100", "1"]).unwrap(),
+            vec!["z", "y"].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "jobs", "0", "-10"]).unwrap(),
+            Vec::<Bytes>::new().as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LINDEX", "jobs", "-1"]).unwrap(),
+            "c".as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LINSERT", "jobs", "BEFORE", "a", "x"]
+            )
+            .unwrap(),
+            6.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["RPOP", "jobs", "2"]).unwrap(),
+            vec!["c", "b"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LMOVE", "jobs", "done", "LEFT", "RIGHT"]
+            )
+            .unwrap(),
+            "z".as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LMPOP", "2", "missing", "jobs", "LEFT", "COUNT", "5"]
+            )
+            .unwrap(),
+            vec!["jobs".as_frame(), vec!["y", "x", "a"].as_frame()].as_frame()
+        );
+        assert!(!values.exists(b"jobs"));
+        assert_eq!(
+            run(handle, &mut values, &["LPOP", "jobs"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPUSHX", "jobs", "a"]).unwrap(),
+            0.as_frame()
+        );
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        assert!(run(handle, &mut values, &["LPUSH", "text", "a"]).is_err());
+        assert!(run(
+            handle,
+            &mut values,
+            &["LMOVE", "done", "text", "LEFT", "LEFT"]
+        )
+        .is_err());
+        assert_eq!(
+            run(handle, &mut values, &["LLEN", "done"]).unwrap(),
+            1.as_frame()
+        );
+    }
+
+    #[test]
+    fn search_and_remove() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["RPUSH", "l", "a", "b", "c", "1", "2", "3", "c", "c"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "c"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LPOS", "l", "c", "RANK", "-1", "COUNT", "2"]
+            )
+            .unwrap(),
+            vec![7, 6].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LPOS", "l", "c", "COUNT", "0", "MAXLEN", "7"]
+            )
+            .unwrap(),
+            vec![2, 6].as_frame()
+        );
+        assert!(run(handle, &mut values, &["LPOS", "l", "c", "RANK", "0"]).is_err());
+
+        assert_eq!(
+            run(handle, &mut values, &["LREM", "l", "-2", "c"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LTRIM", "l", "1", "-2"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "l", "0", "-1"]).unwrap(),
+            vec!["b", "c", "1", "2"].as_frame()
+        );
+        assert!(run(handle, &mut values, &["LSET", "l", "4", "x"]).is_err());
+    }
+
+    #[test]
+    fn pop_count_from_missing_list() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["RPOP", "missing"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["RPOP", "missing", "2"]).unwrap(),
+            null_array()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOP", "missing", "2"]).unwrap(),
+            null_array()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LMPOP", "1", "missing", "LEFT"]).unwrap(),
+            null_array()
+        );
+    }
+
+    #[test]
+    fn insert_edge_cases() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["LINSERT", "l", "AFTER", "a", "x"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"l"));
+
+        run(handle, &mut values, &["RPUSH", "l", "a", "b", "a"]).unwrap();
+        assert_eq!(
+            run(handle, &mut values, &["LINSERT", "l", "BEFORE", "z", "x"]).unwrap(),
+            (-1).as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LINSERT", "l", "after", "a", "x"]).unwrap(),
+            4.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "l", "0", "-1"]).unwrap(),
+            vec!["a", "x", "b", "a"].as_frame()
+        );
+        assert!(run(handle, &mut values, &["LINSERT", "l", "BESIDE", "a", "x"]).is_err());
+    }
+
+    #[test]
+    fn position_edge_cases() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "missing", "a"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "missing", "a", "COUNT", "1"]).unwrap(),
+            Vec::<i64>::new().as_frame()
+        );
+
+        run(
+            handle,
+            &mut values,
+            &["RPUSH", "l", "a", "b", "a", "b", "a"],
+        )
+        .unwrap();
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "a", "RANK", "2"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "a", "RANK", "-2"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "a", "RANK", "4"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "a", "COUNT", "0"]).unwrap(),
+            vec![0, 2, 4].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LPOS", "l", "a", "RANK", "-1", "COUNT", "0", "MAXLEN", "2"]
+            )
+            .unwrap(),
+            vec![4].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LPOS", "l", "b", "MAXLEN", "1"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert!(run(handle, &mut values, &["LPOS", "l", "a", "COUNT", "-1"]).is_err());
+        assert!(run(handle, &mut values, &["LPOS", "l", "a", "MAXLEN", "-1"]).is_err());
+    }
+
+    #[test]
+    fn move_edge_cases() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LMOVE", "missing", "l", "LEFT", "LEFT"]
+            )
+            .unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["LMOVE", "missing", "text", "LEFT", "LEFT"]
+            )
+            .unwrap(),
+            OwnedFrame::Null
+        );
+        assert!(!values.exists(b"l"));
+
+        run(handle, &mut values, &["RPUSH", "l", "a", "b", "c"]).unwrap();
+        assert_eq!(
+            run(handle, &mut values, &["LMOVE", "l", "l", "LEFT", "RIGHT"]).unwrap(),
+            "a".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LMOVE", "l", "l", "RIGHT", "RIGHT"]).unwrap(),
+            "a".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "l", "0", "-1"]).unwrap(),
+            vec!["b", "c", "a"].as_frame()
+        );
+
+        assert!(run(handle, &mut values, &["LMOVE", "text", "l", "LEFT", "LEFT"]).is_err());
+        assert!(run(handle, &mut values, &["LMOVE", "l", "text", "LEFT", "LEFT"]).is_err());
+        assert!(run(handle, &mut values, &["LMOVE", "l", "m", "UP", "LEFT"]).is_err());
+        assert_eq!(
+            run(handle, &mut values, &["LLEN", "l"]).unwrap(),
+            3.as_frame()
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &["RPOPLPUSH", "l", "m"]).unwrap(),
+            "a".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LMOVE", "m", "l", "LEFT", "LEFT"]).unwrap(),
+            "a".as_frame()
+        );
+        assert!(!values.exists(b"m"));
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "l", "0", "-1"]).unwrap(),
+            vec!["a", "b", "c"].as_frame()
+        );
+    }
+
+    #[test]
+    fn set_edge_cases() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["LSET", "l", "0", "x"])
+                .unwrap_err()
+                .details(),
+            error_no_such_key().details()
+        );
+
+        run(handle, &mut values, &["RPUSH", "l", "a", "b", "c"]).unwrap();
+        assert_eq!(
+            run(handle, &mut values, &["LSET", "l", "-1", "z"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LSET", "l", "-3", "x"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LSET", "l", "-4", "x"])
+                .unwrap_err()
+                .details(),
+            "ERR index out of range"
+        );
+        assert_eq!(
+            run(handle, &mut values, &["LSET", "l", "3", "x"])
+                .unwrap_err()
+                .details(),
+            "ERR index out of range"
+        );
+        assert!(run(handle, &mut values, &["LSET", "l", "one", "x"]).is_err());
+        assert_eq!(
+            run(handle, &mut values, &["LRANGE", "l", "0", "-1"]).unwrap(),
+            vec!["x", "b", "z"].as_frame()
+        );
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        assert!(run(handle, &mut values, &["LSET", "text", "0", "x"]).is_err());
+    }
+}
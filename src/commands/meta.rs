@@ -0,0 +1,515 @@
+use crate::commands::acl::Acl;
+use crate::commands::client::Client;
+use crate::commands::parse::Request;
+use bytes::Bytes;
+
+/// Static information about a command
+#[derive(Debug)]
+pub struct CommandInfo {
+    /// Lowercase name, subcommands are written as `command|subcommand`
+    pub name: &'static str,
+    /// ACL categories the command belongs to, without the leading `@`
+    pub categories: &'static [&'static str],
+}
+
+/// ACL categories known to Redis
+pub const CATEGORIES: &[&str] = &[
+    "keyspace",
+    "read",
+    "write",
+    "set",
+    "sortedset",
+    "list",
+    "hash",
+    "string",
+    "bitmap",
+    "hyperloglog",
+    "geo",
+    "stream",
+    "pubsub",
+    "admin",
+    "fast",
+    "slow",
+    "blocking",
+    "dangerous",
+    "connection",
+    "transaction",
+    "scripting",
+];
+
+const fn command(name: &'static str, categories: &'static [&'static str]) -> CommandInfo {
+    CommandInfo { name, categories }
+}
+
+/// Every supported command and its ACL categories
+pub const COMMANDS: &[CommandInfo] = &[
+    command("hello", &["fast", "connection"]),
+    command("auth", &["fast", "connection"]),
+    command("get", &["read", "string", "fast"]),
+    command("set", &["write", "string", "slow"]),
+    command("command", &["slow", "connection"]),
+    command("info", &["slow", "dangerous"]),
+    command("ping", &["fast", "connection"]),
+    command("select", &["fast", "connection"]),
+    command("quit", &["fast", "connection"]),
+    command("cluster", &["slow"]),
+    command("config", &["admin", "slow", "dangerous"]),
+    command("expire", &["keyspace", "write", "fast"]),
+    command("pexpire", &["keyspace", "write", "fast"]),
+    command("expireat", &["keyspace", "write", "fast"]),
+    command("pexpireat", &["keyspace", "write", "fast"]),
+    command("ttl", &["keyspace", "read", "fast"]),
+    command("pttl", &["keyspace", "read", "fast"]),
+    command("persist", &["keyspace", "write", "fast"]),
+    command("expiretime", &["keyspace", "read", "fast"]),
+    command("move", &["keyspace", "write", "fast"]),
+    command("swapdb", &["keyspace", "write", "fast", "dangerous"]),
+    command("flushdb", &["keyspace", "write", "slow", "dangerous"]),
+    command("flushall", &["keyspace", "write", "slow", "dangerous"]),
+    command("dbsize", &["keyspace", "read", "fast"]),
+    command("del", &["keyspace", "write", "slow"]),
+    command("unlink", &["keyspace", "write", "fast"]),
+    command("exists", &["keyspace", "read", "fast"]),
+    command("touch", &["keyspace", "read", "fast"]),
+    command("type", &["keyspace", "read", "fast"]),
+    command("rename", &["keyspace", "write", "slow"]),
+    command("renamenx", &["keyspace", "write", "fast"]),
+    command("copy", &["keyspace", "write", "slow"]),
+    command("scan", &["keyspace", "read", "slow"]),
+    command("keys", &["keyspace", "read", "slow", "dangerous"]),
+    command("append", &["write", "string", "fast"]),
+    command("strlen", &["read", "string", "fast"]),
+    command("getrange", &["read", "string", "slow"]),
+    command("setrange", &["write", "string", "slow"]),
+    command("getdel", &["write", "string", "fast"]),
+    command("getex", &["write", "string", "fast"]),
+    command("getset", &["write", "string", "fast"]),
+    command("incr", &["write", "string", "fast"]),
+    command("decr", &["write", "string", "fast"]),
+    command("incrby", &["write", "string", "fast"]),
+    command("decrby", &["write", "string", "fast"]),
+    command("incrbyfloat", &["write", "string", "fast"]),
+    command("mget", &["read", "string", "fast"]),
+    command("mset", &["write", "string", "slow"]),
+    command("msetnx", &["write", "string", "slow"]),
+    command("hset", &["write", "hash", "fast"]),
+    command("hmset", &["write", "hash", "fast"]),
+    command("hsetnx", &["write", "hash", "fast"]),
+    command("hget", &["read", "hash", "fast"]),
+    command("hmget", &["read", "hash", "fast"]),
+    command("hdel", &["write", "hash", "fast"]),
+    command("hlen", &["read", "hash", "fast"]),
+    command("hstrlen", &["read", "hash", "fast"]),
+    command("hexists", &["read", "hash", "fast"]),
+    command("hkeys", &["read", "hash", "slow"]),
+    command("hvals", &["read", "hash", "slow"]),
+    command("hgetall", &["read", "hash", "slow"]),
+    command("hincrby", &["write", "hash", "fast"]),
+    command("hincrbyfloat", &["write", "hash", "fast"]),
+    command("hscan", &["read", "hash", "slow"]),
+    command("hexpire", &["write", "hash", "fast"]),
+    command("hpexpire", &["write", "hash", "fast"]),
+    command("hexpireat", &["write", "hash", "fast"]),
+    command("hpexpireat", &["write", "hash", "fast"]),
+    command("httl", &["read", "hash", "fast"]),
+    command("hpttl", &["read", "hash", "fast"]),
+    command("hexpiretime", &["read", "hash", "fast"]),
+    command("hpexpiretime", &["read", "hash", "fast"]),
+    command("hpersist", &["write", "hash", "fast"]),
+    command("lpush", &["write", "list", "fast"]),
+    command("rpush", &["write", "list", "fast"]),
+    command("lpushx", &["write", "list", "fast"]),
+    command("rpushx", &["write", "list", "fast"]),
+    command("lpop", &["write", "list", "fast"]),
+    command("rpop", &["write", "list", "fast"]),
+    command("llen", &["read", "list", "fast"]),
+    command("lrange", &["read", "list", "slow"]),
+    command("lindex", &["read", "list", "slow"]),
+    command("lset", &["write", "list", "slow"]),
+    command("linsert", &["write", "list", "slow"]),
+    command("lrem", &["write", "list", "slow"]),
+    command("ltrim", &["write", "list", "slow"]),
+    command("lmove", &["write", "list", "slow"]),
+    command("rpoplpush", &["write", "list", "slow"]),
+    command("lpos", &["read", "list", "slow"]),
+    command("lmpop", &["write", "list", "slow"]),
+    command("blpop", &["write", "list", "slow", "blocking"]),
+    command("brpop", &["write", "list", "slow", "blocking"]),
+    command("blmove", &["write", "list", "slow", "blocking"]),
+    command("blmpop", &["write", "list", "slow", "blocking"]),
+    command("sadd", &["write", "set", "fast"]),
+    command("srem", &["write", "set", "fast"]),
+    command("smembers", &["read", "set", "slow"]),
+    command("sismember", &["read", "set", "fast"]),
+    command("smismember", &["read", "set", "fast"]),
+    command("scard", &["read", "set", "fast"]),
+    command("sinter", &["read", "set", "slow"]),
+    command("sunion", &["read", "set", "slow"]),
+    command("sdiff", &["read", "set", "slow"]),
+    command("sinterstore", &["write", "set", "slow"]),
+    command("sunionstore", &["write", "set", "slow"]),
+    command("sdiffstore", &["write", "set", "slow"]),
+    command("sintercard", &["read", "set", "slow"]),
+    command("srandmember", &["read", "set", "slow"]),
+    command("spop", &["write", "set", "fast"]),
+    command("smove", &["write", "set", "fast"]),
+    command("sscan", &["read", "set", "slow"]),
+    command("zadd", &["write", "sortedset", "fast"]),
+    command("zcard", &["read", "sortedset", "fast"]),
+    command("zscore", &["read", "sortedset", "fast"]),
+    command("zincrby", &["write", "sortedset", "fast"]),
+    command("zrem", &["write", "sortedset", "fast"]),
+    command("zcount", &["read", "sortedset", "fast"]),
+    command("zrange", &["read", "sortedset", "slow"]),
+    command("zrank", &["read", "sortedset", "fast"]),
+    command("zrevrank", &["read", "sortedset", "fast"]),
+    command("zpopmin", &["write", "sortedset", "fast"]),
+    command("zpopmax", &["write", "sortedset", "fast"]),
+    command("zunionstore", &["write", "sortedset", "slow"]),
+    command("zinterstore", &["write", "sortedset", "slow"]),
+    command("acl|setuser", &["admin", "slow", "dangerous"]),
+    command("acl|getuser", &["admin", "slow", "dangerous"]),
+    command("acl|deluser", &["admin", "slow", "dangerous"]),
+    command("acl|list", &["admin", "slow", "dangerous"]),
+    command("acl|whoami", &["slow"]),
+    command("acl|cat", &["slow"]),
+    command("client|setname", &["slow", "connection"]),
+    command("client|getname", &["slow", "connection"]),
+    command("client|id", &["slow", "connection"]),
+    command("client|list", &["admin", "slow", "dangerous", "connection"]),
+    command("client|info", &["slow", "connection"]),
+    command("client|kill", &["admin", "slow", "dangerous", "connection"]),
+    command(
+        "client|pause",
+        &["admin", "slow", "dangerous", "connection"],
+    ),
+    command(
+        "client|unpause",
+        &["admin", "slow", "dangerous", "connection"],
+    ),
+    command(
+        "client|no-evict",
+        &["admin", "slow", "dangerous", "connection"],
+    ),
+    command("client|setinfo", &["slow", "connection"]),
+];
+
+/// Return the [`CommandInfo`] of the command called `name`
+pub fn lookup(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|info| info.name == name)
+}
+
+impl Request {
+    /// Lowercase name of the command, see [`CommandInfo::name`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Request::HELLO { .. } => "hello",
+            Request::AUTH { .. } => "auth",
+            Request::GET { .. } => "get",
+            Request::SET { .. } => "set",
+            Request::COMMAND(_) => "command",
+            Request::INFO(_) => "info",
+            Request::PING(_) => "ping",
+            Request::SELECT(_) => "select",
+            Request::QUIT => "quit",
+            Request::CLUSTER(_) => "cluster",
+            Request::CONFIG(_) => "config",
+            Request::EXPIRE { .. } => "expire",
+            Request::PEXPIRE { .. } => "pexpire",
+            Request::EXPIREAT { .. } => "expireat",
+            Request::PEXPIREAT { .. } => "pexpireat",
+            Request::TTL { .. } => "ttl",
+            Request::PTTL { .. } => "pttl",
+            Request::PERSIST { .. } => "persist",
+            Request::EXPIRETIME { .. } => "expiretime",
+            Request::MOVE { .. } => "move",
+            Request::SWAPDB { .. } => "swapdb",
+            Request::FLUSHDB(_) => "flushdb",
+            Request::FLUSHALL(_) => "flushall",
+            Request::DBSIZE => "dbsize",
+            Request::DEL { .. } => "del",
+            Request::UNLINK { .. } => "unlink",
+            Request::EXISTS { .. } => "exists",
+            Request::TOUCH { .. } => "touch",
+            Request::TYPE { .. } => "type",
+            Request::RENAME { .. } => "rename",
+            Request::RENAMENX { .. } => "renamenx",
+            Request::COPY { .. } => "copy",
+            Request::SCAN { .. } => "scan",
+            Request::KEYS { .. } => "keys",
+            Request::APPEND { .. } => "append",
+            Request::STRLEN { .. } => "strlen",
+            Request::GETRANGE { .. } => "getrange",
+            Request::SETRANGE { .. } => "setrange",
+            Request::GETDEL { .. } => "getdel",
+            Request::GETEX { .. } => "getex",
+            Request::GETSET { .. } => "getset",
+            Request::INCR { .. } => "incr",
+            Request::DECR { .. } => "decr",
+            Request::INCRBY { .. } => "incrby",
+            Request::DECRBY { .. } => "decrby",
+            Request::INCRBYFLOAT { .. } => "incrbyfloat",
+            Request::MGET { .. } => "mget",
+            Request::MSET { .. } => "mset",
+            Request::MSETNX { .. } => "msetnx",
+            Request::HSET { .. } => "hset",
+            Request::HMSET { .. } => "hmset",
+            Request::HSETNX { .. } => "hsetnx",
+            Request::HGET { .. } => "hget",
+            Request::HMGET { .. } => "hmget",
+            Request::HDEL { .. } => "hdel",
+            Request::HLEN { .. } => "hlen",
+            Request::HSTRLEN { .. } => "hstrlen",
+            Request::HEXISTS { .. } => "hexists",
+            Request::HKEYS { .. } => "hkeys",
+            Request::HVALS { .. } => "hvals",
+            Request::HGETALL { .. } => "hgetall",
+            Request::HINCRBY { .. } => "hincrby",
+            Request::HINCRBYFLOAT { .. } => "hincrbyfloat",
+            Request::HSCAN { .. } => "hscan",
+            Request::HEXPIRE { .. } => "hexpire",
+            Request::HPEXPIRE { .. } => "hpexpire",
+            Request::HEXPIREAT { .. } => "hexpireat",
+            Request::HPEXPIREAT { .. } => "hpexpireat",
+            Request::HTTL { .. } => "httl",
+            Request::HPTTL { .. } => "hpttl",
+            Request::HEXPIRETIME { .. } => "hexpiretime",
+            Request::HPEXPIRETIME { .. } => "hpexpiretime",
+            Request::HPERSIST { .. } => "hpersist",
+            Request::LPUSH { .. } => "lpush",
+            Request::RPUSH { .. } => "rpush",
+            Request::LPUSHX { .. } => "lpushx",
+            Request::RPUSHX { .. } => "rpushx",
+            Request::LPOP { .. } => "lpop",
+            Request::RPOP { .. } => "rpop",
+            Request::LLEN { .. } => "llen",
+            Request::LRANGE { .. } => "lrange",
+            Request::LINDEX { .. } => "lindex",
+            Request::LSET { .. } => "lset",
+            Request::LINSERT { .. } => "linsert",
+            Request::LREM { .. } => "lrem",
+            Request::LTRIM { .. } => "ltrim",
+            Request::LMOVE { .. } => "lmove",
+            Request::RPOPLPUSH { .. } => "rpoplpush",
+            Request::LPOS { .. } => "lpos",
+            Request::LMPOP { .. } => "lmpop",
+            Request::BLPOP { .. } => "blpop",
+            Request::BRPOP { .. } => "brpop",
+            Request::BLMOVE { .. } => "blmove",
+            Request::BLMPOP { .. } => "blmpop",
+            Request::SADD { .. } => "sadd",
+            Request::SREM { .. } => "srem",
+            Request::SMEMBERS { .. } => "smembers",
+            Request::SISMEMBER { .. } => "sismember",
+            Request::SMISMEMBER { .. } => "smismember",
+            Request::SCARD { .. } => "scard",
+            Request::SINTER { .. } => "sinter",
+            Request::SUNION { .. } => "sunion",
+            Request::SDIFF { .. } => "sdiff",
+            Request::SINTERSTORE { .. } => "sinterstore",
+            Request::SUNIONSTORE { .. } => "sunionstore",
+            Request::SDIFFSTORE { .. } => "sdiffstore",
+            Request::SINTERCARD { .. } => "sintercard",
+            Request::SRANDMEMBER { .. } => "srandmember",
+            Request::SPOP { .. } => "spop",
+            Request::SMOVE { .. } => "smove",
+            Request::SSCAN { .. } => "sscan",
+            Request::ZADD { .. } => "zadd",
+            Request::ZCARD { .. } => "zcard",
+            Request::ZSCORE { .. } => "zscore",
+            Request::ZINCRBY { .. } => "zincrby",
+            Request::ZREM { .. } => "zrem",
+            Request::ZCOUNT { .. } => "zcount",
+            Request::ZRANGE { .. } => "zrange",
+            Request::ZRANK { .. } => "zrank",
+            Request::ZREVRANK { .. } => "zrevrank",
+            Request::ZPOPMIN { .. } => "zpopmin",
+            Request::ZPOPMAX { .. } => "zpopmax",
+            Request::ZUNIONSTORE { .. } => "zunionstore",
+            Request::ZINTERSTORE { .. } => "zinterstore",
+            Request::ACL(acl) => match acl {
+                Acl::SetUser { .. } => "acl|setuser",
+                Acl::GetUser(_) => "acl|getuser",
+                Acl::DelUser(_) => "acl|deluser",
+                Acl::List => "acl|list",
+                Acl::WhoAmI => "acl|whoami",
+                Acl::Cat(_) => "acl|cat",
+            },
+            Request::CLIENT(client) => match client {
+                Client::SetName(_) => "client|setname",
+                Client::GetName => "client|getname",
+                Client::Id => "client|id",
+                Client::List { .. } => "client|list",
+                Client::Info => "client|info",
+                Client::KillAddr(_) | Client::Kill(_) => "client|kill",
+                Client::Pause { .. } => "client|pause",
+                Client::Unpause => "client|unpause",
+                Client::NoEvict(_) => "client|no-evict",
+                Client::SetInfo { .. } => "client|setinfo",
+            },
+        }
+    }
+
+    /// Return the [`CommandInfo`] of this request
+    pub fn info(&self) -> &'static CommandInfo {
+        lookup(self.name()).expect("Every request has an entry in the command table")
+    }
+
+    /// Keys the request reads or writes
+    pub fn keys(&self) -> Vec<&Bytes> {
+        match self {
+            Request::GET { key }
+            | Request::SET { key, .. }
+            | Request::EXPIRE { key, .. }
+            | Request::PEXPIRE { key, .. }
+            | Request::EXPIREAT { key, .. }
+            | Request::PEXPIREAT { key, .. }
+            | Request::TTL { key }
+            | Request::PTTL { key }
+            | Request::PERSIST { key }
+            | Request::EXPIRETIME { key }
+            | Request::MOVE { key, .. }
+            | Request::TYPE { key }
+            | Request::APPEND { key, .. }
+            | Request::STRLEN { key }
+            | Request::GETRANGE { key, .. }
+            | Request::SETRANGE { key, .. }
+            | Request::GETDEL { key }
+            | Request::GETEX { key, .. }
+            | Request::GETSET { key, .. }
+            | Request::INCR { key }
+            | Request::DECR { key }
+            | Request::INCRBY { key, .. }
+            | Request::DECRBY { key, .. }
+            | Request::INCRBYFLOAT { key, .. }
+            | Request::HSET { key, .. }
+            | Request::HMSET { key, .. }
+            | Request::HSETNX { key, .. }
+            | Request::HGET { key, .. }
+            | Request::HMGET { key, .. }
+            | Request::HDEL { key, .. }
+            | Request::HLEN { key, .. }
+            | Request::HSTRLEN { key, .. }
+            | Request::HEXISTS { key, .. }
+            | Request::HKEYS { key, .. }
+            | Request::HVALS { key, .. }
+            | Request::HGETALL { key, .. }
+            | Request::HINCRBY { key, .. }
+            | Request::HINCRBYFLOAT { key, .. }
+            | Request::HSCAN { key, .. }
+            | Request::HEXPIRE { key, .. }
+            | Request::HPEXPIRE { key, .. }
+            | Request::HEXPIREAT { key, .. }
+            | Request::HPEXPIREAT { key, .. }
+            | Request::HTTL { key, .. }
+            | Request::HPTTL { key, .. }
+            | Request::HEXPIRETIME { key, .. }
+            | Request::HPEXPIRETIME { key, .. }
+            | Request::HPERSIST { key, .. }
+            | Request::LPUSH { key, .. }
+            | Request::RPUSH { key, .. }
+            | Request::LPUSHX { key, .. }
+            | Request::RPUSHX { key, .. }
+            | Request::LPOP { key, .. }
+            | Request::RPOP { key, .. }
+            | Request::LLEN { key, .. }
+            | Request::LRANGE { key, .. }
+            | Request::LINDEX { key, .. }
+            | Request::LSET { key, .. }
+            | Request::LINSERT { key, .. }
+            | Request::LREM { key, .. }
+            | Request::LTRIM { key, .. }
+            | Request::LPOS { key, .. }
+            | Request::SADD { key, .. }
+            | Request::SREM { key, .. }
+            | Request::SMEMBERS { key }
+            | Request::SISMEMBER { key, .. }
+            | Request::SMISMEMBER { key, .. }
+            | Request::SCARD { key }
+            | Request::SRANDMEMBER { key, .. }
+            | Request::SPOP { key, .. }
+            | Request::SSCAN { key, .. }
+            | Request::ZADD { key, .. }
+            | Request::ZCARD { key }
+            | Request::ZSCORE { key, .. }
+            | Request::ZINCRBY { key, .. }
+            | Request::ZREM { key, .. }
+            | Request::ZCOUNT { key, .. }
+            | Request::ZRANGE { key, .. }
+            | Request::ZRANK { key, .. }
+            | Request::ZREVRANK { key, .. }
+            | Request::ZPOPMIN { key, .. }
+            | Request::ZPOPMAX { key, .. } => vec![key],
+            Request::DEL { keys }
+            | Request::MGET { keys }
+            | Request::LMPOP { keys, .. }
+            | Request::BLPOP { keys, .. }
+            | Request::BRPOP { keys, .. }
+            | Request::BLMPOP { keys, .. }
+            | Request::SINTER { keys }
+            | Request::SUNION { keys }
+            | Request::SDIFF { keys }
+            | Request::SINTERCARD { keys, .. }
+            | Request::UNLINK { keys }
+            | Request::EXISTS { keys }
+            | Request::TOUCH { keys } => keys.iter().collect(),
+            Request::RENAME { key, newkey } | Request::RENAMENX { key, newkey } => {
+                vec![key, newkey]
+            }
+            Request::COPY {
+                source,
+                destination,
+                ..
+            }
+            | Request::LMOVE {
+                source,
+                destination,
+                ..
+            }
+            | Request::RPOPLPUSH {
+                source,
+                destination,
+            }
+            | Request::BLMOVE {
+                source,
+                destination,
+                ..
+            }
+            | Request::SMOVE {
+                source,
+                destination,
+                ..
+            } => vec![source, destination],
+            Request::SINTERSTORE { destination, keys }
+            | Request::SUNIONSTORE { destination, keys }
+            | Request::SDIFFSTORE { destination, keys }
+            | Request::ZUNIONSTORE {
+                destination, keys, ..
+            }
+            | Request::ZINTERSTORE {
+                destination, keys, ..
+            } => std::iter::once(destination).chain(keys).collect(),
+            Request::MSET { pairs } | Request::MSETNX { pairs } => {
+                pairs.iter().map(|(key, _)| key).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_consistent() {
+        for info in COMMANDS {
+            assert_eq!(info.name, info.name.to_lowercase());
+            for category in info.categories {
+                assert!(CATEGORIES.contains(category), "{category} is unknown");
+            }
+        }
+
+        assert_eq!(Request::ACL(Acl::WhoAmI).info().name, "acl|whoami");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::AsFrame;
+use crate::util::errors::error_wrong_arity;
+use bytes::Bytes;
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::collections::HashMap;
+
+/// Parse MGET, MSET and MSETNX
+///
+/// # Syntax
+/// ```text
+/// MGET key [key ...]
+/// MSET key value [key value ...]
+/// MSETNX key value [key value ...]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    if command == "MGET" {
+        if args.is_empty() {
+            return Err(error_wrong_arity(command));
+        }
+        return Ok(Request::MGET { keys: args });
+    }
+
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return Err(error_wrong_arity(command));
+    }
+    let mut iter = args.into_iter();
+    let mut pairs = Vec::new();
+    while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+        pairs.push((key, value));
+    }
+
+    Ok(match command {
+        "MSET" => Request::MSET { pairs },
+        _ => Request::MSETNX { pairs },
+    })
+}
+
+/// Handle MGET, MSET and MSETNX on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle `MGET`, `MSET` and `MSETNX`.
+///
+/// `MSET` and `MSETNX` write all keys at once with [`KeyValueStore::update_many`], so other
+/// connections see either none or all of the new values. Like `SET`, they discard the time to
+/// live of previous values. If a key is given more than once, its last value is written.
+///
+/// # Returns
+///  * `MGET`: the value of every key, [`OwnedFrame::Null`] for keys that don't exist or don't
+///    hold a string
+///  * `MSET`: `OK`
+///  * `MSETNX`: `1` if all keys were set, `0` if none were because at least one exists
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::MGET { keys } => {
+            /* Like in Redis, values other than strings are reported as missing */
+            let found: Vec<Option<Bytes>> = keys
+                .iter()
+                .map(|key| values.read(key, |value| value?.as_string().cloned()))
+                .collect();
+            Ok(found.as_frame())
+        }
+        Request::MSET { pairs } => {
+            let (keys, new) = distinct(pairs);
+            values.update_many(&keys, |entries| {
+                for (entry, value) in entries.iter_mut().zip(new) {
+                    *entry = Some(Entry::new(value));
+                }
+            });
+            Ok("OK".as_frame())
+        }
+        Request::MSETNX { pairs } => {
+            let (keys, new) = distinct(pairs);
+            let written = values.update_many(&keys, |entries| {
+                if entries.iter().any(Option::is_some) {
+                    return false;
+                }
+                for (entry, value) in entries.iter_mut().zip(new) {
+                    *entry = Some(Entry::new(value));
+                }
+                true
+            });
+            Ok((written as i64).as_frame())
+        }
+        _ => panic!(
+            "Expected enum variant MGET, MSET or MSETNX, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Split `pairs` into distinct keys and their values, keeping the last value of every key
+fn distinct(pairs: &[(Bytes, Bytes)]) -> (Vec<Bytes>, Vec<Bytes>) {
+    let mut indices: HashMap<&Bytes, usize> = HashMap::with_capacity(pairs.len());
+    let mut keys: Vec<Bytes> = Vec::with_capacity(pairs.len());
+    let mut values: Vec<Bytes> = Vec::with_capacity(pairs.len());
+    for (key, value) in pairs {
+        match indices.get(key) {
+            Some(index) => values[*index] = value.clone(),
+            None => {
+                indices.insert(key, keys.len());
+                keys.push(key.clone());
+                values.push(value.clone());
+            }
+        }
+    }
+    (keys, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+    use crate::storage::sharded::ShardedStore;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn multiple_keys() {
+        let store = ShardedStore::new();
+        let mut values = &store;
+        values.set(Bytes::from_static(b"a"), Bytes::from_static(b"old"));
+        values.expire(b"a", Some(SystemTime::now() + Duration::from_secs(60)));
+
+        assert_eq!(
+            run(handle, &mut values, &["MSET", "a", "1", "b", "2", "a", "3"]).unwrap(),
+            "OK".as_frame()
+        );
+        assert!(values.entry(b"a").unwrap().expires.is_none());
+        assert_eq!(
+            run(handle, &mut values, &["MGET", "a", "missing", "b"]).unwrap(),
+            vec!["3".as_frame(), OwnedFrame::Null, "2".as_frame()].as_frame()
+        );
+
+        assert_eq!(
+            run(handle, &mut values, &["MSETNX", "c", "1", "b", "x"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"c"));
+        assert_eq!(
+            run(handle, &mut values, &["MSETNX", "c", "1", "d", "2"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(store.len(), 4);
+
+        assert!(run(handle, &mut values, &["MSET", "a"]).is_err());
+        assert!(run(handle, &mut values, &["MGET"]).is_err());
+    }
+}
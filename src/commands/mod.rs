@@ -4,7 +4,7 @@
 /// authenticate and set a name for the connection.
 ///
 /// ## Syntax
-/// ```
+/// ```text
 /// HELLO [protover [AUTH username password] [SETNAME clientname]]
 /// ```
 ///
@@ -12,12 +12,18 @@
 /// [Docs/Commands/HELLO](https://redis.io/docs/latest/commands/hello/)
 pub mod hello;
 
+/// AUTH
+pub mod auth;
+
 /// GET
 pub mod get;
 
 /// Util functions
 pub mod parse;
 
+/// Names, ACL categories and keys of commands
+pub mod meta;
+
 /// SET
 pub mod set;
 
@@ -39,3 +45,48 @@ pub mod quit;
 /// CLUSTER [SHARDS, INFO, ...]
 pub mod cluster;
 pub mod config;
+
+/// MOVE, SWAPDB, FLUSHDB, FLUSHALL, DBSIZE
+pub mod db;
+
+/// DEL, UNLINK, EXISTS, TOUCH, TYPE, RENAME, RENAMENX, COPY
+pub mod keys;
+
+/// SCAN, KEYS
+pub mod scan;
+
+/// APPEND, STRLEN, GETRANGE, SETRANGE, GETDEL, GETEX, GETSET
+pub mod string;
+
+/// INCR, DECR, INCRBY, DECRBY, INCRBYFLOAT
+pub mod incr;
+
+/// MGET, MSET, MSETNX
+pub mod mget;
+
+/// HSET, HGET, HDEL, HGETALL, HINCRBY, HSCAN, HEXPIRE, HTTL, HPERSIST and the other hash commands
+pub mod hash;
+
+/// LPUSH, RPUSH, LPOP, RPOP, LRANGE, LINSERT, LMOVE, LPOS and the other list commands
+pub mod list;
+
+/// SADD, SREM, SMEMBERS, SINTER, SUNION, SDIFF, SINTERSTORE, SRANDMEMBER, SPOP and the other set
+/// commands
+pub mod sets;
+
+/// ZADD, ZRANGE, ZRANK, ZREM, ZINCRBY, ZCOUNT, ZPOPMIN, ZUNIONSTORE and the other sorted set
+/// commands
+pub mod zset;
+
+/// EXPIRE, PEXPIRE, EXPIREAT, PEXPIREAT, TTL, PTTL, PERSIST, EXPIRETIME
+pub mod expire;
+
+/// ACL SETUSER, GETUSER, DELUSER, LIST, WHOAMI, CAT
+pub mod acl;
+
+/// CLIENT SETNAME, GETNAME, ID, LIST, INFO, KILL, PAUSE, UNPAUSE, NO-EVICT, SETINFO
+pub mod client;
+
+/// Helpers shared by the tests of the command modules
+#[cfg(test)]
+mod testing;
@@ -1,9 +1,22 @@
+use crate::commands::acl::Acl;
+use crate::commands::auth::Password;
+use crate::commands::client::Client;
 use crate::commands::cluster::Cluster;
 use crate::commands::command::Command;
 use crate::commands::config::Config;
+use crate::commands::db::FlushMode;
+use crate::commands::expire::ExpireCondition;
 use crate::commands::info::Info;
+use crate::commands::list::Side;
+use crate::commands::set::SetOptions;
+use crate::commands::string::GetExExpiry;
+use crate::commands::zset::{Aggregate, ZAddOptions, ZRangeBy};
 use crate::commands::*;
+use crate::util::convert::bytes_to_string;
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use std::ops::Bound;
+use std::time::Duration;
 
 /// Wrapper for supported commands
 #[derive(Debug, Clone)]
@@ -13,33 +26,518 @@ pub enum Request {
     HELLO {
         version: Option<String>,
         clientname: Option<String>,
-        auth: Option<(String, String)>,
+        auth: Option<(String, Password)>,
+    },
+    AUTH {
+        username: Option<String>,
+        password: Password,
     },
     GET {
-        key: String,
+        key: Bytes,
     },
     SET {
-        key: String,
-        value: String,
+        key: Bytes,
+        value: Bytes,
+        options: SetOptions,
     },
     COMMAND(Command),
     INFO(Info),
-    PING(String),
+    PING(Bytes),
     SELECT(u64),
     QUIT,
     CLUSTER(Cluster),
     CONFIG(Config),
+    EXPIRE {
+        key: Bytes,
+        seconds: i64,
+        condition: Option<ExpireCondition>,
+    },
+    PEXPIRE {
+        key: Bytes,
+        milliseconds: i64,
+        condition: Option<ExpireCondition>,
+    },
+    EXPIREAT {
+        key: Bytes,
+        timestamp: i64,
+        condition: Option<ExpireCondition>,
+    },
+    PEXPIREAT {
+        key: Bytes,
+        timestamp: i64,
+        condition: Option<ExpireCondition>,
+    },
+    TTL {
+        key: Bytes,
+    },
+    PTTL {
+        key: Bytes,
+    },
+    PERSIST {
+        key: Bytes,
+    },
+    EXPIRETIME {
+        key: Bytes,
+    },
+    ACL(Acl),
+    CLIENT(Client),
+    MOVE {
+        key: Bytes,
+        db: i64,
+    },
+    SWAPDB {
+        index1: i64,
+        index2: i64,
+    },
+    FLUSHDB(FlushMode),
+    FLUSHALL(FlushMode),
+    DBSIZE,
+    DEL {
+        keys: Vec<Bytes>,
+    },
+    UNLINK {
+        keys: Vec<Bytes>,
+    },
+    EXISTS {
+        keys: Vec<Bytes>,
+    },
+    TOUCH {
+        keys: Vec<Bytes>,
+    },
+    TYPE {
+        key: Bytes,
+    },
+    RENAME {
+        key: Bytes,
+        newkey: Bytes,
+    },
+    RENAMENX {
+        key: Bytes,
+        newkey: Bytes,
+    },
+    COPY {
+        source: Bytes,
+        destination: Bytes,
+        db: Option<i64>,
+        replace: bool,
+    },
+    SCAN {
+        cursor: u64,
+        pattern: Option<Bytes>,
+        count: Option<usize>,
+        kind: Option<String>,
+    },
+    KEYS {
+        pattern: Bytes,
+    },
+    APPEND {
+        key: Bytes,
+        value: Bytes,
+    },
+    STRLEN {
+        key: Bytes,
+    },
+    GETRANGE {
+        key: Bytes,
+        start: i64,
+        end: i64,
+    },
+    SETRANGE {
+        key: Bytes,
+        offset: usize,
+        value: Bytes,
+    },
+    GETDEL {
+        key: Bytes,
+    },
+    GETEX {
+        key: Bytes,
+        expiry: Option<GetExExpiry>,
+    },
+    GETSET {
+        key: Bytes,
+        value: Bytes,
+    },
+    INCR {
+        key: Bytes,
+    },
+    DECR {
+        key: Bytes,
+    },
+    INCRBY {
+        key: Bytes,
+        increment: i64,
+    },
+    DECRBY {
+        key: Bytes,
+        decrement: i64,
+    },
+    INCRBYFLOAT {
+        key: Bytes,
+        increment: f64,
+    },
+    MGET {
+        keys: Vec<Bytes>,
+    },
+    MSET {
+        pairs: Vec<(Bytes, Bytes)>,
+    },
+    MSETNX {
+        pairs: Vec<(Bytes, Bytes)>,
+    },
+    HSET {
+        key: Bytes,
+        pairs: Vec<(Bytes, Bytes)>,
+    },
+    HMSET {
+        key: Bytes,
+        pairs: Vec<(Bytes, Bytes)>,
+    },
+    HSETNX {
+        key: Bytes,
+        field: Bytes,
+        value: Bytes,
+    },
+    HGET {
+        key: Bytes,
+        field: Bytes,
+    },
+    HMGET {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HDEL {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HLEN {
+        key: Bytes,
+    },
+    HSTRLEN {
+        key: Bytes,
+        field: Bytes,
+    },
+    HEXISTS {
+        key: Bytes,
+        field: Bytes,
+    },
+    HKEYS {
+        key: Bytes,
+    },
+    HVALS {
+        key: Bytes,
+    },
+    HGETALL {
+        key: Bytes,
+    },
+    HINCRBY {
+        key: Bytes,
+        field: Bytes,
+        increment: i64,
+    },
+    HINCRBYFLOAT {
+        key: Bytes,
+        field: Bytes,
+        increment: f64,
+    },
+    HSCAN {
+        key: Bytes,
+        cursor: u64,
+        pattern: Option<Bytes>,
+        count: Option<usize>,
+        novalues: bool,
+    },
+    HEXPIRE {
+        key: Bytes,
+        seconds: i64,
+        condition: Option<ExpireCondition>,
+        fields: Vec<Bytes>,
+    },
+    HPEXPIRE {
+        key: Bytes,
+        milliseconds: i64,
+        condition: Option<ExpireCondition>,
+        fields: Vec<Bytes>,
+    },
+    HEXPIREAT {
+        key: Bytes,
+        timestamp: i64,
+        condition: Option<ExpireCondition>,
+        fields: Vec<Bytes>,
+    },
+    HPEXPIREAT {
+        key: Bytes,
+        timestamp: i64,
+        condition: Option<ExpireCondition>,
+        fields: Vec<Bytes>,
+    },
+    HTTL {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HPTTL {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HEXPIRETIME {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HPEXPIRETIME {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    HPERSIST {
+        key: Bytes,
+        fields: Vec<Bytes>,
+    },
+    LPUSH {
+        key: Bytes,
+        elements: Vec<Bytes>,
+    },
+    RPUSH {
+        key: Bytes,
+        elements: Vec<Bytes>,
+    },
+    LPUSHX {
+        key: Bytes,
+        elements: Vec<Bytes>,
+    },
+    RPUSHX {
+        key: Bytes,
+        elements: Vec<Bytes>,
+    },
+    LPOP {
+        key: Bytes,
+        count: Option<usize>,
+    },
+    RPOP {
+        key: Bytes,
+        count: Option<usize>,
+    },
+    LLEN {
+        key: Bytes,
+    },
+    LRANGE {
+        key: Bytes,
+        start: i64,
+        stop: i64,
+    },
+    LINDEX {
+        key: Bytes,
+        index: i64,
+    },
+    LSET {
+        key: Bytes,
+        index: i64,
+        element: Bytes,
+    },
+    LINSERT {
+        key: Bytes,
+        before: bool,
+        pivot: Bytes,
+        element: Bytes,
+    },
+    LREM {
+        key: Bytes,
+        count: i64,
+        element: Bytes,
+    },
+    LTRIM {
+        key: Bytes,
+        start: i64,
+        stop: i64,
+    },
+    LMOVE {
+        source: Bytes,
+        destination: Bytes,
+        from: Side,
+        to: Side,
+    },
+    RPOPLPUSH {
+        source: Bytes,
+        destination: Bytes,
+    },
+    LPOS {
+        key: Bytes,
+        element: Bytes,
+        rank: Option<i64>,
+        count: Option<usize>,
+        maxlen: Option<usize>,
+    },
+    LMPOP {
+        keys: Vec<Bytes>,
+        side: Side,
+        count: Option<usize>,
+    },
+    BLPOP {
+        keys: Vec<Bytes>,
+        timeout: Duration,
+    },
+    BRPOP {
+        keys: Vec<Bytes>,
+        timeout: Duration,
+    },
+    BLMOVE {
+        source: Bytes,
+        destination: Bytes,
+        from: Side,
+        to: Side,
+        timeout: Duration,
+    },
+    BLMPOP {
+        keys: Vec<Bytes>,
+        side: Side,
+        count: Option<usize>,
+        timeout: Duration,
+    },
+    SADD {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    SREM {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    SMEMBERS {
+        key: Bytes,
+    },
+    SISMEMBER {
+        key: Bytes,
+        member: Bytes,
+    },
+    SMISMEMBER {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    SCARD {
+        key: Bytes,
+    },
+    SINTER {
+        keys: Vec<Bytes>,
+    },
+    SUNION {
+        keys: Vec<Bytes>,
+    },
+    SDIFF {
+        keys: Vec<Bytes>,
+    },
+    SINTERSTORE {
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    },
+    SUNIONSTORE {
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    },
+    SDIFFSTORE {
+        destination: Bytes,
+        keys: Vec<Bytes>,
+    },
+    SINTERCARD {
+        keys: Vec<Bytes>,
+        limit: usize,
+    },
+    SRANDMEMBER {
+        key: Bytes,
+        count: Option<i64>,
+    },
+    SPOP {
+        key: Bytes,
+        count: Option<usize>,
+    },
+    SMOVE {
+        source: Bytes,
+        destination: Bytes,
+        member: Bytes,
+    },
+    SSCAN {
+        key: Bytes,
+        cursor: u64,
+        pattern: Option<Bytes>,
+        count: Option<usize>,
+    },
+    ZADD {
+        key: Bytes,
+        options: ZAddOptions,
+        pairs: Vec<(f64, Bytes)>,
+    },
+    ZCARD {
+        key: Bytes,
+    },
+    ZSCORE {
+        key: Bytes,
+        member: Bytes,
+    },
+    ZINCRBY {
+        key: Bytes,
+        increment: f64,
+        member: Bytes,
+    },
+    ZREM {
+        key: Bytes,
+        members: Vec<Bytes>,
+    },
+    ZCOUNT {
+        key: Bytes,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    },
+    ZRANGE {
+        key: Bytes,
+        range: ZRangeBy,
+        rev: bool,
+        limit: Option<(i64, i64)>,
+        withscores: bool,
+    },
+    ZRANK {
+        key: Bytes,
+        member: Bytes,
+        withscore: bool,
+    },
+    ZREVRANK {
+        key: Bytes,
+        member: Bytes,
+        withscore: bool,
+    },
+    ZPOPMIN {
+        key: Bytes,
+        count: Option<usize>,
+    },
+    ZPOPMAX {
+        key: Bytes,
+        count: Option<usize>,
+    },
+    ZUNIONSTORE {
+        destination: Bytes,
+        keys: Vec<Bytes>,
+        weights: Vec<f64>,
+        aggregate: Aggregate,
+    },
+    ZINTERSTORE {
+        destination: Bytes,
+        keys: Vec<Bytes>,
+        weights: Vec<f64>,
+        aggregate: Aggregate,
+    },
 }
 
 /// Parse incoming commands
 ///
+/// Keys and values are kept as raw [`Bytes`], so binary payloads pass through unchanged.
+///
 /// # Returns
 /// Result<[`Request`], [`RedisProtocolError`]>
-pub fn parse(mut query: Vec<String>) -> Result<Request, RedisProtocolError> {
-    let args = query.split_off(1);
+pub fn parse(mut query: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let args = query.split_off(query.len().min(1));
     if let Some(command) = query.first() {
-        match command.to_uppercase().as_ref() {
+        let command = bytes_to_string(command);
+        let command = command.to_uppercase();
+        match command.as_ref() {
             "HELLO" => hello::parse(args),
+            "AUTH" => auth::parse(args),
+            "ACL" => acl::parse(args),
+            "CLIENT" => client::parse(args),
             "GET" => get::parse(args),
             "SET" => set::parse(args),
             "COMMAND" => command::parse(args),
@@ -49,10 +547,40 @@ pub fn parse(mut query: Vec<String>) -> Result<Request, RedisProtocolError> {
             "QUIT" => quit::parse(args),
             "CLUSTER" => cluster::parse(args),
             "CONFIG" => config::parse(args),
+            "EXPIRE" | "PEXPIRE" | "EXPIREAT" | "PEXPIREAT" | "TTL" | "PTTL" | "PERSIST"
+            | "EXPIRETIME" => expire::parse(&command, args),
+            "MOVE" | "SWAPDB" | "FLUSHDB" | "FLUSHALL" | "DBSIZE" => db::parse(&command, args),
+            "DEL" | "UNLINK" | "EXISTS" | "TOUCH" | "TYPE" | "RENAME" | "RENAMENX" | "COPY" => {
+                keys::parse(&command, args)
+            }
+            "SCAN" | "KEYS" => scan::parse(&command, args),
+            "APPEND" | "STRLEN" | "GETRANGE" | "SETRANGE" | "GETDEL" | "GETEX" | "GETSET" => {
+                string::parse(&command, args)
+            }
+            "INCR" | "DECR" | "INCRBY" | "DECRBY" | "INCRBYFLOAT" => incr::parse(&command, args),
+            "MGET" | "MSET" | "MSETNX" => mget::parse(&command, args),
+            "HSET" | "HMSET" | "HSETNX" | "HGET" | "HMGET" | "HDEL" | "HLEN" | "HSTRLEN"
+            | "HEXISTS" | "HKEYS" | "HVALS" | "HGETALL" | "HINCRBY" | "HINCRBYFLOAT" | "HSCAN"
+            | "HEXPIRE" | "HPEXPIRE" | "HEXPIREAT" | "HPEXPIREAT" | "HTTL" | "HPTTL"
+            | "HEXPIRETIME" | "HPEXPIRETIME" | "HPERSIST" => hash::parse(&command, args),
+            "LPUSH" | "RPUSH" | "LPUSHX" | "RPUSHX" | "LPOP" | "RPOP" | "LLEN" | "LRANGE"
+            | "LINDEX" | "LSET" | "LINSERT" | "LREM" | "LTRIM" | "LMOVE" | "RPOPLPUSH" | "LPOS"
+            | "LMPOP" => list::parse(&command, args),
+            "BLPOP" | "BRPOP" | "BLMOVE" | "BLMPOP" => list::parse(&command, args),
+            "SADD" | "SREM" | "SMEMBERS" | "SISMEMBER" | "SMISMEMBER" | "SCARD" | "SINTER"
+            | "SUNION" | "SDIFF" | "SINTERSTORE" | "SUNIONSTORE" | "SDIFFSTORE" | "SINTERCARD"
+            | "SRANDMEMBER" | "SPOP" | "SMOVE" | "SSCAN" => sets::parse(&command, args),
+            "ZADD" | "ZCARD" | "ZSCORE" | "ZINCRBY" | "ZREM" | "ZCOUNT" | "ZRANGE" | "ZRANK"
+            | "ZREVRANK" | "ZPOPMIN" | "ZPOPMAX" | "ZUNIONSTORE" | "ZINTERSTORE" => {
+                zset::parse(&command, args)
+            }
 
             _ => Err(RedisProtocolError::new(
                 RedisProtocolErrorKind::Parse,
-                format!("Unsupported command: {command} {}", args.join(" ")),
+                format!(
+                    "Unsupported command: {command} {}",
+                    bytes_to_string(&args.join(&b' '))
+                ),
             )),
         }
     } else {
@@ -67,6 +595,26 @@ pub fn parse(mut query: Vec<String>) -> Result<Request, RedisProtocolError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_binary_set() {
+        let value = Bytes::from_static(&[0x00, 0xff, 0x80, 0x0a]);
+        let query = vec![
+            Bytes::from_static(b"set"),
+            Bytes::from_static(b"key"),
+            value.clone(),
+        ];
+
+        if let Request::SET {
+            key, value: parsed, ..
+        } = parse(query).unwrap()
+        {
+            assert_eq!(key, Bytes::from_static(b"key"));
+            assert_eq!(parsed, value);
+        } else {
+            panic!("parsed wrong variant")
+        }
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serialize_hello() {
@@ -1,5 +1,6 @@
 use crate::commands::parse::Request;
 use crate::util::convert::AsFrame;
+use bytes::Bytes;
 use redis_protocol::error::RedisProtocolError;
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
@@ -7,8 +8,8 @@ use std::any::Any;
 /// # Implementation
 ///
 /// Merge all strings in args and return [`Request::PING`] with resulting string.
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
-    Ok(Request::PING(args.join(" ")))
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    Ok(Request::PING(Bytes::from(args.join(&b' '))))
 }
 
 /// Return passed string.
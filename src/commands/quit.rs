@@ -1,5 +1,6 @@
 use crate::commands::parse::Request;
 use crate::util::convert::AsFrame;
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
@@ -7,7 +8,7 @@ use std::any::Any;
 /// # Implementation
 ///
 /// Ensure args is empty, then return [`Request::QUIT`]
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
     if !args.is_empty() {
         Err(RedisProtocolError::new(
             RedisProtocolErrorKind::Parse,
@@ -0,0 +1,190 @@
+use crate::commands::keys::type_name;
+use crate::commands::parse::Request;
+use crate::storage::memory::MemoryStore;
+use crate::storage::KeyValueStore;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_invalid_cursor, error_not_integer, error_syntax, error_wrong_arity,
+};
+use crate::util::glob;
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+
+/// Number of keys `SCAN` visits per call unless `COUNT` is given, like in Redis
+pub const DEFAULT_COUNT: usize = 10;
+
+/// Value types `SCAN TYPE` accepts
+const TYPES: &[&str] = &["string", "list", "set", "zset", "hash", "stream"];
+
+/// Parse SCAN and KEYS
+///
+/// # Syntax
+/// ```text
+/// SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]
+/// KEYS pattern
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut iter = args.into_iter();
+
+    if command == "KEYS" {
+        return match (iter.next(), iter.next()) {
+            (Some(pattern), None) => Ok(Request::KEYS { pattern }),
+            _ => Err(error_wrong_arity(command)),
+        };
+    }
+
+    let cursor = iter.next().ok_or_else(|| error_wrong_arity(command))?;
+    let cursor = bytes_to_string(&cursor)
+        .parse()
+        .map_err(|_| error_invalid_cursor())?;
+
+    let (mut pattern, mut count, mut kind) = (None, None, None);
+    while let Some(option) = iter.next() {
+        let value = iter.next().ok_or_else(error_syntax)?;
+        match bytes_to_string(&option).to_uppercase().as_str() {
+            "MATCH" => pattern = Some(value),
+            "COUNT" => {
+                let value: i64 = bytes_to_string(&value)
+                    .parse()
+                    .map_err(|_| error_not_integer())?;
+                if value < 1 {
+                    return Err(error_syntax());
+                }
+                count = Some(value as usize);
+            }
+            "TYPE" => {
+                let name = bytes_to_string(&value).to_lowercase();
+                if !TYPES.contains(&name.as_str()) {
+                    return Err(RedisProtocolError::new(
+                        RedisProtocolErrorKind::Parse,
+                        format!("ERR unknown type name '{name}'"),
+                    ));
+                }
+                kind = Some(name);
+            }
+            _ => return Err(error_syntax()),
+        }
+    }
+
+    Ok(Request::SCAN {
+        cursor,
+        pattern,
+        count,
+        kind,
+    })
+}
+
+/// Handle SCAN and KEYS on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    handle(&MemoryStore::new(), args)
+}
+
+/// Handle SCAN and KEYS.
+///
+/// # Returns
+///  * `SCAN`: the cursor to continue with, `0` once the iteration is complete, and the keys
+///    found in this batch that match the pattern and type. The batch may be empty even if the
+///    iteration is not complete.
+///  * `KEYS`: every key matching the pattern
+pub fn handle<S: KeyValueStore>(
+    values: &S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::SCAN {
+            cursor,
+            pattern,
+            count,
+            kind,
+        } => {
+            let (next, keys) = values.scan(*cursor, count.unwrap_or(DEFAULT_COUNT));
+            let keys: Vec<Bytes> = keys
+                .into_iter()
+                .filter(|key| pattern.as_ref().is_none_or(|p| matches(p, key)))
+                .filter(|key| {
+                    kind.as_ref()
+                        .is_none_or(|kind| type_name(values, key) == Some(kind.as_str()))
+                })
+                .collect();
+
+            Ok(OwnedFrame::Array {
+                data: vec![next.to_string().as_frame(), keys.as_frame()],
+                attributes: None,
+            })
+        }
+        Request::KEYS { pattern } => {
+            let mut keys = Vec::new();
+            let mut cursor = 0;
+            loop {
+                let (next, batch) = values.scan(cursor, 1000);
+                keys.extend(batch.into_iter().filter(|key| matches(pattern, key)));
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+            Ok(keys.as_frame())
+        }
+        _ => panic!(
+            "Expected enum variant SCAN or KEYS, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Like Redis, `*` matches every key without looking at it, including the empty key
+pub(crate) fn matches(pattern: &[u8], key: &[u8]) -> bool {
+    pattern == b"*" || glob::matches(pattern, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    #[test]
+    fn scan_and_keys() {
+        let mut values = MemoryStore::new();
+        for i in 0..25 {
+            values.set(Bytes::from(format!("user:{i}")), Bytes::new());
+            values.set(Bytes::from(format!("session:{i}")), Bytes::new());
+        }
+
+        let mut found = 0;
+        let mut cursor = "0".to_string();
+        loop {
+            let reply = run(
+                handle,
+                &values,
+                &["SCAN", &cursor, "MATCH", "user:*", "COUNT", "7"],
+            )
+            .unwrap();
+            let OwnedFrame::Array { data, .. } = reply else {
+                panic!("SCAN should return an array")
+            };
+            let (OwnedFrame::BlobString { data: next, .. }, OwnedFrame::Array { data: keys, .. }) =
+                (&data[0], &data[1])
+            else {
+                panic!("SCAN should return a cursor and keys")
+            };
+            found += keys.len();
+            cursor = String::from_utf8(next.clone()).unwrap();
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(found, 25);
+
+        let OwnedFrame::Array { data, .. } = run(handle, &values, &["KEYS", "session:1?"]).unwrap()
+        else {
+            panic!("KEYS should return an array")
+        };
+        assert_eq!(data.len(), 10);
+
+        assert!(run(handle, &values, &["SCAN", "x"]).is_err());
+        assert!(run(handle, &values, &["SCAN", "0", "COUNT", "0"]).is_err());
+        assert!(run(handle, &values, &["SCAN", "0", "TYPE", "nope"]).is_err());
+    }
+}
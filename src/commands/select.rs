@@ -1,10 +1,13 @@
 use crate::commands::parse::Request;
-use crate::util::convert::AsFrame;
+use crate::server::session::Session;
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::error_db_index_out_of_range;
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
 
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
     if args.len() != 1 {
         return Err(RedisProtocolError::new(
             RedisProtocolErrorKind::Parse,
@@ -13,7 +16,7 @@ pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
     }
 
     if let Some(arg) = args.first() {
-        if let Ok(number) = arg.parse::<u64>() {
+        if let Ok(number) = bytes_to_string(arg).parse::<u64>() {
             return Ok(Request::SELECT(number));
         }
     }
@@ -24,18 +27,57 @@ pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
     ))
 }
 
-/// Return `Ok` if requested database index is 0. Return Error otherwise.
+/// Return `OK` if requested database index is 0, the only database of a default server.
+/// Return Error otherwise. See [`handle`] for multiple databases.
 pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
     if let Request::SELECT(db) = args {
         return if db.eq(&0) {
-            Ok("Ok".as_frame())
+            Ok("OK".as_frame())
         } else {
-            Err(RedisProtocolError::new(
-                RedisProtocolErrorKind::Parse,
-                format!("Invalid index: {}", db),
-            ))
+            Err(error_db_index_out_of_range())
         };
     }
 
     panic!("Expected enum variant SELECT, but got {:?}", args.type_id())
 }
+
+/// Select database `index` for all following commands of `session`.
+///
+/// # Arguments
+///
+///  * `session` - The connection issuing the request
+///  * `databases` - Number of databases available, valid indices are `0..databases`
+///  * `args` - The requested database index
+pub fn handle(
+    session: &mut Session,
+    databases: usize,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    if let Request::SELECT(db) = args {
+        return match usize::try_from(*db) {
+            Ok(db) if db < databases => {
+                session.select(db);
+                Ok("OK".as_frame())
+            }
+            _ => Err(error_db_index_out_of_range()),
+        };
+    }
+
+    panic!("Expected enum variant SELECT, but got {:?}", args.type_id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_changes_session_db() {
+        let mut session = Session::new(1, "127.0.0.1:6379".parse().unwrap());
+
+        assert!(handle(&mut session, 16, &Request::SELECT(3)).is_ok());
+        assert_eq!(session.db(), 3);
+
+        assert!(handle(&mut session, 16, &Request::SELECT(16)).is_err());
+        assert_eq!(session.db(), 3);
+    }
+}
@@ -1,57 +1,312 @@
 use crate::commands::parse::Request;
-use crate::util::convert::AsFrame;
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_invalid_expire, error_not_integer, error_syntax, error_wrong_type,
+};
+use bytes::Bytes;
 use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
 use redis_protocol::resp3::types::OwnedFrame;
 use std::any::Any;
-use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Only set the key if it does (`XX`) or does not (`NX`) exist yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetCondition {
+    Nx,
+    Xx,
+}
+
+/// Time to live of the new value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetExpiry {
+    /// Expire after the given number of seconds
+    Ex(u64),
+    /// Expire after the given number of milliseconds
+    Px(u64),
+    /// Expire at the given unix time in seconds
+    ExAt(u64),
+    /// Expire at the given unix time in milliseconds
+    PxAt(u64),
+    /// Retain the time to live of the previous value
+    KeepTtl,
+}
+
+impl SetExpiry {
+    /// Point in time the new value expires, relative to `now`.
+    ///
+    /// # Returns
+    /// `None` for [`SetExpiry::KeepTtl`]
+    pub fn deadline(&self, now: SystemTime) -> Option<SystemTime> {
+        match *self {
+            SetExpiry::Ex(seconds) => Some(now + Duration::from_secs(seconds)),
+            SetExpiry::Px(millis) => Some(now + Duration::from_millis(millis)),
+            SetExpiry::ExAt(seconds) => Some(UNIX_EPOCH + Duration::from_secs(seconds)),
+            SetExpiry::PxAt(millis) => Some(UNIX_EPOCH + Duration::from_millis(millis)),
+            SetExpiry::KeepTtl => None,
+        }
+    }
+
+    /// Name of the `SET` option, e.g. `EX`
+    pub fn option(&self) -> &'static str {
+        match self {
+            SetExpiry::Ex(_) => "EX",
+            SetExpiry::Px(_) => "PX",
+            SetExpiry::ExAt(_) => "EXAT",
+            SetExpiry::PxAt(_) => "PXAT",
+            SetExpiry::KeepTtl => "KEEPTTL",
+        }
+    }
+}
+
+/// Optional arguments of `SET`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetOptions {
+    pub condition: Option<SetCondition>,
+    /// Reply with the previous value instead of `OK`
+    pub get: bool,
+    pub expiry: Option<SetExpiry>,
+}
 
 /// Parse command and write to enum for easy handling
 ///
 /// # Syntax
-/// ```
+/// ```text
 /// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
 ///   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
 /// ```
-pub fn parse(args: Vec<String>) -> Result<Request, RedisProtocolError> {
+pub fn parse(args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
     let mut iter = args.into_iter();
-    let key = iter.next();
-    let value = iter.next();
-    if key.is_none() || value.is_none() {
-        Err(RedisProtocolError::new(
-            RedisProtocolErrorKind::Parse,
-            "Required arguments: Key, Value",
-        ))
-    } else {
-        Ok(Request::SET {
-            key: key.unwrap(),
-            value: value.unwrap(),
-        })
-    }
-}
-
-/// Default reply to SET: "Ok"
-#[allow(dead_code)]
-pub fn default_handle(args: Request) -> Result<OwnedFrame, RedisProtocolError> {
-    {
-        let mut dummy: HashMap<String, String> = HashMap::new();
-        handle(&mut dummy, &args)
+    let (key, value) = match (iter.next(), iter.next()) {
+        (Some(key), Some(value)) => (key, value),
+        _ => {
+            return Err(RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                "Required arguments: Key, Value",
+            ))
+        }
+    };
+
+    let mut options = SetOptions::default();
+    /* Like Redis, repeating an option is fine, but conflicting options are not */
+    while let Some(arg) = iter.next() {
+        let option = bytes_to_string(&arg).to_uppercase();
+        match option.as_str() {
+            "NX" | "XX" => {
+                let condition = match option.as_str() {
+                    "NX" => SetCondition::Nx,
+                    _ => SetCondition::Xx,
+                };
+                if options
+                    .condition
+                    .is_some_and(|previous| previous != condition)
+                {
+                    return Err(error_syntax());
+                }
+                options.condition = Some(condition);
+            }
+            "GET" => options.get = true,
+            "EX" | "PX" | "EXAT" | "PXAT" | "KEEPTTL" => {
+                if options
+                    .expiry
+                    .is_some_and(|previous| previous.option() != option)
+                {
+                    return Err(error_syntax());
+                }
+                options.expiry = Some(match option.as_str() {
+                    "KEEPTTL" => SetExpiry::KeepTtl,
+                    unit => {
+                        let time = parse_expire_time("set", iter.next())?;
+                        match unit {
+                            "EX" => SetExpiry::Ex(time),
+                            "PX" => SetExpiry::Px(time),
+                            "EXAT" => SetExpiry::ExAt(time),
+                            _ => SetExpiry::PxAt(time),
+                        }
+                    }
+                });
+            }
+            _ => return Err(error_syntax()),
+        }
+    }
+
+    Ok(Request::SET {
+        key,
+        value,
+        options,
+    })
+}
+
+/// Parse the argument following `EX`, `PX`, `EXAT` or `PXAT`.
+///
+/// Like Redis, reject times that are not positive or would overflow once converted to
+/// milliseconds.
+pub(crate) fn parse_expire_time(
+    command: &str,
+    arg: Option<Bytes>,
+) -> Result<u64, RedisProtocolError> {
+    let arg = arg.ok_or_else(error_syntax)?;
+    let time: i64 = bytes_to_string(&arg)
+        .parse()
+        .map_err(|_| error_not_integer())?;
+
+    if time <= 0 || time > i64::MAX / 1000 {
+        return Err(error_invalid_expire(command));
     }
+
+    Ok(time as u64)
 }
 
-/// Set the given key to the given value
+/// Default reply to SET: "OK"
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Set the given key to the given value, honoring the [`SetOptions`] of the request.
 ///
-/// # TODO:
+/// Checking the condition and writing the value happen atomically.
 ///
-/// Redis allows to specify whether or not to return the previously stored value - if any.
-/// Rusts `.insert()` also returns the previous value, so it would be easy to do.
-pub fn handle(
-    values: &mut HashMap<String, String>,
+/// # Returns
+///  * With `GET`: the previous value or [`OwnedFrame::Null`], or a `WRONGTYPE` error without
+///    setting the key if the previous value is not a string
+///  * Otherwise: `OK`, or [`OwnedFrame::Null`] if the `NX`/`XX` condition was not met
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
     args: &Request,
 ) -> Result<OwnedFrame, RedisProtocolError> {
-    if let Request::SET { key, value } = args {
-        values.insert(key.to_owned(), value.to_owned());
-        return Ok("Ok".as_frame());
+    if let Request::SET {
+        key,
+        value,
+        options,
+    } = args
+    {
+        let (written, previous) = values.update(key.clone(), |entry| {
+            /* With GET, a previous value that is not a string fails the whole command */
+            let previous = match entry.as_ref().map(|e| e.value.as_string()) {
+                Some(None) if options.get => return Err(error_wrong_type()),
+                _ => entry.take(),
+            };
+            let written = match options.condition {
+                Some(SetCondition::Nx) => previous.is_none(),
+                Some(SetCondition::Xx) => previous.is_some(),
+                None => true,
+            };
+
+            *entry = if written {
+                let expires = match options.expiry {
+                    Some(SetExpiry::KeepTtl) => previous.as_ref().and_then(|e| e.expires),
+                    Some(expiry) => expiry.deadline(SystemTime::now()),
+                    None => None,
+                };
+                Some(Entry {
+                    value: value.clone().into(),
+                    expires,
+                })
+            } else {
+                previous.clone()
+            };
+
+            Ok((written, previous.map(|e| e.value)))
+        })?;
+
+        return Ok(match (options.get, written, previous) {
+            (true, _, Some(Value::String(previous))) => previous.as_frame(),
+            (true, _, _) | (false, false, _) => OwnedFrame::Null,
+            (false, true, _) => "OK".as_frame(),
+        });
     }
 
     panic!("Expected enum variant SET, but got {:?}", args.type_id())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(args: &[&'static str]) -> Result<Request, RedisProtocolError> {
+        parse(
+            args.iter()
+                .map(|arg| Bytes::from_static(arg.as_bytes()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_options() {
+        let Request::SET { options, .. } = request(&["k", "v", "nx", "GET", "PX", "100"]).unwrap()
+        else {
+            panic!("Expected SET")
+        };
+
+        assert_eq!(
+            options,
+            SetOptions {
+                condition: Some(SetCondition::Nx),
+                get: true,
+                expiry: Some(SetExpiry::Px(100)),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_conflicting_options() {
+        assert!(request(&["k", "v", "NX", "XX"]).is_err());
+        assert!(request(&["k", "v", "EX", "1", "KEEPTTL"]).is_err());
+        assert!(request(&["k", "v", "EX"]).is_err());
+        assert!(request(&["k", "v", "EX", "0"]).is_err());
+        assert!(request(&["k", "v", "EX", "one"]).is_err());
+        assert!(request(&["k", "v", "SOMETIMES"]).is_err());
+    }
+
+    #[test]
+    fn parse_repeated_options() {
+        let Request::SET { options, .. } =
+            request(&["k", "v", "NX", "nx", "GET", "GET", "EX", "1", "EX", "2"]).unwrap()
+        else {
+            panic!("Expected SET")
+        };
+        assert_eq!(options.condition, Some(SetCondition::Nx));
+        assert_eq!(options.expiry, Some(SetExpiry::Ex(2)));
+
+        let unknown = request(&["k", "v", "SOMETIMES", "later"]).unwrap_err();
+        assert_eq!(unknown.details(), error_syntax().details());
+    }
+
+    #[test]
+    fn handle_condition_and_get() {
+        let mut store = MemoryStore::new();
+
+        let nx = request(&["k", "a", "NX", "GET"]).unwrap();
+        assert_eq!(handle(&mut store, &nx).unwrap(), OwnedFrame::Null);
+        assert_eq!(handle(&mut store, &nx).unwrap(), "a".as_frame());
+        assert_eq!(store.get(b"k"), Some(Bytes::from_static(b"a").into()));
+
+        let xx = request(&["k", "b", "XX"]).unwrap();
+        assert_eq!(handle(&mut store, &xx).unwrap(), "OK".as_frame());
+        assert_eq!(store.get(b"k"), Some(Bytes::from_static(b"b").into()));
+
+        let missing = request(&["other", "b", "XX"]).unwrap();
+        assert_eq!(handle(&mut store, &missing).unwrap(), OwnedFrame::Null);
+        assert!(!store.exists(b"other"));
+    }
+
+    #[test]
+    fn handle_keepttl() {
+        let mut store = MemoryStore::new();
+
+        handle(&mut store, &request(&["k", "a", "EX", "100"]).unwrap()).unwrap();
+        handle(&mut store, &request(&["k", "b", "KEEPTTL"]).unwrap()).unwrap();
+        let expires = store.update(Bytes::from_static(b"k"), |entry| entry.clone().unwrap());
+        assert!(expires.expires.is_some());
+
+        handle(&mut store, &request(&["k", "c"]).unwrap()).unwrap();
+        let expires = store.update(Bytes::from_static(b"k"), |entry| entry.clone().unwrap());
+        assert!(expires.expires.is_none());
+    }
+}
@@ -0,0 +1,830 @@
+use crate::commands::list::parse_count;
+use crate::commands::parse::Request;
+use crate::commands::scan::{matches, DEFAULT_COUNT};
+use crate::storage::memory::MemoryStore;
+use crate::storage::set::Set;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_i64, bytes_to_string, AsFrame};
+use crate::util::errors::{
+    error_invalid_cursor, error_not_integer, error_syntax, error_wrong_arity, error_wrong_type,
+};
+use crate::util::random::Random;
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+
+/// Parse one of the set commands
+///
+/// # Syntax
+/// ```text
+/// SADD key member [member ...]
+/// SREM key member [member ...]
+/// SMEMBERS key
+/// SISMEMBER key member
+/// SMISMEMBER key member [member ...]
+/// SCARD key
+/// SINTER key [key ...]
+/// SUNION key [key ...]
+/// SDIFF key [key ...]
+/// SINTERSTORE destination key [key ...]
+/// SUNIONSTORE destination key [key ...]
+/// SDIFFSTORE destination key [key ...]
+/// SINTERCARD numkeys key [key ...] [LIMIT limit]
+/// SRANDMEMBER key [count]
+/// SPOP key [count]
+/// SMOVE source destination member
+/// SSCAN key cursor [MATCH pattern] [COUNT count]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "SMEMBERS" | "SCARD" => args.len() == 1,
+        "SISMEMBER" => args.len() == 2,
+        "SINTER" | "SUNION" | "SDIFF" => !args.is_empty(),
+        "SRANDMEMBER" | "SPOP" => args.len() == 1 || args.len() == 2,
+        "SMOVE" => args.len() == 3,
+        _ => args.len() >= 2,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let request = match command {
+        "SINTER" => Request::SINTER {
+            keys: iter.collect(),
+        },
+        "SUNION" => Request::SUNION {
+            keys: iter.collect(),
+        },
+        "SDIFF" => Request::SDIFF {
+            keys: iter.collect(),
+        },
+        "SINTERSTORE" => Request::SINTERSTORE {
+            destination: iter.next().unwrap(),
+            keys: iter.collect(),
+        },
+        "SUNIONSTORE" => Request::SUNIONSTORE {
+            destination: iter.next().unwrap(),
+            keys: iter.collect(),
+        },
+        "SDIFFSTORE" => Request::SDIFFSTORE {
+            destination: iter.next().unwrap(),
+            keys: iter.collect(),
+        },
+        "SINTERCARD" => {
+            let numkeys = bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?;
+            if numkeys <= 0 {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR numkeys should be greater than 0",
+                ));
+            }
+            let keys: Vec<Bytes> = iter.by_ref().take(numkeys as usize).collect();
+            if keys.len() as i64 != numkeys {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR Number of keys can't be greater than number of args",
+                ));
+            }
+
+            let limit = match (iter.next(), iter.next(), iter.next()) {
+                (None, ..) => 0,
+                (Some(option), Some(limit), None) if option.eq_ignore_ascii_case(b"LIMIT") => {
+                    let limit = bytes_to_i64(&limit).ok_or_else(error_not_integer)?;
+                    if limit < 0 {
+                        return Err(RedisProtocolError::new(
+                            RedisProtocolErrorKind::Parse,
+                            "ERR LIMIT can't be negative",
+                        ));
+                    }
+                    limit as usize
+                }
+                _ => return Err(error_syntax()),
+            };
+            Request::SINTERCARD { keys, limit }
+        }
+        "SMOVE" => Request::SMOVE {
+            source: iter.next().unwrap(),
+            destination: iter.next().unwrap(),
+            member: iter.next().unwrap(),
+        },
+        _ => {
+            let key = iter.next().unwrap();
+            match command {
+                "SADD" => Request::SADD {
+                    key,
+                    members: iter.collect(),
+                },
+                "SREM" => Request::SREM {
+                    key,
+                    members: iter.collect(),
+                },
+                "SMEMBERS" => Request::SMEMBERS { key },
+                "SISMEMBER" => Request::SISMEMBER {
+                    key,
+                    member: iter.next().unwrap(),
+                },
+                "SMISMEMBER" => Request::SMISMEMBER {
+                    key,
+                    members: iter.collect(),
+                },
+                "SCARD" => Request::SCARD { key },
+                "SRANDMEMBER" => {
+                    let count = match iter.next() {
+                        Some(count) => {
+                            let count = bytes_to_i64(&count).ok_or_else(error_not_integer)?;
+                            /* The number of members is the absolute value */
+                            if count == i64::MIN {
+                                return Err(RedisProtocolError::new(
+                                    RedisProtocolErrorKind::Parse,
+                                    "ERR value is out of range",
+                                ));
+                            }
+                            Some(count)
+                        }
+                        None => None,
+                    };
+                    Request::SRANDMEMBER { key, count }
+                }
+                "SPOP" => Request::SPOP {
+                    key,
+                    count: iter.next().map(|count| parse_count(&count)).transpose()?,
+                },
+                _ => {
+                    let cursor = bytes_to_string(&iter.next().unwrap())
+                        .parse()
+                        .map_err(|_| error_invalid_cursor())?;
+
+                    let (mut pattern, mut count) = (None, None);
+                    while let Some(option) = iter.next() {
+                        match bytes_to_string(&option).to_uppercase().as_str() {
+                            "MATCH" => pattern = Some(iter.next().ok_or_else(error_syntax)?),
+                            "COUNT" => {
+                                let value = iter.next().ok_or_else(error_syntax)?;
+                                let value = bytes_to_i64(&value).ok_or_else(error_not_integer)?;
+                                if value < 1 {
+                                    return Err(error_syntax());
+                                }
+                                count = Some(value as usize);
+                            }
+                            _ => return Err(error_syntax()),
+                        }
+                    }
+
+                    Request::SSCAN {
+                        key,
+                        cursor,
+                        pattern,
+                        count,
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(request)
+}
+
+/// Handle set commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle the set commands, picking members for `SRANDMEMBER` and `SPOP` with a generator
+/// seeded differently every time. See [`handle_with_random`] to pick reproducibly.
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    handle_with_random(values, args, &mut Random::new())
+}
+
+/// Handle the set commands, picking members for `SRANDMEMBER` and `SPOP` with `random`.
+///
+/// Keys that don't exist count as empty sets, and a set is removed with its last member. The
+/// commands reading several keys and the `STORE` variants read and write all keys atomically.
+///
+/// # Returns
+///  * `SADD`, `SREM`: the number of members added or removed
+///  * `SMEMBERS`: every member
+///  * `SISMEMBER`, `SMISMEMBER`: `1` if the member is part of the set, `0` otherwise
+///  * `SCARD`: the number of members
+///  * `SINTER`, `SUNION`, `SDIFF`: the members of all sets, of any set, or of the first set but
+///    none of the others
+///  * `SINTERSTORE`, `SUNIONSTORE`, `SDIFFSTORE`: the number of members stored at
+///    `destination`, which is replaced whatever its type and removed if there are none
+///  * `SINTERCARD`: the number of members of all sets, counting at most `limit` unless it is
+///    `0`
+///  * `SRANDMEMBER`: a random member, or [`OwnedFrame::Null`] if the set does not exist. With a
+///    positive `count`, up to `count` distinct members. With a negative `count`, exactly
+///    `-count` members that may repeat.
+///  * `SPOP`: the member removed at random, or up to `count` members if `count` was given.
+///    [`OwnedFrame::Null`] if the set does not exist and there is no `count`.
+///  * `SMOVE`: `1` if the member was moved, `0` if it is not part of `source`
+///  * `SSCAN`: the cursor to continue with, `0` once the iteration is complete, and the members
+///    found in this batch
+///
+/// All of them fail with `WRONGTYPE` if a key they read holds a value other than a set.
+pub fn handle_with_random<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+    random: &mut Random,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::SADD { key, members } => {
+            let added = values.update(key.clone(), |entry| {
+                let set = set_mut(entry)?;
+                let added = members
+                    .iter()
+                    .filter(|member| set.insert((*member).clone()))
+                    .count();
+                Ok::<_, RedisProtocolError>(added)
+            })?;
+            Ok((added as i64).as_frame())
+        }
+        Request::SREM { key, members } => {
+            let removed = values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(0);
+                }
+                let set = set_mut(entry)?;
+                let removed = members.iter().filter(|member| set.remove(member)).count();
+                Ok::<_, RedisProtocolError>(removed)
+            })?;
+            Ok((removed as i64).as_frame())
+        }
+        Request::SMEMBERS { key } => values.read(key, |value| {
+            Ok(set(value)?.map_or_else(|| Set::new().as_frame(), Set::as_frame))
+        }),
+        Request::SISMEMBER { key, member } => values.read(key, |value| {
+            let found = set(value)?.is_some_and(|set| set.contains(member));
+            Ok((found as i64).as_frame())
+        }),
+        Request::SMISMEMBER { key, members } => values.read(key, |value| {
+            let set = set(value)?;
+            let found: Vec<i64> = members
+                .iter()
+                .map(|member| set.is_some_and(|set| set.contains(member)) as i64)
+                .collect();
+            Ok(found.as_frame())
+        }),
+        Request::SCARD { key } => values.read(key, |value| {
+            let length = set(value)?.map_or(0, Set::len);
+            Ok((length as i64).as_frame())
+        }),
+        Request::SINTER { keys } => {
+            Ok(combine(values, Operation::Intersection(0), keys)?.as_frame())
+        }
+        Request::SUNION { keys } => Ok(combine(values, Operation::Union, keys)?.as_frame()),
+        Request::SDIFF { keys } => Ok(combine(values, Operation::Difference, keys)?.as_frame()),
+        Request::SINTERSTORE { destination, keys }
+        | Request::SUNIONSTORE { destination, keys }
+        | Request::SDIFFSTORE { destination, keys } => {
+            let operation = match args {
+                Request::SINTERSTORE { .. } => Operation::Intersection(0),
+                Request::SUNIONSTORE { .. } => Operation::Union,
+                _ => Operation::Difference,
+            };
+            let length = store(values, operation, keys, destination)?;
+            Ok((length as i64).as_frame())
+        }
+        Request::SINTERCARD { keys, limit } => {
+            let length = combine(values, Operation::Intersection(*limit), keys)?.len();
+            Ok((length as i64).as_frame())
+        }
+        Request::SRANDMEMBER { key, count } => values.read(key, |value| {
+            let set = set(value)?;
+            let Some(count) = count else {
+                let member = set.and_then(|set| set.get(random.below(set.len())));
+                return Ok(member.cloned().as_frame());
+            };
+
+            let Some(set) = set else {
+                return Ok(Vec::<Bytes>::new().as_frame());
+            };
+            let members: Vec<Bytes> = if *count >= 0 {
+                sample(set.len(), *count as usize, random)
+                    .into_iter()
+                    .map(|index| set.get(index).unwrap().clone())
+                    .collect()
+            } else {
+                (0..count.unsigned_abs())
+                    .map(|_| set.get(random.below(set.len())).unwrap().clone())
+                    .collect()
+            };
+            Ok(members.as_frame())
+        }),
+        Request::SPOP { key, count } => values.update(key.clone(), |entry| {
+            if entry.is_none() {
+                return Ok(match count {
+                    Some(_) => Set::new().as_frame(),
+                    None => OwnedFrame::Null,
+                });
+            }
+            let set = set_mut(entry)?;
+            let Some(count) = count else {
+                return Ok(set.swap_remove(random.below(set.len())).as_frame());
+            };
+
+            if *count >= set.len() {
+                return Ok(std::mem::take(set).as_frame());
+            }
+            let popped: Set = (0..*count)
+                .map(|_| set.swap_remove(random.below(set.len())))
+                .collect();
+            Ok(popped.as_frame())
+        }),
+        Request::SMOVE {
+            source,
+            destination,
+            member,
+        } => {
+            let moved = if source == destination {
+                values.read(source, |value| {
+                    Ok::<_, RedisProtocolError>(set(value)?.is_some_and(|set| set.contains(member)))
+                })?
+            } else {
+                values.update_many(&[source.clone(), destination.clone()], |entries| {
+                    let [source, destination] = entries else {
+                        unreachable!("Two keys were given");
+                    };
+                    if source.is_none() {
+                        return Ok(false);
+                    }
+                    set(destination.as_ref().map(|e| &e.value))?;
+                    if !set_mut(source)?.remove(member) {
+                        return Ok(false);
+                    }
+                    set_mut(destination)?.insert(member.clone());
+                    Ok::<_, RedisProtocolError>(true)
+                })?
+            };
+            Ok((moved as i64).as_frame())
+        }
+        Request::SSCAN {
+            key,
+            cursor,
+            pattern,
+            count,
+        } => values.read(key, |value| {
+            let (next, mut members) = match set(value)? {
+                Some(set) => set.scan(*cursor, count.unwrap_or(DEFAULT_COUNT)),
+                None => (0, Vec::new()),
+            };
+            if let Some(pattern) = pattern {
+                members.retain(|member| matches(pattern, member));
+            }
+
+            Ok(OwnedFrame::Array {
+                data: vec![next.to_string().as_frame(), members.as_frame()],
+                attributes: None,
+            })
+        }),
+        _ => panic!(
+            "Expected enum variant SADD, SREM, SMEMBERS, SISMEMBER, SMISMEMBER, SCARD, SINTER, \
+             SUNION, SDIFF, SINTERSTORE, SUNIONSTORE, SDIFFSTORE, SINTERCARD, SRANDMEMBER, SPOP, \
+             SMOVE or SSCAN, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Return the set `value`, `None` if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a set
+pub(crate) fn set(value: Option<&Value>) -> Result<Option<&Set>, RedisProtocolError> {
+    match value {
+        Some(value) => value.as_set().map(Some).ok_or_else(error_wrong_type),
+        None => Ok(None),
+    }
+}
+
+/// Return the set in `entry` for modification, creating an empty one if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a set
+fn set_mut(entry: &mut Option<Entry>) -> Result<&mut Set, RedisProtocolError> {
+    let entry = entry.get_or_insert_with(|| Entry::new(Set::new()));
+    entry.value.as_set_mut().ok_or_else(error_wrong_type)
+}
+
+/// Way to combine several sets into one
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    /// Members of every set, at most the given number unless it is `0`
+    Intersection(usize),
+    /// Members of any set
+    Union,
+    /// Members of the first set that are not part of any other
+    Difference,
+}
+
+impl Operation {
+    /// Combine `sets`, where `None` stands for a key that does not exist
+    fn apply(self, sets: &[Option<&Set>]) -> Set {
+        match self {
+            Operation::Intersection(limit) => {
+                let Some(sets) = sets.iter().copied().collect::<Option<Vec<&Set>>>() else {
+                    return Set::new();
+                };
+                let smallest = sets.iter().copied().min_by_key(|set| set.len());
+                let members = smallest
+                    .into_iter()
+                    .flat_map(Set::iter)
+                    .filter(|member| sets.iter().all(|set| set.contains(member)))
+                    .cloned();
+                match limit {
+                    0 => members.collect(),
+                    limit => members.take(limit).collect(),
+                }
+            }
+            Operation::Union => sets
+                .iter()
+                .flatten()
+                .flat_map(|set| set.iter())
+                .cloned()
+                .collect(),
+            Operation::Difference => {
+                let Some((Some(first), others)) = sets.split_first() else {
+                    return Set::new();
+                };
+                first
+                    .iter()
+                    .filter(|member| others.iter().flatten().all(|set| !set.contains(member)))
+                    .cloned()
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Combine the sets at `keys` with `operation`, reading all of them at once
+fn combine<S: KeyValueStore>(
+    values: &mut S,
+    operation: Operation,
+    keys: &[Bytes],
+) -> Result<Set, RedisProtocolError> {
+    let (distinct, positions) = distinct(keys.iter());
+    values.update_many(&distinct, |entries| {
+        let sets = sets(entries, keys, &positions)?;
+        Ok(operation.apply(&sets))
+    })
+}
+
+/// Combine the sets at `keys` with `operation` and replace the value at `destination` with the
+/// result, all at once.
+///
+/// # Returns
+/// The number of members stored
+fn store<S: KeyValueStore>(
+    values: &mut S,
+    operation: Operation,
+    keys: &[Bytes],
+    destination: &Bytes,
+) -> Result<usize, RedisProtocolError> {
+    let (distinct, positions) = distinct(keys.iter().chain([destination]));
+    values.update_many(&distinct, |entries| {
+        let result = operation.apply(&sets(entries, keys, &positions)?);
+        let length = result.len();
+        /* Replacing the entry drops the time to live, and an empty set deletes the key */
+        entries[positions[destination]] = Some(Entry::new(result));
+        Ok(length)
+    })
+}
+
+/// Keys without repetitions, as [`KeyValueStore::update_many`] expects them, and the position
+/// of every key among them
+pub(crate) fn distinct<'a>(
+    keys: impl Iterator<Item = &'a Bytes>,
+) -> (Vec<Bytes>, HashMap<&'a Bytes, usize>) {
+    let mut distinct = Vec::new();
+    let mut positions = HashMap::new();
+    for key in keys {
+        positions.entry(key).or_insert_with(|| {
+            distinct.push(key.clone());
+            distinct.len() - 1
+        });
+    }
+    (distinct, positions)
+}
+
+/// Sets of `keys` among the `entries` of distinct keys at `positions`
+fn sets<'a>(
+    entries: &'a [Option<Entry>],
+    keys: &[Bytes],
+    positions: &HashMap<&Bytes, usize>,
+) -> Result<Vec<Option<&'a Set>>, RedisProtocolError> {
+    keys.iter()
+        .map(|key| set(entries[positions[key]].as_ref().map(|entry| &entry.value)))
+        .collect()
+}
+
+/// Pick `count` distinct indices below `length` at random, or all of them if there are not
+/// that many.
+///
+/// Uses Floyd's algorithm, which takes `count` steps however large `length` is.
+fn sample(length: usize, count: usize, random: &mut Random) -> Vec<usize> {
+    if count >= length {
+        return (0..length).collect();
+    }
+
+    let mut picked = HashSet::with_capacity(count);
+    let mut indices = Vec::with_capacity(count);
+    for upper in length - count..length {
+        let index = random.below(upper + 1);
+        let index = if picked.insert(index) {
+            index
+        } else {
+            picked.insert(upper);
+            upper
+        };
+        indices.push(index);
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    fn members(members: &[&str]) -> OwnedFrame {
+        members
+            .iter()
+            .map(|member| Bytes::from(member.to_string()))
+            .collect::<Set>()
+            .as_frame()
+    }
+
+    #[test]
+    fn algebra() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(handle, &mut values, &["SADD", "a", "x", "y", "z", "x"]).unwrap(),
+            3.as_frame()
+        );
+        run(handle, &mut values, &["SADD", "b", "y", "z", "w"]).unwrap();
+
+        assert_eq!(
+            run(handle, &mut values, &["SINTER", "a", "b"]).unwrap(),
+            members(&["y", "z"])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SUNION", "a", "b", "missing"]).unwrap(),
+            members(&["w", "x", "y", "z"])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SDIFF", "a", "b"]).unwrap(),
+            members(&["x"])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SINTER", "a", "missing"]).unwrap(),
+            members(&[])
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["SINTERCARD", "2", "a", "b", "LIMIT", "1"]
+            )
+            .unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SINTERCARD", "3", "a", "b"])
+                .unwrap_err()
+                .details(),
+            "ERR Number of keys can't be greater than number of args"
+        );
+
+        /* The destination may be one of the sources */
+        assert_eq!(
+            run(handle, &mut values, &["SUNIONSTORE", "a", "a", "b"]).unwrap(),
+            4.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SDIFFSTORE", "c", "b", "a"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"c"));
+
+        assert_eq!(
+            run(handle, &mut values, &["SMOVE", "a", "c", "x"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SMISMEMBER", "a", "x", "w"]).unwrap(),
+            vec![0, 1].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SCARD", "c"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SREM", "c", "x", "y"]).unwrap(),
+            1.as_frame()
+        );
+        assert!(!values.exists(b"c"));
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        let error = run(handle, &mut values, &["SUNION", "a", "text"]).unwrap_err();
+        assert!(error.details().starts_with("WRONGTYPE"));
+        assert_eq!(
+            run(handle, &mut values, &["SINTERSTORE", "text", "a", "b"]).unwrap(),
+            3.as_frame()
+        );
+    }
+
+    #[test]
+    fn seeded_randomness() {
+        let mut values = MemoryStore::new();
+        let members: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let mut query = vec!["SADD".to_string(), "s".to_string()];
+        query.extend(members.iter().cloned());
+        run(
+            handle,
+            &mut values,
+            &query.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let pick = |values: &mut MemoryStore, args: &[&str], seed| {
+            let mut random = Random::seeded(seed);
+            let handle =
+                |values, request: &Request| handle_with_random(values, request, &mut random);
+            run(handle, values, args).unwrap()
+        };
+
+        let random = pick(&mut values, &["SRANDMEMBER", "s", "10"], 7);
+        assert_eq!(random, pick(&mut values, &["SRANDMEMBER", "s", "10"], 7));
+        let OwnedFrame::Array { data, .. } = &random else {
+            panic!("Expected an array, but got {random:?}");
+        };
+        assert_eq!(data.iter().collect::<HashSet<_>>().len(), 10);
+
+        let OwnedFrame::Array { data, .. } = pick(&mut values, &["SRANDMEMBER", "s", "-300"], 7)
+        else {
+            panic!("Expected an array");
+        };
+        assert_eq!(data.len(), 300);
+
+        /* Popping from equal sets with the same seed removes the same members */
+        let mut copy = values.clone();
+        let popped = pick(&mut values, &["SPOP", "s", "30"], 11);
+        assert_eq!(popped, pick(&mut copy, &["SPOP", "s", "30"], 11));
+        assert_eq!(values.get(b"s"), copy.get(b"s"));
+        assert_eq!(
+            run(handle, &mut values, &["SCARD", "s"]).unwrap(),
+            70.as_frame()
+        );
+
+        let remaining = values.get(b"s").unwrap();
+        assert_eq!(
+            run(handle, &mut values, &["SPOP", "s", "1000"]).unwrap(),
+            remaining.as_set().unwrap().as_frame()
+        );
+        assert!(!values.exists(b"s"));
+        assert_eq!(
+            run(handle, &mut values, &["SPOP", "s"]).unwrap(),
+            OwnedFrame::Null
+        );
+    }
+
+    #[test]
+    fn random_counts() {
+        let mut values = MemoryStore::new();
+        run(handle, &mut values, &["SADD", "s", "a", "b", "c"]).unwrap();
+        let pick = |values: &mut MemoryStore, args: &[&str], seed| {
+            let mut random = Random::seeded(seed);
+            let handle =
+                |values, request: &Request| handle_with_random(values, request, &mut random);
+            run(handle, values, args).unwrap()
+        };
+        let elements = |reply: OwnedFrame| match reply {
+            OwnedFrame::Array { data, .. } => data,
+            OwnedFrame::Set { data, .. } => data.into_iter().collect(),
+            reply => panic!("Expected an array or a set, but got {reply:?}"),
+        };
+
+        /* Negative counts pick exactly that many members, repeating them as needed */
+        let picked = elements(pick(&mut values, &["SRANDMEMBER", "s", "-50"], 3));
+        assert_eq!(picked.len(), 50);
+        let distinct: HashSet<_> = picked.iter().collect();
+        assert!(distinct.len() <= 3);
+        assert!(distinct.is_subset(&HashSet::from([
+            &"a".as_frame(),
+            &"b".as_frame(),
+            &"c".as_frame()
+        ])));
+        assert_eq!(
+            picked,
+            elements(pick(&mut values, &["SRANDMEMBER", "s", "-50"], 3))
+        );
+        assert_ne!(
+            picked,
+            elements(pick(&mut values, &["SRANDMEMBER", "s", "-50"], 4))
+        );
+
+        /* Positive counts never repeat and are capped by the size of the set */
+        let picked = elements(pick(&mut values, &["SRANDMEMBER", "s", "10"], 3));
+        assert_eq!(picked.iter().collect::<HashSet<_>>().len(), 3);
+        assert!(elements(pick(&mut values, &["SRANDMEMBER", "s", "0"], 3)).is_empty());
+        assert!(elements(pick(&mut values, &["SRANDMEMBER", "missing", "-5"], 3)).is_empty());
+        assert_eq!(
+            run(handle, &mut values, &["SRANDMEMBER", "missing"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert!(run(
+            handle,
+            &mut values,
+            &["SRANDMEMBER", "s", "-9223372036854775808"]
+        )
+        .is_err());
+
+        /* Reading leaves the set alone, popping does not */
+        assert_eq!(
+            run(handle, &mut values, &["SCARD", "s"]).unwrap(),
+            3.as_frame()
+        );
+        assert!(elements(pick(&mut values, &["SPOP", "s", "0"], 3)).is_empty());
+        assert!(elements(pick(&mut values, &["SPOP", "missing", "2"], 3)).is_empty());
+        assert!(run(handle, &mut values, &["SPOP", "s", "-1"]).is_err());
+
+        let mut copy = values.clone();
+        let popped = pick(&mut values, &["SPOP", "s"], 5);
+        assert_eq!(popped, pick(&mut copy, &["SPOP", "s"], 5));
+        assert_eq!(values.get(b"s"), copy.get(b"s"));
+        assert_eq!(
+            run(handle, &mut values, &["SCARD", "s"]).unwrap(),
+            2.as_frame()
+        );
+    }
+
+    #[test]
+    fn store_edge_cases() {
+        let mut values = MemoryStore::new();
+        run(handle, &mut values, &["SADD", "a", "x", "y", "z"]).unwrap();
+        run(handle, &mut values, &["SADD", "b", "y", "z", "w"]).unwrap();
+        run(handle, &mut values, &["SADD", "d", "old"]).unwrap();
+
+        /* A missing key is an empty set, so an empty result removes the destination */
+        assert_eq!(
+            run(handle, &mut values, &["SINTERSTORE", "d", "a", "missing"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"d"));
+        assert_eq!(
+            run(handle, &mut values, &["SDIFFSTORE", "d", "missing", "a"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"d"));
+        assert_eq!(
+            run(handle, &mut values, &["SDIFFSTORE", "d", "a", "missing"]).unwrap(),
+            3.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SMEMBERS", "d"]).unwrap(),
+            members(&["x", "y", "z"])
+        );
+
+        /* The destination may be one of the sources */
+        assert_eq!(
+            run(handle, &mut values, &["SINTERSTORE", "a", "a", "b"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SMEMBERS", "a"]).unwrap(),
+            members(&["y", "z"])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SDIFFSTORE", "b", "b", "a"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SMEMBERS", "b"]).unwrap(),
+            members(&["w"])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SDIFFSTORE", "b", "b", "b"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"b"));
+
+        /* A source of another type fails without touching the destination */
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        let error = run(handle, &mut values, &["SINTERSTORE", "d", "a", "text"]).unwrap_err();
+        assert!(error.details().starts_with("WRONGTYPE"));
+        let error = run(handle, &mut values, &["SDIFFSTORE", "d", "text", "a"]).unwrap_err();
+        assert!(error.details().starts_with("WRONGTYPE"));
+        assert_eq!(
+            run(handle, &mut values, &["SCARD", "d"]).unwrap(),
+            3.as_frame()
+        );
+        assert!(run(handle, &mut values, &["SINTERSTORE", "d"]).is_err());
+    }
+}
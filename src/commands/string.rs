@@ -0,0 +1,463 @@
+use crate::commands::parse::Request;
+use crate::commands::set::{parse_expire_time, SetExpiry};
+use crate::storage::memory::MemoryStore;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_string, AsFrame};
+use crate::util::errors::{error_not_integer, error_syntax, error_wrong_arity, error_wrong_type};
+use bytes::{Bytes, BytesMut};
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::time::SystemTime;
+
+/// Longest string `APPEND` and `SETRANGE` may create, the default `proto-max-bulk-len` of Redis
+pub const MAX_STRING_LENGTH: usize = 512 * 1024 * 1024;
+
+/// Change to the time to live requested with `GETEX`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GetExExpiry {
+    /// Set a new time to live, never [`SetExpiry::KeepTtl`]
+    Expire(SetExpiry),
+    /// Remove the time to live
+    Persist,
+}
+
+/// Parse one of the string commands
+///
+/// # Syntax
+/// ```text
+/// APPEND key value
+/// STRLEN key
+/// GETRANGE key start end
+/// SETRANGE key offset value
+/// GETDEL key
+/// GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+///   PXAT unix-time-milliseconds | PERSIST]
+/// GETSET key value
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "STRLEN" | "GETDEL" => args.len() == 1,
+        "APPEND" | "GETSET" => args.len() == 2,
+        "GETRANGE" | "SETRANGE" => args.len() == 3,
+        "GETEX" => !args.is_empty(),
+        _ => false,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().unwrap();
+    let request = match command {
+        "APPEND" => Request::APPEND {
+            key,
+            value: iter.next().unwrap(),
+        },
+        "STRLEN" => Request::STRLEN { key },
+        "GETRANGE" => Request::GETRANGE {
+            key,
+            start: parse_integer(&iter.next().unwrap())?,
+            end: parse_integer(&iter.next().unwrap())?,
+        },
+        "SETRANGE" => {
+            let offset = parse_integer(&iter.next().unwrap())?;
+            Request::SETRANGE {
+                key,
+                offset: usize::try_from(offset).map_err(|_| error_offset_out_of_range())?,
+                value: iter.next().unwrap(),
+            }
+        }
+        "GETDEL" => Request::GETDEL { key },
+        "GETSET" => Request::GETSET {
+            key,
+            value: iter.next().unwrap(),
+        },
+        _ => {
+            let mut expiry = None;
+            while let Some(option) = iter.next() {
+                if expiry.is_some() {
+                    return Err(error_syntax());
+                }
+                let option = bytes_to_string(&option).to_uppercase();
+                expiry = Some(match option.as_str() {
+                    "PERSIST" => GetExExpiry::Persist,
+                    "EX" | "PX" | "EXAT" | "PXAT" => {
+                        let time = parse_expire_time("getex", iter.next())?;
+                        GetExExpiry::Expire(match option.as_str() {
+                            "EX" => SetExpiry::Ex(time),
+                            "PX" => SetExpiry::Px(time),
+                            "EXAT" => SetExpiry::ExAt(time),
+                            _ => SetExpiry::PxAt(time),
+                        })
+                    }
+                    _ => return Err(error_syntax()),
+                });
+            }
+            Request::GETEX { key, expiry }
+        }
+    };
+
+    Ok(request)
+}
+
+fn parse_integer(arg: &[u8]) -> Result<i64, RedisProtocolError> {
+    bytes_to_string(arg)
+        .parse()
+        .map_err(|_| error_not_integer())
+}
+
+fn error_offset_out_of_range() -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR offset is out of range")
+}
+
+fn error_too_long() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR string exceeds maximum allowed size (proto-max-bulk-len)",
+    )
+}
+
+/// Handle string commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle `APPEND`, `STRLEN`, `GETRANGE`, `SETRANGE`, `GETDEL`, `GETEX` and `GETSET`.
+///
+/// `APPEND` and `SETRANGE` keep the time to live of the key, `GETSET` discards it like `SET`.
+///
+/// # Returns
+///  * `APPEND`, `SETRANGE`: the length of the string after the modification
+///  * `STRLEN`: the length of the string, `0` if the key does not exist
+///  * `GETRANGE`: the substring between the inclusive offsets `start` and `end`, counted from the
+///    end if negative
+///  * `GETDEL`, `GETEX`, `GETSET`: the value before the command, or [`OwnedFrame::Null`]
+///
+/// All of them fail with `WRONGTYPE` if the key holds a value other than a string.
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    match args {
+        Request::APPEND { key, value } => {
+            let length = values.update(key.clone(), |entry| {
+                let current = string(entry.as_ref().map(|e| &e.value))?.map_or(0, Bytes::len);
+                if current + value.len() > MAX_STRING_LENGTH {
+                    return Err(error_too_long());
+                }
+                let mut buffer = buffer(entry);
+                buffer.extend_from_slice(value);
+                Ok(replace_value(entry, buffer))
+            })?;
+            Ok((length as i64).as_frame())
+        }
+        Request::STRLEN { key } => {
+            let length = values.read(key, |value| {
+                Ok::<_, RedisProtocolError>(string(value)?.map_or(0, Bytes::len))
+            })?;
+            Ok((length as i64).as_frame())
+        }
+        Request::GETRANGE { key, start, end } => values.read(key, |value| {
+            let value = string(value)?.cloned().unwrap_or_default();
+            Ok(value.slice(range(value.len(), *start, *end)).as_frame())
+        }),
+        Request::SETRANGE { key, offset, value } => {
+            let length = values.update(key.clone(), |entry| {
+                let current = string(entry.as_ref().map(|e| &e.value))?.map_or(0, Bytes::len);
+                /* Writing nothing neither creates nor pads the string */
+                if value.is_empty() {
+                    return Ok::<_, RedisProtocolError>(current);
+                }
+                let end = offset
+                    .checked_add(value.len())
+                    .filter(|end| *end <= MAX_STRING_LENGTH)
+                    .ok_or_else(error_too_long)?;
+                let mut buffer = buffer(entry);
+                if buffer.len() < end {
+                    buffer.resize(end, 0);
+                }
+                buffer[*offset..end].copy_from_slice(value);
+                Ok(replace_value(entry, buffer))
+            })?;
+            Ok((length as i64).as_frame())
+        }
+        Request::GETDEL { key } => {
+            let value = values.update(key.clone(), |entry| {
+                let value = string(entry.as_ref().map(|e| &e.value))?.cloned();
+                if value.is_some() {
+                    *entry = None;
+                }
+                Ok::<_, RedisProtocolError>(value)
+            })?;
+            Ok(value.as_frame())
+        }
+        Request::GETEX { key, expiry } => {
+            let value = values.update(key.clone(), |entry| {
+                let Some(value) = string(entry.as_ref().map(|e| &e.value))?.cloned() else {
+                    return Ok::<_, RedisProtocolError>(None);
+                };
+                match expiry {
+                    Some(GetExExpiry::Expire(expiry)) => {
+                        let now = SystemTime::now();
+                        let deadline = expiry.deadline(now);
+                        if deadline.is_some_and(|deadline| deadline <= now) {
+                            *entry = None;
+                        } else if let Some(entry) = entry {
+                            entry.expires = deadline;
+                        }
+                    }
+                    Some(GetExExpiry::Persist) => {
+                        if let Some(entry) = entry {
+                            entry.expires = None;
+                        }
+                    }
+                    None => {}
+                }
+                Ok(Some(value))
+            })?;
+            Ok(value.as_frame())
+        }
+        Request::GETSET { key, value } => {
+            let previous = values.update(key.clone(), |entry| {
+                let previous = string(entry.as_ref().map(|e| &e.value))?.cloned();
+                *entry = Some(Entry::new(value.clone()));
+                Ok::<_, RedisProtocolError>(previous)
+            })?;
+            Ok(previous.as_frame())
+        }
+        _ => panic!(
+            "Expected enum variant APPEND, STRLEN, GETRANGE, SETRANGE, GETDEL, GETEX or GETSET, \
+             but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Return the string `value`, `None` if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a string
+pub(crate) fn string(value: Option<&Value>) -> Result<Option<&Bytes>, RedisProtocolError> {
+    match value {
+        Some(value) => value.as_string().map(Some).ok_or_else(error_wrong_type),
+        None => Ok(None),
+    }
+}
+
+/// Take the string out of `entry` for modification, which must not hold another type.
+///
+/// The buffer is reused unless it is shared, e.g. with a reply that is still being sent, so
+/// appending to a string does not copy it every time. Put it back with [`replace_value`].
+fn buffer(entry: &mut Option<Entry>) -> BytesMut {
+    match entry.as_mut().map(|entry| &mut entry.value) {
+        Some(Value::String(value)) => std::mem::take(value)
+            .try_into_mut()
+            .unwrap_or_else(|shared| BytesMut::from(&shared[..])),
+        _ => BytesMut::new(),
+    }
+}
+
+/// Store `value` in `entry`, keeping its time to live.
+///
+/// # Returns
+/// The length of `value`
+fn replace_value(entry: &mut Option<Entry>, value: BytesMut) -> usize {
+    let length = value.len();
+    let expires = entry.as_ref().and_then(|entry| entry.expires);
+    *entry = Some(Entry {
+        value: value.freeze().into(),
+        expires,
+    });
+    length
+}
+
+/// Byte range selected by `GETRANGE` with the inclusive offsets `start` and `end`, following
+/// the clamping rules of Redis
+fn range(length: usize, start: i64, end: i64) -> std::ops::Range<usize> {
+    let length = length as i64;
+    if start < 0 && end < 0 && start > end {
+        return 0..0;
+    }
+
+    let start = if start < 0 { length + start } else { start }.max(0);
+    let end = if end < 0 { length + end } else { end }.max(0);
+    let end = end.min(length - 1);
+    if start > end || length == 0 {
+        return 0..0;
+    }
+
+    start as usize..end as usize + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+    use std::time::Duration;
+
+    #[test]
+    fn append_and_ranges() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"log"), Bytes::from_static(b"Hello"));
+        values.expire(b"log", Some(SystemTime::now() + Duration::from_secs(60)));
+
+        assert_eq!(
+            run(handle, &mut values, &["APPEND", "log", " World"]).unwrap(),
+            11.as_frame()
+        );
+        assert!(values.entry(b"log").unwrap().expires.is_some());
+        assert_eq!(
+            run(handle, &mut values, &["STRLEN", "log"]).unwrap(),
+            11.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["STRLEN", "none"]).unwrap(),
+            0.as_frame()
+        );
+
+        for (start, end, expected) in [
+            ("0", "4", "Hello"),
+            ("-5", "-1", "World"),
+            ("-3", "-5", ""),
+            ("5", "3", ""),
+            ("-100", "100", "Hello World"),
+            ("20", "30", ""),
+        ] {
+            assert_eq!(
+                run(handle, &mut values, &["GETRANGE", "log", start, end]).unwrap(),
+                expected.as_frame()
+            );
+        }
+
+        assert_eq!(
+            run(handle, &mut values, &["SETRANGE", "log", "6", "Redis"]).unwrap(),
+            11.as_frame()
+        );
+        assert_eq!(
+            values.get(b"log"),
+            Some(Bytes::from_static(b"Hello Redis").into())
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SETRANGE", "padded", "3", "x"]).unwrap(),
+            4.as_frame()
+        );
+        assert_eq!(
+            values.get(b"padded"),
+            Some(Bytes::from_static(b"\0\0\0x").into())
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SETRANGE", "empty", "3", ""]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"empty"));
+
+        assert_eq!(
+            run(handle, &mut values, &["SETRANGE", "log", "-1", "x"])
+                .unwrap_err()
+                .details(),
+            "ERR offset is out of range"
+        );
+        assert_eq!(
+            run(handle, &mut values, &["SETRANGE", "log", "536870911", "xx"])
+                .unwrap_err()
+                .details(),
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)"
+        );
+        assert_eq!(
+            values.get(b"log"),
+            Some(Bytes::from_static(b"Hello Redis").into())
+        );
+        assert!(run(handle, &mut values, &["GETRANGE", "log", "a", "1"]).is_err());
+    }
+
+    #[test]
+    fn append_in_place() {
+        let mut values = MemoryStore::new();
+        let address = |values: &MemoryStore| {
+            values.read(b"log", |value| value.unwrap().as_string().unwrap().as_ptr())
+        };
+
+        /* The buffer grows like a vector instead of being copied by every command */
+        run(handle, &mut values, &["APPEND", "log", "a"]).unwrap();
+        let mut previous = address(&values);
+        let mut moved = 0;
+        for i in 0..100 {
+            let command = if i % 2 == 0 { "APPEND" } else { "SETRANGE" };
+            let args = match command {
+                "APPEND" => vec![command, "log", "b"],
+                _ => vec![command, "log", "0", "c"],
+            };
+            run(handle, &mut values, &args).unwrap();
+            let current = address(&values);
+            moved += (current != previous) as usize;
+            previous = current;
+        }
+        assert_eq!(
+            run(handle, &mut values, &["STRLEN", "log"]).unwrap(),
+            51.as_frame()
+        );
+        assert!(moved < 10, "Moved the buffer {moved} times");
+
+        /* A value that is still referenced elsewhere is copied instead */
+        let shared = values.get(b"log").unwrap();
+        run(handle, &mut values, &["APPEND", "log", "d"]).unwrap();
+        assert_eq!(shared.as_string().unwrap().len(), 51);
+        assert_eq!(
+            run(handle, &mut values, &["STRLEN", "log"]).unwrap(),
+            52.as_frame()
+        );
+    }
+
+    #[test]
+    fn get_and_modify() {
+        let mut values = MemoryStore::new();
+        values.set(Bytes::from_static(b"token"), Bytes::from_static(b"secret"));
+
+        assert_eq!(
+            run(handle, &mut values, &["GETEX", "token", "EX", "60"]).unwrap(),
+            "secret".as_frame()
+        );
+        assert!(values.entry(b"token").unwrap().expires.is_some());
+        run(handle, &mut values, &["GETEX", "token", "PERSIST"]).unwrap();
+        assert!(values.entry(b"token").unwrap().expires.is_none());
+        assert_eq!(
+            run(handle, &mut values, &["GETEX", "token", "EXAT", "1"]).unwrap(),
+            "secret".as_frame()
+        );
+        assert!(!values.exists(b"token"));
+        assert_eq!(
+            run(handle, &mut values, &["GETEX", "token", "EX", "0"])
+                .unwrap_err()
+                .details(),
+            "ERR invalid expire time in 'getex' command"
+        );
+        assert!(run(
+            handle,
+            &mut values,
+            &["GETEX", "token", "EX", "1", "PERSIST"]
+        )
+        .is_err());
+
+        assert_eq!(
+            run(handle, &mut values, &["GETSET", "token", "a"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["GETSET", "token", "b"]).unwrap(),
+            "a".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["GETDEL", "token"]).unwrap(),
+            "b".as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["GETDEL", "token"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert!(run(handle, &mut values, &["GETDEL"]).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::commands::parse::{parse, Request};
+use bytes::Bytes;
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+
+/// Parse `args` like a request sent by a client, e.g. `&["GET", "key"]`
+pub(crate) fn request(args: &[&str]) -> Result<Request, RedisProtocolError> {
+    parse(
+        args.iter()
+            .map(|arg| Bytes::from(arg.to_string()))
+            .collect(),
+    )
+}
+
+/// Parse `args` like a request sent by a client and pass it to the `handle` function of a
+/// command module, together with the keyspace `values`
+pub(crate) fn run<V>(
+    handle: impl FnOnce(V, &Request) -> Result<OwnedFrame, RedisProtocolError>,
+    values: V,
+    args: &[&str],
+) -> Result<OwnedFrame, RedisProtocolError> {
+    handle(values, &request(args)?)
+}
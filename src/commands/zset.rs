@@ -0,0 +1,1486 @@
+use crate::codec::Protocol;
+use crate::commands::incr::error_not_float;
+use crate::commands::list::{parse_count, range};
+use crate::commands::parse::Request;
+use crate::commands::set::SetCondition;
+use crate::commands::sets::distinct;
+use crate::storage::memory::MemoryStore;
+use crate::storage::set::Set;
+use crate::storage::sorted_set::SortedSet;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use crate::util::convert::{bytes_to_f64, bytes_to_i64, bytes_to_string, AsFrame};
+use crate::util::errors::{error_not_integer, error_syntax, error_wrong_arity, error_wrong_type};
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::any::Any;
+use std::collections::HashMap;
+use std::ops::Bound;
+
+/// Only update existing members if the new score is greater (`GT`) or less (`LT`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoreComparison {
+    Gt,
+    Lt,
+}
+
+/// Optional arguments of `ZADD`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZAddOptions {
+    /// Only update existing members (`XX`) or only add new ones (`NX`)
+    pub condition: Option<SetCondition>,
+    pub comparison: Option<ScoreComparison>,
+    /// Count changed members as well as added ones
+    pub ch: bool,
+    /// Increment the score like `ZINCRBY` and reply with the new score
+    pub incr: bool,
+}
+
+/// End of a lexicographic range of `ZRANGE ... BYLEX`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LexBound {
+    /// `-`, before every member
+    Minimum,
+    /// `+`, after every member
+    Maximum,
+    /// `[member`
+    Included(Bytes),
+    /// `(member`
+    Excluded(Bytes),
+}
+
+/// Members selected by `ZRANGE`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZRangeBy {
+    /// Ranks from `start` to `stop`, both inclusive, negative ranks count from the end
+    Rank { start: i64, stop: i64 },
+    /// Scores between `min` and `max`
+    Score { min: Bound<f64>, max: Bound<f64> },
+    /// Members between `min` and `max`, for sorted sets whose members all have the same score
+    Lex { min: LexBound, max: LexBound },
+}
+
+/// How `ZUNIONSTORE` and `ZINTERSTORE` combine the scores of a member found in several sets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+/// Parse one of the sorted set commands
+///
+/// # Syntax
+/// ```text
+/// ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]
+/// ZCARD key
+/// ZSCORE key member
+/// ZINCRBY key increment member
+/// ZREM key member [member ...]
+/// ZCOUNT key min max
+/// ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+/// ZRANK key member [WITHSCORE]
+/// ZREVRANK key member [WITHSCORE]
+/// ZPOPMIN key [count]
+/// ZPOPMAX key [count]
+/// ZUNIONSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>]
+/// ZINTERSTORE destination numkeys key [key ...] [WEIGHTS weight [weight ...]]
+///   [AGGREGATE <SUM | MIN | MAX>]
+/// ```
+pub fn parse(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let valid = match command {
+        "ZADD" | "ZRANGE" | "ZUNIONSTORE" | "ZINTERSTORE" => args.len() >= 3,
+        "ZCARD" => args.len() == 1,
+        "ZSCORE" => args.len() == 2,
+        "ZINCRBY" | "ZCOUNT" => args.len() == 3,
+        "ZRANK" | "ZREVRANK" => args.len() == 2 || args.len() == 3,
+        "ZPOPMIN" | "ZPOPMAX" => args.len() == 1 || args.len() == 2,
+        _ => args.len() >= 2,
+    };
+    if !valid {
+        return Err(error_wrong_arity(command));
+    }
+
+    if matches!(command, "ZUNIONSTORE" | "ZINTERSTORE") {
+        return parse_store(command, args);
+    }
+
+    let mut iter = args.into_iter();
+    let key = iter.next().unwrap();
+    let request = match command {
+        "ZADD" => {
+            let mut iter = iter.peekable();
+            let (mut nx, mut xx, mut gt, mut lt) = (false, false, false, false);
+            let mut options = ZAddOptions::default();
+            while let Some(option) = iter.peek() {
+                match bytes_to_string(option).to_uppercase().as_str() {
+                    "NX" => nx = true,
+                    "XX" => xx = true,
+                    "GT" => gt = true,
+                    "LT" => lt = true,
+                    "CH" => options.ch = true,
+                    "INCR" => options.incr = true,
+                    _ => break,
+                }
+                iter.next();
+            }
+            if nx && xx {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR XX and NX options at the same time are not compatible",
+                ));
+            }
+            if [nx, gt, lt].into_iter().filter(|option| *option).count() > 1 {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR GT, LT, and/or NX options at the same time are not compatible",
+                ));
+            }
+            options.condition = match (nx, xx) {
+                (true, _) => Some(SetCondition::Nx),
+                (_, true) => Some(SetCondition::Xx),
+                _ => None,
+            };
+            options.comparison = match (gt, lt) {
+                (true, _) => Some(ScoreComparison::Gt),
+                (_, true) => Some(ScoreComparison::Lt),
+                _ => None,
+            };
+
+            let mut pairs = Vec::new();
+            while let Some(score) = iter.next() {
+                let member = iter.next().ok_or_else(error_syntax)?;
+                pairs.push((bytes_to_f64(&score).ok_or_else(error_not_float)?, member));
+            }
+            if pairs.is_empty() {
+                return Err(error_syntax());
+            }
+            if options.incr && pairs.len() > 1 {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR INCR option supports a single increment-element pair",
+                ));
+            }
+            Request::ZADD {
+                key,
+                options,
+                pairs,
+            }
+        }
+        "ZCARD" => Request::ZCARD { key },
+        "ZSCORE" => Request::ZSCORE {
+            key,
+            member: iter.next().unwrap(),
+        },
+        "ZINCRBY" => Request::ZINCRBY {
+            key,
+            increment: bytes_to_f64(&iter.next().unwrap()).ok_or_else(error_not_float)?,
+            member: iter.next().unwrap(),
+        },
+        "ZREM" => Request::ZREM {
+            key,
+            members: iter.collect(),
+        },
+        "ZCOUNT" => Request::ZCOUNT {
+            key,
+            min: parse_score_bound(&iter.next().unwrap())?,
+            max: parse_score_bound(&iter.next().unwrap())?,
+        },
+        "ZRANGE" => {
+            let (start, stop) = (iter.next().unwrap(), iter.next().unwrap());
+            let (mut by, mut rev, mut limit, mut withscores) = (None, false, None, false);
+            while let Some(option) = iter.next() {
+                match bytes_to_string(&option).to_uppercase().as_str() {
+                    "BYSCORE" | "BYLEX" if by.is_some() => return Err(error_syntax()),
+                    "BYSCORE" => by = Some("BYSCORE"),
+                    "BYLEX" => by = Some("BYLEX"),
+                    "REV" => rev = true,
+                    "WITHSCORES" => withscores = true,
+                    "LIMIT" => {
+                        let (Some(offset), Some(count)) = (iter.next(), iter.next()) else {
+                            return Err(error_syntax());
+                        };
+                        let offset = bytes_to_i64(&offset).ok_or_else(error_not_integer)?;
+                        let count = bytes_to_i64(&count).ok_or_else(error_not_integer)?;
+                        limit = Some((offset, count));
+                    }
+                    _ => return Err(error_syntax()),
+                }
+            }
+
+            if limit.is_some() && by.is_none() {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR syntax error, LIMIT is only supported in combination with either \
+                     BYSCORE or BYLEX",
+                ));
+            }
+            if withscores && by == Some("BYLEX") {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "ERR syntax error, WITHSCORES not supported in combination with BYLEX",
+                ));
+            }
+
+            /* Reversed score and lex ranges are given from the highest to the lowest */
+            let (min, max) = if rev {
+                (&stop, &start)
+            } else {
+                (&start, &stop)
+            };
+            let range = match by {
+                Some("BYSCORE") => ZRangeBy::Score {
+                    min: parse_score_bound(min)?,
+                    max: parse_score_bound(max)?,
+                },
+                Some(_) => ZRangeBy::Lex {
+                    min: parse_lex_bound(min)?,
+                    max: parse_lex_bound(max)?,
+                },
+                None => ZRangeBy::Rank {
+                    start: bytes_to_i64(&start).ok_or_else(error_not_integer)?,
+                    stop: bytes_to_i64(&stop).ok_or_else(error_not_integer)?,
+                },
+            };
+            Request::ZRANGE {
+                key,
+                range,
+                rev,
+                limit,
+                withscores,
+            }
+        }
+        "ZRANK" | "ZREVRANK" => {
+            let member = iter.next().unwrap();
+            let withscore = match iter.next() {
+                Some(option) if option.eq_ignore_ascii_case(b"WITHSCORE") => true,
+                Some(_) => return Err(error_syntax()),
+                None => false,
+            };
+            match command {
+                "ZRANK" => Request::ZRANK {
+                    key,
+                    member,
+                    withscore,
+                },
+                _ => Request::ZREVRANK {
+                    key,
+                    member,
+                    withscore,
+                },
+            }
+        }
+        _ => {
+            let count = iter.next().map(|count| parse_count(&count)).transpose()?;
+            match command {
+                "ZPOPMIN" => Request::ZPOPMIN { key, count },
+                _ => Request::ZPOPMAX { key, count },
+            }
+        }
+    };
+
+    Ok(request)
+}
+
+/// Parse `destination numkeys key [key ...] [WEIGHTS ...] [AGGREGATE ...]` of `ZUNIONSTORE`
+/// and `ZINTERSTORE`
+fn parse_store(command: &str, args: Vec<Bytes>) -> Result<Request, RedisProtocolError> {
+    let mut iter = args.into_iter();
+    let destination = iter.next().unwrap();
+    let numkeys = bytes_to_i64(&iter.next().unwrap()).ok_or_else(error_not_integer)?;
+    if numkeys < 1 {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            format!(
+                "ERR at least 1 input key is needed for '{}' command",
+                command.to_lowercase()
+            ),
+        ));
+    }
+    let keys: Vec<Bytes> = iter.by_ref().take(numkeys as usize).collect();
+    if keys.len() as i64 != numkeys {
+        return Err(error_syntax());
+    }
+
+    let (mut weights, mut aggregate) = (vec![1.0; keys.len()], Aggregate::Sum);
+    while let Some(option) = iter.next() {
+        match bytes_to_string(&option).to_uppercase().as_str() {
+            "WEIGHTS" => {
+                for weight in weights.iter_mut() {
+                    let value = iter.next().ok_or_else(error_syntax)?;
+                    *weight = bytes_to_f64(&value).ok_or_else(|| {
+                        RedisProtocolError::new(
+                            RedisProtocolErrorKind::Parse,
+                            "ERR weight value is not a float",
+                        )
+                    })?;
+                }
+            }
+            "AGGREGATE" => {
+                let value = iter.next().ok_or_else(error_syntax)?;
+                aggregate = match bytes_to_string(&value).to_uppercase().as_str() {
+                    "SUM" => Aggregate::Sum,
+                    "MIN" => Aggregate::Min,
+                    "MAX" => Aggregate::Max,
+                    _ => return Err(error_syntax()),
+                };
+            }
+            _ => return Err(error_syntax()),
+        }
+    }
+
+    Ok(match command {
+        "ZUNIONSTORE" => Request::ZUNIONSTORE {
+            destination,
+            keys,
+            weights,
+            aggregate,
+        },
+        _ => Request::ZINTERSTORE {
+            destination,
+            keys,
+            weights,
+            aggregate,
+        },
+    })
+}
+
+/// Parse a score bound: a number, `-inf` or `+inf`, exclusive if prefixed with `(`
+fn parse_score_bound(arg: &[u8]) -> Result<Bound<f64>, RedisProtocolError> {
+    let exclusive = arg.first() == Some(&b'(');
+    let score = bytes_to_f64(if exclusive { &arg[1..] } else { arg }).ok_or_else(|| {
+        RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR min or max is not a float",
+        )
+    })?;
+    Ok(match exclusive {
+        true => Bound::Excluded(score),
+        false => Bound::Included(score),
+    })
+}
+
+/// Parse a lexicographic bound: `-`, `+`, or a member prefixed with `[` or `(`
+fn parse_lex_bound(arg: &Bytes) -> Result<LexBound, RedisProtocolError> {
+    match arg.first() {
+        Some(b'-') if arg.len() == 1 => Ok(LexBound::Minimum),
+        Some(b'+') if arg.len() == 1 => Ok(LexBound::Maximum),
+        Some(b'[') => Ok(LexBound::Included(arg.slice(1..))),
+        Some(b'(') => Ok(LexBound::Excluded(arg.slice(1..))),
+        _ => Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR min or max not valid string range item",
+        )),
+    }
+}
+
+/// Handle sorted set commands on an empty keyspace
+pub fn default_handle(args: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+    let mut dummy = MemoryStore::new();
+    handle(&mut dummy, args)
+}
+
+/// Handle the sorted set commands, replying like Redis does to RESP2 clients. See
+/// [`handle_with_protocol`] to reply to clients of another protocol.
+pub fn handle<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    handle_with_protocol(values, args, Protocol::Resp2)
+}
+
+/// Handle the sorted set commands, shaping the replies for clients speaking `protocol`.
+///
+/// Keys that don't exist count as empty sorted sets, and a sorted set is removed with its last
+/// member. Scores are replied as doubles, which RESP2 clients receive as bulk strings. Members
+/// with their scores are replied as a flat array, each member followed by its score. Like in
+/// Redis, RESP3 clients receive an array of `[member, score]` pairs instead from
+/// `ZRANGE ... WITHSCORES` and from `ZPOPMIN` and `ZPOPMAX` with a `count`.
+///
+/// # Returns
+///  * `ZADD`: the number of members added, including the ones whose score changed with `CH`.
+///    With `INCR`, the new score, or [`OwnedFrame::Null`] if a condition was not met.
+///  * `ZCARD`: the number of members
+///  * `ZSCORE`: the score of the member, or [`OwnedFrame::Null`]
+///  * `ZINCRBY`: the new score
+///  * `ZREM`: the number of members removed
+///  * `ZCOUNT`: the number of members with a score between `min` and `max`
+///  * `ZRANGE`: the members in the range, ordered by score or in reverse with `REV`, together
+///    with their scores if `WITHSCORES` is given
+///  * `ZRANK`, `ZREVRANK`: the rank of the member counted from the lowest or highest score,
+///    together with its score if `WITHSCORE` is given, or [`OwnedFrame::Null`]
+///  * `ZPOPMIN`, `ZPOPMAX`: up to `count` members with the lowest or highest scores and their
+///    scores, one if `count` is not given
+///  * `ZUNIONSTORE`, `ZINTERSTORE`: the number of members stored at `destination`, which is
+///    replaced whatever its type and removed if there are none
+///
+/// `ZUNIONSTORE` and `ZINTERSTORE` read sets as well, with a score of `1` for every member. All
+/// commands fail with `WRONGTYPE` if a key they read holds a value of another type.
+pub fn handle_with_protocol<S: KeyValueStore>(
+    values: &mut S,
+    args: &Request,
+    protocol: Protocol,
+) -> Result<OwnedFrame, RedisProtocolError> {
+    let pairs = protocol == Protocol::Resp3;
+    match args {
+        Request::ZADD {
+            key,
+            options,
+            pairs,
+        } => values.update(key.clone(), |entry| {
+            let zset = zset_mut(entry)?;
+            let (mut added, mut changed, mut last) = (0i64, 0i64, None);
+            for (score, member) in pairs {
+                let current = zset.score(member);
+                match (current, options.condition) {
+                    (Some(_), Some(SetCondition::Nx)) | (None, Some(SetCondition::Xx)) => continue,
+                    _ => {}
+                }
+
+                let score = match (options.incr, current) {
+                    (true, Some(current)) => increment(current, *score)?,
+                    _ => *score,
+                };
+                let allowed = match (current, options.comparison) {
+                    (Some(current), Some(ScoreComparison::Gt)) => score > current,
+                    (Some(current), Some(ScoreComparison::Lt)) => score < current,
+                    _ => true,
+                };
+                if !allowed {
+                    continue;
+                }
+
+                match current {
+                    None => added += 1,
+                    Some(current) if current != score => changed += 1,
+                    Some(_) => {}
+                }
+                zset.insert(member.clone(), score);
+                last = Some(score);
+            }
+
+            Ok(match options {
+                ZAddOptions { incr: true, .. } => last.as_frame(),
+                ZAddOptions { ch: true, .. } => (added + changed).as_frame(),
+                _ => added.as_frame(),
+            })
+        }),
+        Request::ZCARD { key } => values.read(key, |value| {
+            let length = zset(value)?.map_or(0, SortedSet::len);
+            Ok((length as i64).as_frame())
+        }),
+        Request::ZSCORE { key, member } => values.read(key, |value| {
+            Ok(zset(value)?.and_then(|zset| zset.score(member)).as_frame())
+        }),
+        Request::ZINCRBY {
+            key,
+            increment: delta,
+            member,
+        } => values.update(key.clone(), |entry| {
+            let zset = zset_mut(entry)?;
+            let score = increment(zset.score(member).unwrap_or(0.0), *delta)?;
+            zset.insert(member.clone(), score);
+            Ok(score.as_frame())
+        }),
+        Request::ZREM { key, members } => {
+            let removed = values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(0);
+                }
+                let zset = zset_mut(entry)?;
+                let removed = members
+                    .iter()
+                    .filter(|member| zset.remove(member).is_some())
+                    .count();
+                Ok::<_, RedisProtocolError>(removed)
+            })?;
+            Ok((removed as i64).as_frame())
+        }
+        Request::ZCOUNT { key, min, max } => values.read(key, |value| {
+            let count = zset(value)?.map_or(0, |zset| zset.range_by_score(*min, *max).count());
+            Ok((count as i64).as_frame())
+        }),
+        Request::ZRANGE {
+            key,
+            range: by,
+            rev,
+            limit,
+            withscores,
+        } => values.read(key, |value| {
+            let Some(zset) = zset(value)? else {
+                return Ok(Vec::<Bytes>::new().as_frame());
+            };
+
+            let (offset, count) = match limit {
+                Some((offset, _)) if *offset < 0 => return Ok(Vec::<Bytes>::new().as_frame()),
+                Some((offset, count)) => (*offset as usize, usize::try_from(*count).ok()),
+                None => (0, None),
+            };
+            let members = match by {
+                ZRangeBy::Rank { start, stop } => {
+                    let ranks = range(zset.len(), *start, *stop);
+                    select(zset.iter(), *rev, ranks.start, Some(ranks.len()))
+                }
+                ZRangeBy::Score { min, max } => {
+                    select(zset.range_by_score(*min, *max), *rev, offset, count)
+                }
+                ZRangeBy::Lex { min, max } => {
+                    let members = zset
+                        .iter()
+                        .filter(|(member, _)| lex_contains(min, max, member));
+                    select(members, *rev, offset, count)
+                }
+            };
+            Ok(scored(members, *withscores, pairs))
+        }),
+        Request::ZRANK {
+            key,
+            member,
+            withscore,
+        }
+        | Request::ZREVRANK {
+            key,
+            member,
+            withscore,
+        } => values.read(key, |value| {
+            let Some(zset) = zset(value)? else {
+                return Ok(OwnedFrame::Null);
+            };
+            let (Some(rank), Some(score)) = (zset.rank(member), zset.score(member)) else {
+                return Ok(OwnedFrame::Null);
+            };
+
+            let rank = match args {
+                Request::ZRANK { .. } => rank,
+                _ => zset.len() - 1 - rank,
+            } as i64;
+            Ok(match withscore {
+                true => vec![rank.as_frame(), score.as_frame()].as_frame(),
+                false => rank.as_frame(),
+            })
+        }),
+        Request::ZPOPMIN { key, count } | Request::ZPOPMAX { key, count } => {
+            values.update(key.clone(), |entry| {
+                if entry.is_none() {
+                    return Ok(Vec::<Bytes>::new().as_frame());
+                }
+                let zset = zset_mut(entry)?;
+                let popped: Vec<(Bytes, f64)> = (0..count.unwrap_or(1))
+                    .map_while(|_| match args {
+                        Request::ZPOPMIN { .. } => zset.pop_first(),
+                        _ => zset.pop_last(),
+                    })
+                    .collect();
+                let popped = popped.iter().map(|(m, s)| (m, *s)).collect();
+                Ok(scored(popped, true, pairs && count.is_some()))
+            })
+        }
+        Request::ZUNIONSTORE {
+            destination,
+            keys,
+            weights,
+            aggregate,
+        }
+        | Request::ZINTERSTORE {
+            destination,
+            keys,
+            weights,
+            aggregate,
+        } => {
+            let union = matches!(args, Request::ZUNIONSTORE { .. });
+            let (distinct, positions) = distinct(keys.iter().chain([destination]));
+            let length = values.update_many(&distinct, |entries| {
+                let sources = keys
+                    .iter()
+                    .map(|key| Source::new(entries[positions[key]].as_ref()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let result = combine(&sources, weights, *aggregate, union);
+                let length = result.len();
+                entries[positions[destination]] = Some(Entry::new(result));
+                Ok::<_, RedisProtocolError>(length)
+            })?;
+            Ok((length as i64).as_frame())
+        }
+        _ => panic!(
+            "Expected enum variant ZADD, ZCARD, ZSCORE, ZINCRBY, ZREM, ZCOUNT, ZRANGE, ZRANK, \
+             ZREVRANK, ZPOPMIN, ZPOPMAX, ZUNIONSTORE or ZINTERSTORE, but got {:?}",
+            args.type_id()
+        ),
+    }
+}
+
+/// Return the sorted set `value`, `None` if there is no value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a sorted set
+pub(crate) fn zset(value: Option<&Value>) -> Result<Option<&SortedSet>, RedisProtocolError> {
+    match value {
+        Some(value) => value.as_zset().map(Some).ok_or_else(error_wrong_type),
+        None => Ok(None),
+    }
+}
+
+/// Return the sorted set in `entry` for modification, creating an empty one if there is no
+/// value.
+///
+/// # Returns
+/// A `WRONGTYPE` error if the value is not a sorted set
+fn zset_mut(entry: &mut Option<Entry>) -> Result<&mut SortedSet, RedisProtocolError> {
+    let entry = entry.get_or_insert_with(|| Entry::new(SortedSet::new()));
+    entry.value.as_zset_mut().ok_or_else(error_wrong_type)
+}
+
+/// Add `delta` to `score`, failing if the result is not a number, e.g. for `+inf` and `-inf`
+fn increment(score: f64, delta: f64) -> Result<f64, RedisProtocolError> {
+    let score = score + delta;
+    if score.is_nan() {
+        return Err(RedisProtocolError::new(
+            RedisProtocolErrorKind::Parse,
+            "ERR resulting score is not a number (NaN)",
+        ));
+    }
+    Ok(score)
+}
+
+/// Skip `offset` members and take up to `count` of the rest, or all of them if `count` is
+/// `None`, starting from the highest score if `rev` is given
+fn select<'a>(
+    members: impl DoubleEndedIterator<Item = (&'a Bytes, f64)>,
+    rev: bool,
+    offset: usize,
+    count: Option<usize>,
+) -> Vec<(&'a Bytes, f64)> {
+    let count = count.unwrap_or(usize::MAX);
+    match rev {
+        true => members.rev().skip(offset).take(count).collect(),
+        false => members.skip(offset).take(count).collect(),
+    }
+}
+
+/// Reply with `members`, each followed by its score if `withscores` is given. With `pairs`, each
+/// member and its score are replied as an array of their own.
+fn scored(members: Vec<(&Bytes, f64)>, withscores: bool, pairs: bool) -> OwnedFrame {
+    let mut data = Vec::new();
+    for (member, score) in members {
+        match (withscores, pairs) {
+            (true, true) => data.push(vec![member.as_frame(), score.as_frame()].as_frame()),
+            (true, false) => data.extend([member.as_frame(), score.as_frame()]),
+            (false, _) => data.push(member.as_frame()),
+        }
+    }
+    OwnedFrame::Array {
+        data,
+        attributes: None,
+    }
+}
+
+/// Check whether `member` is between the lexicographic bounds `min` and `max`
+fn lex_contains(min: &LexBound, max: &LexBound, member: &[u8]) -> bool {
+    let above = match min {
+        LexBound::Minimum => true,
+        LexBound::Maximum => false,
+        LexBound::Included(min) => member >= &min[..],
+        LexBound::Excluded(min) => member > &min[..],
+    };
+    let below = match max {
+        LexBound::Minimum => false,
+        LexBound::Maximum => true,
+        LexBound::Included(max) => member <= &max[..],
+        LexBound::Excluded(max) => member < &max[..],
+    };
+    above && below
+}
+
+/// Input of `ZUNIONSTORE` and `ZINTERSTORE`
+enum Source<'a> {
+    Missing,
+    Sorted(&'a SortedSet),
+    /// A set, whose members all have a score of `1`
+    Plain(&'a Set),
+}
+
+impl<'a> Source<'a> {
+    /// Read the sorted set or set in `entry`.
+    ///
+    /// # Returns
+    /// A `WRONGTYPE` error for values of any other type
+    fn new(entry: Option<&'a Entry>) -> Result<Self, RedisProtocolError> {
+        match entry.map(|entry| &entry.value) {
+            None => Ok(Source::Missing),
+            Some(Value::ZSet(zset)) => Ok(Source::Sorted(zset)),
+            Some(Value::Set(set)) => Ok(Source::Plain(set)),
+            Some(_) => Err(error_wrong_type()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Source::Missing => 0,
+            Source::Sorted(zset) => zset.len(),
+            Source::Plain(set) => set.len(),
+        }
+    }
+
+    fn score(&self, member: &[u8]) -> Option<f64> {
+        match self {
+            Source::Missing => None,
+            Source::Sorted(zset) => zset.score(member),
+            Source::Plain(set) => set.contains(member).then_some(1.0),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&'a Bytes, f64)> + 'a> {
+        match *self {
+            Source::Missing => Box::new(std::iter::empty()),
+            Source::Sorted(zset) => Box::new(zset.iter()),
+            Source::Plain(set) => Box::new(set.iter().map(|member| (member, 1.0))),
+        }
+    }
+}
+
+impl Aggregate {
+    /// Combine the scores `a` and `b`, where infinities of opposite sign sum up to `0`
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            Aggregate::Sum => zero_if_nan(a + b),
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+/// Combine the scores of the members of `sources`, multiplied by their `weights`. A union
+/// contains the members of any source, an intersection those of every source.
+fn combine(sources: &[Source], weights: &[f64], aggregate: Aggregate, union: bool) -> SortedSet {
+    let weighted = |score: f64, weight: f64| zero_if_nan(score * weight);
+
+    if union {
+        let mut scores: HashMap<&Bytes, f64> = HashMap::new();
+        for (source, weight) in sources.iter().zip(weights) {
+            for (member, score) in source.iter() {
+                let score = weighted(score, *weight);
+                scores
+                    .entry(member)
+                    .and_modify(|total| *total = aggregate.apply(*total, score))
+                    .or_insert(score);
+            }
+        }
+        return scores
+            .into_iter()
+            .map(|(member, score)| (member.clone(), score))
+            .collect();
+    }
+
+    let Some(smallest) = sources.iter().min_by_key(|source| source.len()) else {
+        return SortedSet::new();
+    };
+    smallest
+        .iter()
+        .filter_map(|(member, _)| {
+            let mut total = None;
+            for (source, weight) in sources.iter().zip(weights) {
+                let score = weighted(source.score(member)?, *weight);
+                total = Some(total.map_or(score, |total| aggregate.apply(total, score)));
+            }
+            Some((member.clone(), total?))
+        })
+        .collect()
+}
+
+fn zero_if_nan(score: f64) -> f64 {
+    if score.is_nan() {
+        0.0
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::testing::run;
+
+    /// Flat reply of members and their scores
+    fn scores(members: &[(&str, f64)]) -> OwnedFrame {
+        let members: Vec<(Bytes, f64)> = members
+            .iter()
+            .map(|(member, score)| (Bytes::from(member.to_string()), *score))
+            .collect();
+        scored(members.iter().map(|(m, s)| (m, *s)).collect(), true, false)
+    }
+
+    #[test]
+    fn leaderboard() {
+        let mut values = MemoryStore::new();
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "board", "10", "ann", "20", "bob", "15", "cat"]
+            )
+            .unwrap(),
+            3.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "board", "GT", "CH", "5", "ann", "25", "bob"]
+            )
+            .unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "board", "NX", "INCR", "1", "ann"]
+            )
+            .unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZINCRBY", "board", "2.5", "ann"]).unwrap(),
+            12.5.as_frame()
+        );
+
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "board", "0", "-1", "WITHSCORES"]
+            )
+            .unwrap(),
+            scores(&[("ann", 12.5), ("cat", 15.0), ("bob", 25.0)])
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "board", "+inf", "(12.5", "BYSCORE", "REV", "LIMIT", "0", "1"]
+            )
+            .unwrap(),
+            vec!["bob"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZREVRANK", "board", "cat", "WITHSCORE"]
+            )
+            .unwrap(),
+            vec![1.as_frame(), 15.0.as_frame()].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZCOUNT", "board", "(12.5", "25"]).unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZPOPMAX", "board"]).unwrap(),
+            scores(&[("bob", 25.0)])
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZREM", "board", "ann", "bob"]).unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZRANK", "board", "bob"]).unwrap(),
+            OwnedFrame::Null
+        );
+
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "board", "NX", "XX", "1", "a"]
+            )
+            .unwrap_err()
+            .details(),
+            "ERR XX and NX options at the same time are not compatible"
+        );
+        assert!(run(handle, &mut values, &["ZADD", "board", "nan", "a"]).is_err());
+        assert!(run(
+            handle,
+            &mut values,
+            &["ZRANGE", "board", "0", "1", "LIMIT", "0", "1"]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn lex_ranges_and_aggregation() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["ZADD", "words", "0", "a", "0", "b", "0", "c", "0", "d"],
+        )
+        .unwrap();
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "words", "(a", "[c", "BYLEX"]
+            )
+            .unwrap(),
+            vec!["b", "c"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "words", "+", "(b", "BYLEX", "REV"]
+            )
+            .unwrap(),
+            vec!["d", "c"].as_frame()
+        );
+
+        run(handle, &mut values, &["ZADD", "x", "1", "a", "2", "b"]).unwrap();
+        run(handle, &mut values, &["ZADD", "y", "3", "b", "4", "c"]).unwrap();
+        let tags: Set = [Bytes::from("b")].into_iter().collect();
+        values.set(Bytes::from_static(b"tags"), tags);
+
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZUNIONSTORE", "out", "2", "x", "y", "WEIGHTS", "2", "1"]
+            )
+            .unwrap(),
+            3.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "out", "0", "-1", "WITHSCORES"]
+            )
+            .unwrap(),
+            scores(&[("a", 2.0), ("c", 4.0), ("b", 7.0)])
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &[
+                    "ZINTERSTORE",
+                    "out",
+                    "3",
+                    "x",
+                    "y",
+                    "tags",
+                    "AGGREGATE",
+                    "MAX"
+                ]
+            )
+            .unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZSCORE", "out", "b"]).unwrap(),
+            3.0.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZINTERSTORE", "out", "2", "x", "missing"]
+            )
+            .unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"out"));
+
+        values.set(Bytes::from_static(b"text"), Bytes::from_static(b"x"));
+        let error = run(
+            handle,
+            &mut values,
+            &["ZUNIONSTORE", "out", "2", "x", "text"],
+        )
+        .unwrap_err();
+        assert!(error.details().starts_with("WRONGTYPE"));
+    }
+
+    #[test]
+    fn add_conditions() {
+        let mut values = MemoryStore::new();
+        run(handle, &mut values, &["ZADD", "z", "10", "a", "20", "b"]).unwrap();
+
+        /* GT and LT only restrict updates, new members are still added */
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "GT", "CH", "5", "a", "30", "b", "1", "c"]
+            )
+            .unwrap(),
+            2.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "LT", "5", "a", "40", "b", "2", "d"]
+            )
+            .unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "z", "0", "-1", "WITHSCORES"]
+            )
+            .unwrap(),
+            scores(&[("c", 1.0), ("d", 2.0), ("a", 5.0), ("b", 30.0)])
+        );
+
+        /* XX never adds, and CH counts only members whose score changed */
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "XX", "CH", "5", "a", "31", "b", "1", "e"]
+            )
+            .unwrap(),
+            1.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZSCORE", "z", "e"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "XX", "GT", "CH", "1", "a"]
+            )
+            .unwrap(),
+            0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZADD", "missing", "XX", "1", "a"]).unwrap(),
+            0.as_frame()
+        );
+        assert!(!values.exists(b"missing"));
+
+        /* INCR replies the new score, or nil if a condition rejected it */
+        assert_eq!(
+            run(handle, &mut values, &["ZADD", "z", "GT", "INCR", "2", "a"]).unwrap(),
+            7.0.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZADD", "z", "GT", "INCR", "-2", "a"]).unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZADD", "z", "LT", "INCR", "-2", "a"]).unwrap(),
+            5.0.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "XX", "INCR", "1", "new"]
+            )
+            .unwrap(),
+            OwnedFrame::Null
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "NX", "INCR", "3", "new"]
+            )
+            .unwrap(),
+            3.0.as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "CH", "INCR", "0", "new"]
+            )
+            .unwrap(),
+            3.0.as_frame()
+        );
+
+        let incompatible = "ERR GT, LT, and/or NX options at the same time are not compatible";
+        for options in [["GT", "LT"], ["NX", "GT"], ["LT", "NX"]] {
+            let args = ["ZADD", "z", options[0], options[1], "1", "a"];
+            let error = run(handle, &mut values, &args).unwrap_err();
+            assert_eq!(error.details(), incompatible);
+        }
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZADD", "z", "INCR", "1", "a", "2", "b"]
+            )
+            .unwrap_err()
+            .details(),
+            "ERR INCR option supports a single increment-element pair"
+        );
+        assert!(run(handle, &mut values, &["ZADD", "z", "GT", "1"]).is_err());
+        assert!(run(handle, &mut values, &["ZADD", "z", "INCR", "+inf", "c"]).is_ok());
+        assert!(run(handle, &mut values, &["ZADD", "z", "INCR", "-inf", "c"]).is_err());
+    }
+
+    #[test]
+    fn lex_range_bounds() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &[
+                "ZADD", "w", "0", "a", "0", "ab", "0", "b", "0", "ba", "0", "c",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "w", "-", "+", "BYLEX"]).unwrap(),
+            vec!["a", "ab", "b", "ba", "c"].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "w", "[a", "(b", "BYLEX"]).unwrap(),
+            vec!["a", "ab"].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "w", "(a", "[b", "BYLEX"]).unwrap(),
+            vec!["ab", "b"].as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "w", "[b", "+", "BYLEX"]).unwrap(),
+            vec!["b", "ba", "c"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "w", "-", "+", "BYLEX", "LIMIT", "1", "2"]
+            )
+            .unwrap(),
+            vec!["ab", "b"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "w", "[ba", "(ab", "BYLEX", "REV", "LIMIT", "0", "-1"]
+            )
+            .unwrap(),
+            vec!["ba", "b"].as_frame()
+        );
+
+        /* Empty and inverted ranges */
+        let empty = Vec::<Bytes>::new().as_frame();
+        for (min, max) in [("(b", "[b"), ("[c", "[a"), ("+", "-"), ("(c", "+")] {
+            let args = ["ZRANGE", "w", min, max, "BYLEX"];
+            assert_eq!(run(handle, &mut values, &args).unwrap(), empty);
+        }
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "missing", "-", "+", "BYLEX"]
+            )
+            .unwrap(),
+            empty
+        );
+
+        for (min, max) in [("a", "+"), ("-", "c"), ("", "+")] {
+            let args = ["ZRANGE", "w", min, max, "BYLEX"];
+            assert!(run(handle, &mut values, &args).is_err());
+        }
+    }
+
+    #[test]
+    fn reverse_ranges_with_limit() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &[
+                "ZADD", "z", "1", "a", "2", "b", "3", "c", "4", "d", "5", "e",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "z", "0", "1", "REV"]).unwrap(),
+            vec!["e", "d"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "z", "-2", "-1", "REV", "WITHSCORES"]
+            )
+            .unwrap(),
+            scores(&[("b", 2.0), ("a", 1.0)])
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "z", "+inf", "-inf", "BYSCORE", "REV", "LIMIT", "1", "2"]
+            )
+            .unwrap(),
+            vec!["d", "c"].as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &[
+                    "ZRANGE",
+                    "z",
+                    "(4",
+                    "2",
+                    "BYSCORE",
+                    "REV",
+                    "LIMIT",
+                    "1",
+                    "-1",
+                    "WITHSCORES"
+                ]
+            )
+            .unwrap(),
+            scores(&[("b", 2.0)])
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "z", "5", "1", "BYSCORE", "REV", "LIMIT", "10", "5"]
+            )
+            .unwrap(),
+            Vec::<Bytes>::new().as_frame()
+        );
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZRANGE", "z", "5", "1", "BYSCORE", "REV", "LIMIT", "0", "0"]
+            )
+            .unwrap(),
+            Vec::<Bytes>::new().as_frame()
+        );
+
+        /* Without REV, the bounds are in the usual order */
+        assert_eq!(
+            run(handle, &mut values, &["ZRANGE", "z", "5", "1", "BYSCORE"]).unwrap(),
+            Vec::<Bytes>::new().as_frame()
+        );
+        assert!(run(
+            handle,
+            &mut values,
+            &["ZRANGE", "z", "5", "1", "BYSCORE", "LIMIT", "0"]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn weighted_aggregation() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["ZADD", "x", "1", "a", "2", "b", "3", "c"],
+        )
+        .unwrap();
+        run(
+            handle,
+            &mut values,
+            &["ZADD", "y", "10", "b", "20", "c", "30", "d"],
+        )
+        .unwrap();
+        let store = |values: &mut MemoryStore, args: &[&str]| {
+            run(handle, values, args).unwrap();
+            run(handle, values, &["ZRANGE", "out", "0", "-1", "WITHSCORES"]).unwrap()
+        };
+
+        assert_eq!(
+            store(
+                &mut values,
+                &[
+                    "ZUNIONSTORE",
+                    "out",
+                    "2",
+                    "x",
+                    "y",
+                    "WEIGHTS",
+                    "3",
+                    "0.5",
+                    "AGGREGATE",
+                    "MIN"
+                ]
+            ),
+            scores(&[("a", 3.0), ("b", 5.0), ("c", 9.0), ("d", 15.0)])
+        );
+        assert_eq!(
+            store(
+                &mut values,
+                &[
+                    "ZUNIONSTORE",
+                    "out",
+                    "2",
+                    "x",
+                    "y",
+                    "WEIGHTS",
+                    "3",
+                    "0.5",
+                    "AGGREGATE",
+                    "MAX"
+                ]
+            ),
+            scores(&[("a", 3.0), ("b", 6.0), ("c", 10.0), ("d", 15.0)])
+        );
+        assert_eq!(
+            store(
+                &mut values,
+                &[
+                    "ZINTERSTORE",
+                    "out",
+                    "2",
+                    "x",
+                    "y",
+                    "WEIGHTS",
+                    "-1",
+                    "1",
+                    "AGGREGATE",
+                    "min"
+                ]
+            ),
+            scores(&[("c", -3.0), ("b", -2.0)])
+        );
+        assert_eq!(
+            store(
+                &mut values,
+                &[
+                    "ZINTERSTORE",
+                    "out",
+                    "2",
+                    "x",
+                    "y",
+                    "WEIGHTS",
+                    "2",
+                    "1",
+                    "AGGREGATE",
+                    "MAX"
+                ]
+            ),
+            scores(&[("b", 10.0), ("c", 20.0)])
+        );
+        assert_eq!(
+            store(
+                &mut values,
+                &["ZINTERSTORE", "out", "2", "x", "y", "WEIGHTS", "2", "1"]
+            ),
+            scores(&[("b", 14.0), ("c", 26.0)])
+        );
+
+        /* An infinite score times a weight of zero counts as zero */
+        run(handle, &mut values, &["ZADD", "inf", "+inf", "a"]).unwrap();
+        assert_eq!(
+            store(
+                &mut values,
+                &["ZUNIONSTORE", "out", "1", "inf", "WEIGHTS", "0"]
+            ),
+            scores(&[("a", 0.0)])
+        );
+
+        /* The destination may be one of the sources */
+        assert_eq!(
+            run(
+                handle,
+                &mut values,
+                &["ZUNIONSTORE", "x", "2", "x", "x", "AGGREGATE", "MAX"]
+            )
+            .unwrap(),
+            3.as_frame()
+        );
+        assert_eq!(
+            run(handle, &mut values, &["ZSCORE", "x", "c"]).unwrap(),
+            3.0.as_frame()
+        );
+
+        for args in [
+            &["ZUNIONSTORE", "out", "2", "x", "y", "WEIGHTS", "1"][..],
+            &["ZUNIONSTORE", "out", "2", "x", "y", "WEIGHTS", "1", "one"],
+            &["ZUNIONSTORE", "out", "2", "x", "y", "AGGREGATE", "AVG"],
+            &["ZUNIONSTORE", "out", "2", "x", "y", "AGGREGATE"],
+            &["ZINTERSTORE", "out", "0", "x"],
+            &["ZINTERSTORE", "out", "3", "x", "y"],
+        ] {
+            assert!(run(handle, &mut values, args).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn scores_by_protocol() {
+        let mut values = MemoryStore::new();
+        run(
+            handle,
+            &mut values,
+            &["ZADD", "z", "1", "a", "2", "b", "3", "c"],
+        )
+        .unwrap();
+        let query = |values: &mut MemoryStore, args: &[&str], protocol| {
+            let handle =
+                |values, request: &Request| handle_with_protocol(values, request, protocol);
+            run(handle, values, args).unwrap()
+        };
+        let pair = |member: &str, score: f64| vec![member.as_frame(), score.as_frame()].as_frame();
+
+        let withscores = ["ZRANGE", "z", "0", "1", "WITHSCORES"];
+        assert_eq!(
+            query(&mut values, &withscores, Protocol::Resp2),
+            scores(&[("a", 1.0), ("b", 2.0)])
+        );
+        assert_eq!(
+            query(&mut values, &withscores, Protocol::Resp3),
+            vec![pair("a", 1.0), pair("b", 2.0)].as_frame()
+        );
+        assert_eq!(
+            query(&mut values, &["ZRANGE", "z", "0", "1"], Protocol::Resp3),
+            vec!["a", "b"].as_frame()
+        );
+
+        /* Without a count, a single member is replied flat in either protocol */
+        let mut copy = values.clone();
+        assert_eq!(
+            query(&mut values, &["ZPOPMIN", "z"], Protocol::Resp3),
+            scores(&[("a", 1.0)])
+        );
+        assert_eq!(
+            query(&mut copy, &["ZPOPMIN", "z"], Protocol::Resp2),
+            scores(&[("a", 1.0)])
+        );
+        assert_eq!(
+            query(&mut values, &["ZPOPMAX", "z", "1"], Protocol::Resp3),
+            vec![pair("c", 3.0)].as_frame()
+        );
+        assert_eq!(
+            query(&mut copy, &["ZPOPMAX", "z", "1"], Protocol::Resp2),
+            scores(&[("c", 3.0)])
+        );
+        assert_eq!(
+            query(&mut values, &["ZPOPMIN", "z", "5"], Protocol::Resp3),
+            vec![pair("b", 2.0)].as_frame()
+        );
+        assert_eq!(
+            query(&mut values, &["ZPOPMIN", "z", "5"], Protocol::Resp3),
+            Vec::<Bytes>::new().as_frame()
+        );
+    }
+}
@@ -1,115 +1,70 @@
 /*! # Example
 
-Open a tcp port and wait for connections:
+Implement a [`server::handler::CommandHandler`] for the commands you want to serve yourself.
+Every other command falls back to its `default_handle`:
 
 ```rust
-use tokio::net::{TcpListener, TcpStream};
-use core::net::SocketAddr;
-
-# fn handle_client(tcp_stream: TcpStream, socket_addr: SocketAddr) {}
-
-#[tokio::main]
-async fn main() -> std::io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:6380").await?;
-
-    loop {
-        let (tcp_stream, socket_addr) = listener.accept().await?;
-        tokio::spawn(async move {
-            handle_client(tcp_stream, socket_addr).await;
-        });
-    }
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+use redis_protocol_bridge::commands::parse::Request;
+use redis_protocol_bridge::commands::{get, set};
+use redis_protocol_bridge::server::handler::CommandHandler;
+use redis_protocol_bridge::server::session::Session;
+use redis_protocol_bridge::storage::sharded::ShardedStore;
+
+#[derive(Default)]
+struct MyHandler {
+    store: ShardedStore,
 }
- ```
-
-Read the incoming data, pass it to [`parse_owned_frame`] and then handle the command yourself
-using `handle_command`:
-
-```rust
-use tokio::net::{TcpListener, TcpStream};
-use core::net::SocketAddr;
-use std::collections::HashMap;
-
-async fn handle_client(mut stream: TcpStream, addr: SocketAddr) {
-    info!("Incoming connection from: {}", addr);
-    let mut map: HashMap<String, String> = HashMap::new();
-    loop {
-        stream.readable().await.unwrap();
-        let mut buf = [0; 512];
 
-        match stream.read(&mut buf).await {
-            /* [...] */
-        };
+impl CommandHandler for MyHandler {
+    fn get(&self, _session: &mut Session, request: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+        get::handle(&&self.store, request)
+    }
 
-        let res_op = decode::complete::decode(&mut buf);
-        match res_op {
-            Ok(Some((frame, _size))) => {
-                let query = parse_owned_frame(frame);
-                info!("{:?}", query);
-                let reply = handle_command(query, &mut map);
-                stream
-                    .write_all(&reply)
-                    .await
-                    .expect("Failed to send reply");
-                stream.flush().await.unwrap()
-            }
-            Ok(None) => warn!("Received empty command"),
-            Err(e) => error!("Error: {}", e),
-        }
+    fn set(&self, _session: &mut Session, request: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+        set::handle(&mut &self.store, request)
     }
 }
 ```
 
-Your `handle_command` could look like this:
-
-```rust
-fn handle_command(query: Vec<String>, map: &mut HashMap<String, String>) -> Vec<u8> {
-    let reply: OwnedFrame;
+Then open a tcp port and answer incoming connections until the process receives `Ctrl+C`:
 
-    if let Ok(request) = parse::parse(query) {
-        let r = match request {
-            Request::HELLO   { .. } => hello::default_handle(request),
-            Request::GET     { .. } => get::handle(map, request),
-            Request::SET     { .. } => set::handle(map, request),
-            Request::COMMAND { .. } => command::default_handle(request),
-            Request::INFO    { .. } => info::default_handle(request),
-            Request::PING    { .. } => ping::default_handle(request),
-            Request::SELECT  { .. } => select::default_handle(request)
-        };
+```rust,no_run
+# use redis_protocol_bridge::server::handler::CommandHandler;
+# #[derive(Default)]
+# struct MyHandler;
+# impl CommandHandler for MyHandler {}
+use redis_protocol_bridge::server::runner::serve_with_shutdown;
+use tokio::net::TcpListener;
 
-        reply = r.unwrap_or_else(|err|
-            OwnedFrame::SimpleError {
-                data: err.details().to_string(),
-                attributes: None
-            }
-        )
-    } else {
-        reply = OwnedFrame::SimpleError {
-            data: "Unsupported Command".to_string(),
-            attributes: None
-        }
-    }
-
-    debug!("Reply: {:#?}", reply);
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:6380").await?;
 
-    let mut buf: Vec<u8> = vec![0u8; reply.encode_len(false)];
-    encode::complete::encode(
-        &mut buf,
-        &reply,
-        false).expect("Failed to encode");
-    buf
+    serve_with_shutdown(listener, MyHandler::default(), async {
+        tokio::signal::ctrl_c().await.ok();
+    })
+    .await
 }
 ```
 
+To run your own accept loop instead, wrap each stream in a [`codec::RespCodec`], pass the
+frames to [`parse_owned_frame`] and let a [`server::dispatcher::Dispatcher`] call your handler.
+
 **/
 
+pub mod codec;
 pub mod commands;
+pub mod server;
+pub mod storage;
 pub mod util;
 
+use bytes::Bytes;
 use redis_protocol::resp3::types::OwnedFrame;
-use redis_protocol::resp3::types::Resp3Frame;
 
-/// Turn an incoming, potentially nested  [`OwnedFrame`] into a list of strings
-pub fn parse_owned_frame(frame: OwnedFrame) -> Vec<String> {
+/// Turn an incoming, potentially nested  [`OwnedFrame`] into a list of binary-safe arguments
+pub fn parse_owned_frame(frame: OwnedFrame) -> Vec<Bytes> {
     // Maybe return command type here
     let mut ret = Vec::new();
     match frame {
@@ -120,14 +75,10 @@ pub fn parse_owned_frame(frame: OwnedFrame) -> Vec<String> {
             }
         }
 
-        OwnedFrame::BlobString {
-            data: _,
-            attributes: _,
-        } => match frame.to_string() {
-            Some(content) => ret.push(content),
-            None => ret.push("ERROR".to_string()),
-        },
-        _ => ret.push("No Idea".to_string()),
+        OwnedFrame::BlobString { data, .. } | OwnedFrame::SimpleString { data, .. } => {
+            ret.push(Bytes::from(data))
+        }
+        _ => ret.push(Bytes::from_static(b"No Idea")),
     }
     ret
 }
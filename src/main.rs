@@ -1,115 +1,1048 @@
-mod commands;
-mod util;
-use crate::commands::*;
-
-use log::{debug, error, info, warn};
-
-use std::net::SocketAddr;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
-
-use crate::commands::config;
-use crate::commands::parse::Request;
+use redis_protocol::error::RedisProtocolError;
 use redis_protocol::resp3::types::*;
-use redis_protocol::resp3::{decode, encode};
-use redis_protocol_bridge::parse_owned_frame;
-use std::collections::HashMap;
-use std::env;
+use redis_protocol_bridge::commands::parse::Request;
+use redis_protocol_bridge::commands::{
+    db, expire, get, hash, incr, keys, list, mget, scan, select, set, sets, string, zset,
+};
+use redis_protocol_bridge::server::auth::StaticPassword;
+use redis_protocol_bridge::server::handler::CommandHandler;
+use redis_protocol_bridge::server::runner::{setup_logging, Server};
+use redis_protocol_bridge::server::session::Session;
+use redis_protocol_bridge::storage::databases::{Databases, DEFAULT_DATABASES};
+use redis_protocol_bridge::storage::sharded::ShardedStore;
+use redis_protocol_bridge::util::errors::error_db_index_out_of_range;
+use std::sync::Arc;
+use tokio::net::TcpListener;
 /*##########################################################*/
 /*  Everything below is part of the minimal example binary  */
 /*##########################################################*/
 
-/// Dispatch command handlers.
+/// Serve the keyspace commands, i.e. keys, expiry, strings, hashes, lists, sets and sorted sets,
+/// from one [`ShardedStore`] per database, and actively expire keys in all of them. Connection
+/// and server commands like `PING`, `HELLO`, `INFO` and `CONFIG` use the default handles. Every
+/// connection sees the same keyspace, `SELECT` switches between the databases and `MOVE`,
+/// `SWAPDB` and `COPY ... DB` work across them. The number of databases is read from
+/// `REDIS_DATABASES` and defaults to 16.
 ///
-/// This method is not part of the library and would be implemented by the server itself.
+/// This handler is not part of the library and would be implemented by the server itself.
 /// For redis documentation on commands see [Commands](https://redis.io/docs/latest/commands/)
-fn handle_command(query: Vec<String>, map: &mut HashMap<String, String>) -> Vec<u8> {
-    let reply: OwnedFrame = match parse::parse(query) {
-        Ok(request) => {
-            debug!("{:?}", request);
-
-            let r = match request {
-                Request::HELLO { .. } => hello::default_handle(&request),
-                Request::GET { .. } => get::handle(map, &request),
-                Request::SET { .. } => set::handle(map, &request),
-                Request::COMMAND { .. } => command::default_handle(&request),
-                Request::INFO { .. } => info::default_handle(&request),
-                Request::PING { .. } => ping::default_handle(&request),
-                Request::SELECT { .. } => select::default_handle(&request),
-                Request::QUIT { .. } => quit::default_handle(&request),
-                Request::CLUSTER { .. } => cluster::default_handle(&request),
-                Request::CONFIG { .. } => config::default_handle(&request),
-            };
-
-            r.unwrap_or_else(|err| OwnedFrame::SimpleError {
-                data: err.details().to_string(),
-                attributes: None,
-            })
+struct ExampleHandler {
+    databases: Databases,
+}
+
+impl ExampleHandler {
+    fn new(databases: usize) -> Self {
+        Self {
+            databases: Databases::new(databases),
         }
+    }
 
-        Err(err) => OwnedFrame::SimpleError {
-            data: err.details().to_string(),
-            attributes: None,
-        },
-    };
+    fn store(&self, session: &Session) -> Arc<ShardedStore> {
+        self.databases
+            .get(session.db())
+            .expect("SELECT only accepts existing databases")
+    }
+}
 
-    debug!("Reply: {:#?}", reply);
+impl CommandHandler for ExampleHandler {
+    fn get(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        get::handle(&self.store(session).as_ref(), request)
+    }
 
-    let mut buf: Vec<u8> = vec![0u8; reply.encode_len(false)];
-    encode::complete::encode(&mut buf, &reply, false).expect("Failed to encode");
-    buf
-}
+    fn set(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        set::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn select(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        select::handle(session, self.databases.len(), request)
+    }
+
+    fn expire(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn pexpire(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn expireat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn pexpireat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn ttl(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn pttl(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn persist(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn expiretime(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn r#move(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::handle(&self.databases, session, request)
+    }
+
+    fn swapdb(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::handle(&self.databases, session, request)
+    }
+
+    fn flushdb(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::handle(&self.databases, session, request)
+    }
+
+    fn flushall(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::handle(&self.databases, session, request)
+    }
+
+    fn dbsize(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::handle(&self.databases, session, request)
+    }
+
+    fn del(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn unlink(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn exists(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
 
-async fn handle_client(mut stream: TcpStream, addr: SocketAddr) {
-    info!("Incoming connection from: {}", addr);
-    let mut map: HashMap<String, String> = HashMap::new();
-    loop {
-        stream.readable().await.unwrap();
-        let mut buf = [0; 512];
-
-        match stream.read(&mut buf).await {
-            Ok(0) => {
-                warn!("Client closed channel");
-                break;
-            }
-            Ok(_) => {}
-            Err(e) => {
-                error!("Error reading from socket: {}", e);
-                break;
-            }
+    fn touch(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn r#type(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn rename(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn renamenx(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn copy(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        let Request::COPY { db: Some(db), .. } = request else {
+            return keys::handle(&mut self.store(session).as_ref(), request);
         };
 
-        let res_op = decode::complete::decode(&buf);
-        match res_op {
-            Ok(Some((frame, _size))) => {
-                let query = parse_owned_frame(frame);
-                info!("{:?}", query);
-                let reply = handle_command(query, &mut map);
-                stream
-                    .write_all(&reply)
-                    .await
-                    .expect("Failed to send reply");
-                stream.flush().await.unwrap()
-            }
-            Ok(None) => warn!("Received empty command"),
-            Err(e) => error!("Error: {}", e),
+        let destination = usize::try_from(*db)
+            .ok()
+            .and_then(|db| self.databases.get(db))
+            .ok_or_else(error_db_index_out_of_range)?;
+        if *db as usize == session.db() {
+            return keys::handle(&mut destination.as_ref(), request);
         }
+        keys::copy(
+            &self.store(session).as_ref(),
+            &mut destination.as_ref(),
+            request,
+        )
     }
-}
 
-fn setup_logging() {
-    // Set default RUST_LOG level if not already set
-    if env::var("RUST_LOG").is_err() {
-        let log_level = if cfg!(debug_assertions) {
-            "debug" // dev profile
-        } else {
-            "info" // release profile
-        };
-        env::set_var("RUST_LOG", log_level);
+    fn scan(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        scan::handle(&self.store(session).as_ref(), request)
+    }
+
+    fn keys(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        scan::handle(&self.store(session).as_ref(), request)
+    }
+
+    fn append(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn strlen(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn getrange(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn setrange(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn getdel(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn getex(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn getset(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn incr(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn decr(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn incrby(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn decrby(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn incrbyfloat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn mget(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn mset(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn msetnx(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hset(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hmset(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hsetnx(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hget(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hmget(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hdel(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hlen(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hstrlen(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hexists(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hkeys(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hvals(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hgetall(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hincrby(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hincrbyfloat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hscan(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hexpire(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hpexpire(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hexpireat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hpexpireat(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn httl(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hpttl(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hexpiretime(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hpexpiretime(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn hpersist(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lpush(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn rpush(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lpushx(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn rpushx(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn rpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn llen(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lrange(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lindex(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lset(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn linsert(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lrem(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn ltrim(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lmove(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn rpoplpush(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lpos(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn lmpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn blpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn brpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn blmove(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn blmpop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sadd(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn srem(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn smembers(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sismember(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn smismember(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn scard(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sinter(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sunion(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sdiff(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sinterstore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sunionstore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sdiffstore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sintercard(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn srandmember(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn spop(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn smove(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn sscan(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::handle(&mut self.store(session).as_ref(), request)
+    }
+
+    fn zadd(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zcard(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zscore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zincrby(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zrem(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zcount(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zrange(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
     }
 
-    // Initialize env_logger
-    env_logger::init();
+    fn zrank(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zrevrank(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zpopmin(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zpopmax(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zunionstore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn zinterstore(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::handle_with_protocol(
+            &mut self.store(session).as_ref(),
+            request,
+            session.protocol(),
+        )
+    }
+
+    fn active_expire(&self, limit: usize) -> usize {
+        self.databases.evict_expired(limit)
+    }
 }
 
 #[tokio::main]
@@ -118,10 +1051,21 @@ async fn main() -> std::io::Result<()> {
 
     setup_logging();
 
-    loop {
-        let (tcp_stream, socket_addr) = listener.accept().await?;
-        tokio::spawn(async move {
-            handle_client(tcp_stream, socket_addr).await;
-        });
+    let databases = std::env::var("REDIS_DATABASES")
+        .ok()
+        .and_then(|databases| databases.parse().ok())
+        .filter(|databases| *databases > 0)
+        .unwrap_or(DEFAULT_DATABASES);
+
+    let mut server = Server::new(ExampleHandler::new(databases));
+    /* Like `requirepass`, clients have to AUTH with this password if it is set */
+    if let Ok(password) = std::env::var("REDIS_PASSWORD") {
+        server = server.authenticator(StaticPassword::new(password));
     }
+
+    server
+        .run(listener, async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await
 }
@@ -0,0 +1,420 @@
+use crate::commands::auth::Password;
+use crate::commands::meta::{self, CommandInfo, CATEGORIES};
+use crate::commands::parse::Request;
+use crate::server::auth::{Authenticator, DEFAULT_USER};
+use crate::util::glob;
+use bytes::Bytes;
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Commands a rule of a [`User`] applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    All,
+    Category(String),
+    Command(String),
+}
+
+/// A user of the [`AccessControl`] list
+#[derive(Debug, Clone)]
+pub struct User {
+    name: String,
+    enabled: bool,
+    nopass: bool,
+    passwords: Vec<Password>,
+    /// `+` and `-` rules in the order they were added, later rules win
+    commands: Vec<(bool, Target)>,
+    keys: Vec<Bytes>,
+}
+
+impl User {
+    /// Create a disabled user that may not run any command, like `ACL SETUSER` does
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: false,
+            nopass: false,
+            passwords: Vec::new(),
+            commands: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Apply a single `ACL SETUSER` rule, e.g. `on`, `>password`, `~key:*` or `+@read`
+    pub fn apply(&mut self, rule: &str) -> Result<(), RedisProtocolError> {
+        match rule.to_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "allkeys" => self.keys = vec![Bytes::from_static(b"*")],
+            "resetkeys" => self.keys.clear(),
+            "allcommands" => self.commands = vec![(true, Target::All)],
+            "nocommands" => self.commands.clear(),
+            "reset" => *self = Self::new(std::mem::take(&mut self.name)),
+            _ => return self.apply_argument(rule),
+        }
+        Ok(())
+    }
+
+    /// Apply a rule with an argument, which may be case-sensitive
+    fn apply_argument(&mut self, rule: &str) -> Result<(), RedisProtocolError> {
+        let error = |reason: &str| {
+            RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                format!("ERR Error in ACL SETUSER modifier '{rule}': {reason}"),
+            )
+        };
+
+        let (prefix, argument) = rule.split_at(rule.chars().next().map_or(0, char::len_utf8));
+        match prefix {
+            ">" => {
+                self.nopass = false;
+                let password = Password::new(argument);
+                if !self.passwords.contains(&password) {
+                    self.passwords.push(password);
+                }
+            }
+            "<" => {
+                let password = Password::new(argument);
+                if !self.passwords.contains(&password) {
+                    return Err(error("no such password"));
+                }
+                self.passwords.retain(|p| *p != password);
+            }
+            "~" => self.keys.push(Bytes::copy_from_slice(argument.as_bytes())),
+            "+" | "-" => {
+                let argument = argument.to_lowercase();
+                let target = match argument.strip_prefix('@') {
+                    Some("all") => Target::All,
+                    Some(category) if CATEGORIES.contains(&category) => {
+                        Target::Category(category.to_string())
+                    }
+                    None if meta::COMMANDS.iter().any(|info| {
+                        info.name == argument || info.name.starts_with(&format!("{argument}|"))
+                    }) =>
+                    {
+                        Target::Command(argument)
+                    }
+                    _ => return Err(error("Unknown command or category name in ACL")),
+                };
+
+                let allow = prefix == "+";
+                if target == Target::All {
+                    self.commands.clear();
+                }
+                self.commands.push((allow, target));
+            }
+            _ => return Err(error("Syntax error")),
+        }
+        Ok(())
+    }
+
+    /// Whether the user may run the command described by `info`
+    pub fn can_run(&self, info: &CommandInfo) -> bool {
+        let mut allowed = false;
+        for (allow, target) in &self.commands {
+            let applies = match target {
+                Target::All => true,
+                Target::Category(category) => info.categories.contains(&category.as_str()),
+                Target::Command(name) => {
+                    info.name == name
+                        || info
+                            .name
+                            .strip_prefix(name.as_str())
+                            .is_some_and(|rest| rest.starts_with('|'))
+                }
+            };
+            if applies {
+                allowed = *allow;
+            }
+        }
+        allowed
+    }
+
+    /// Whether `key` matches one of the key patterns of the user. `*` matches every key, even
+    /// the empty one.
+    pub fn can_access(&self, key: &[u8]) -> bool {
+        self.keys
+            .iter()
+            .any(|pattern| pattern.as_ref() == b"*" || glob::matches(pattern, key))
+    }
+
+    /// Check `password` for this user
+    pub fn check_password(&self, password: &Password) -> bool {
+        self.enabled && (self.nopass || self.passwords.contains(password))
+    }
+
+    /// `on`/`off` and `nopass`, as reported by `ACL GETUSER`
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    /// Command rules in `ACL SETUSER` syntax
+    pub fn command_rules(&self) -> String {
+        if self.commands.is_empty() {
+            return "-@all".into();
+        }
+
+        self.commands
+            .iter()
+            .map(|(allow, target)| {
+                let sign = if *allow { '+' } else { '-' };
+                match target {
+                    Target::All => format!("{sign}@all"),
+                    Target::Category(category) => format!("{sign}@{category}"),
+                    Target::Command(name) => format!("{sign}{name}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Key patterns in `ACL SETUSER` syntax
+    pub fn key_rules(&self) -> String {
+        self.keys
+            .iter()
+            .map(|pattern| format!("~{}", String::from_utf8_lossy(pattern)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Describe the user like `ACL LIST` does. Passwords are never included.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("user {}", self.name)];
+        parts.extend(self.flags().into_iter().map(String::from));
+        let keys = self.key_rules();
+        if !keys.is_empty() {
+            parts.push(keys);
+        }
+        parts.push(self.command_rules());
+        parts.join(" ")
+    }
+}
+
+/// Users with their passwords, permitted commands and permitted keys, like Redis' ACL.
+///
+/// Set it with [`Dispatcher::with_acl`](crate::server::dispatcher::Dispatcher::with_acl) or
+/// [`Server::acl`](crate::server::runner::Server::acl). The dispatcher then authenticates
+/// sessions against these users and checks every request before it reaches the
+/// [`CommandHandler`](crate::server::handler::CommandHandler).
+#[derive(Debug)]
+pub struct AccessControl {
+    users: RwLock<HashMap<String, User>>,
+}
+
+impl AccessControl {
+    /// Create a list containing only the [`DEFAULT_USER`], which may run every command on every
+    /// key without a password. Remove `nopass` from it to require authentication.
+    pub fn new() -> Self {
+        let mut default = User::new(DEFAULT_USER);
+        for rule in ["on", "nopass", "allkeys", "allcommands"] {
+            default.apply(rule).expect("Rule is valid");
+        }
+
+        Self {
+            users: RwLock::new(HashMap::from([(DEFAULT_USER.to_string(), default)])),
+        }
+    }
+
+    /// Create `username` if needed and apply `rules` to it.
+    ///
+    /// Either all rules are applied or, if one of them is invalid, none.
+    pub fn set_user(&self, username: &str, rules: &[String]) -> Result<(), RedisProtocolError> {
+        let mut users = self.users.write().expect("ACL lock poisoned");
+        let mut user = users
+            .get(username)
+            .cloned()
+            .unwrap_or_else(|| User::new(username));
+
+        for rule in rules {
+            user.apply(rule)?;
+        }
+
+        users.insert(username.to_string(), user);
+        Ok(())
+    }
+
+    /// Return a copy of `username`
+    pub fn user(&self, username: &str) -> Option<User> {
+        self.users
+            .read()
+            .expect("ACL lock poisoned")
+            .get(username)
+            .cloned()
+    }
+
+    /// Remove `username`.
+    ///
+    /// # Returns
+    /// `true` if the user existed
+    pub fn del_user(&self, username: &str) -> Result<bool, RedisProtocolError> {
+        if username == DEFAULT_USER {
+            return Err(RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                "ERR The 'default' user cannot be removed",
+            ));
+        }
+
+        Ok(self
+            .users
+            .write()
+            .expect("ACL lock poisoned")
+            .remove(username)
+            .is_some())
+    }
+
+    /// Return all users, sorted by name
+    pub fn users(&self) -> Vec<User> {
+        let mut users: Vec<User> = self
+            .users
+            .read()
+            .expect("ACL lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        users.sort_by(|a, b| a.name.cmp(&b.name));
+        users
+    }
+
+    /// Whether sessions are authenticated as [`DEFAULT_USER`] without sending `AUTH`, which is
+    /// the case while the default user is enabled and has `nopass` set
+    pub fn allows_anonymous(&self) -> bool {
+        self.user(DEFAULT_USER)
+            .is_some_and(|user| user.enabled && user.nopass)
+    }
+
+    /// Check whether `username` may run `request` on all keys it accesses.
+    pub fn check(&self, username: &str, request: &Request) -> Result<(), RedisProtocolError> {
+        let users = self.users.read().expect("ACL lock poisoned");
+        let info = request.info();
+
+        match users.get(username) {
+            Some(user) if user.enabled && user.can_run(info) => {}
+            _ => {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    format!(
+                        "NOPERM User {username} has no permissions to run the '{}' command",
+                        info.name
+                    ),
+                ))
+            }
+        }
+
+        let user = &users[username];
+        if request.keys().into_iter().all(|key| user.can_access(key)) {
+            Ok(())
+        } else {
+            Err(RedisProtocolError::new(
+                RedisProtocolErrorKind::Parse,
+                "NOPERM No permissions to access a key",
+            ))
+        }
+    }
+}
+
+impl Default for AccessControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Authenticator for AccessControl {
+    fn authenticate(&self, username: &str, password: &Password) -> bool {
+        self.user(username)
+            .is_some_and(|user| user.check_password(password))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::parse::parse;
+
+    fn request(args: &[&'static str]) -> Request {
+        parse(
+            args.iter()
+                .map(|arg| Bytes::from_static(arg.as_bytes()))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn rules(rules: &[&str]) -> Vec<String> {
+        rules.iter().map(|rule| rule.to_string()).collect()
+    }
+
+    #[test]
+    fn categories_commands_and_keys() {
+        let acl = AccessControl::new();
+        acl.set_user(
+            "reader",
+            &rules(&["on", ">pw", "~app:*", "+@read", "-ttl", "+set"]),
+        )
+        .unwrap();
+
+        assert!(acl.check("reader", &request(&["GET", "app:1"])).is_ok());
+        assert!(acl
+            .check("reader", &request(&["SET", "app:1", "v"]))
+            .is_ok());
+        assert!(acl.check("reader", &request(&["GET", "other"])).is_err());
+        assert!(acl.check("reader", &request(&["TTL", "app:1"])).is_err());
+        assert!(acl.check("reader", &request(&["PTTL", "app:1"])).is_ok());
+        assert!(acl
+            .check("reader", &request(&["EXPIRE", "app:1", "1"]))
+            .is_err());
+        assert!(acl.check("nobody", &request(&["GET", "app:1"])).is_err());
+
+        assert!(acl.authenticate("reader", &Password::new("pw")));
+        assert!(!acl.authenticate("reader", &Password::new("wrong")));
+        assert_eq!(
+            acl.user("reader").unwrap().describe(),
+            "user reader on ~app:* +@read -ttl +set"
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_not_applied() {
+        let acl = AccessControl::new();
+
+        assert!(acl
+            .set_user("user", &rules(&["on", "+@nosuchcategory"]))
+            .is_err());
+        assert!(acl.user("user").is_none());
+        assert!(acl.set_user("user", &rules(&["+nosuchcommand"])).is_err());
+        assert!(acl.set_user("user", &rules(&["sometimes"])).is_err());
+        assert!(acl.del_user(DEFAULT_USER).is_err());
+    }
+
+    #[test]
+    fn subcommands() {
+        let acl = AccessControl::new();
+        acl.set_user("user", &rules(&["on", "nopass", "+acl", "-acl|setuser"]))
+            .unwrap();
+
+        assert!(acl.check("user", &request(&["ACL", "WHOAMI"])).is_ok());
+        assert!(acl
+            .check("user", &request(&["ACL", "SETUSER", "other"]))
+            .is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::commands::auth::Password;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Name of the user clients authenticate as if they send `AUTH password` without a username
+pub const DEFAULT_USER: &str = "default";
+
+/// Decide whether a client may authenticate as a user.
+///
+/// Set one with [`Dispatcher::with_authenticator`](crate::server::dispatcher::Dispatcher::with_authenticator)
+/// or [`Server::authenticator`](crate::server::runner::Server::authenticator) to require
+/// authentication. Closures taking a username and a password implement this trait as well:
+///
+/// ```
+/// use redis_protocol_bridge::server::auth::Authenticator;
+///
+/// fn check(authenticator: impl Authenticator) {}
+///
+/// check(|username: &str, password: &str| username == "admin" && password == "hunter2");
+/// ```
+pub trait Authenticator: Send + Sync {
+    /// Return `true` if `password` is valid for `username`
+    fn authenticate(&self, username: &str, password: &Password) -> bool;
+}
+
+/// Accept a single password for the [`DEFAULT_USER`], like `requirepass` in Redis
+#[derive(Clone)]
+pub struct StaticPassword {
+    password: Password,
+}
+
+impl StaticPassword {
+    pub fn new(password: impl Into<String>) -> Self {
+        Self {
+            password: Password::new(password),
+        }
+    }
+}
+
+impl Authenticator for StaticPassword {
+    fn authenticate(&self, username: &str, password: &Password) -> bool {
+        username == DEFAULT_USER && constant_time_eq(&self.password, password)
+    }
+}
+
+/// Accept the users in a table mapping usernames to passwords
+#[derive(Clone, Default)]
+pub struct UserTable {
+    users: HashMap<String, Password>,
+}
+
+impl UserTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `username` or replace its password
+    pub fn insert(&mut self, username: impl Into<String>, password: impl Into<String>) {
+        self.users.insert(username.into(), Password::new(password));
+    }
+
+    /// Remove `username`
+    pub fn remove(&mut self, username: &str) -> bool {
+        self.users.remove(username).is_some()
+    }
+}
+
+impl<U: Into<String>, P: Into<String>> FromIterator<(U, P)> for UserTable {
+    fn from_iter<T: IntoIterator<Item = (U, P)>>(iter: T) -> Self {
+        let mut table = Self::new();
+        for (username, password) in iter {
+            table.insert(username, password);
+        }
+        table
+    }
+}
+
+impl Authenticator for UserTable {
+    fn authenticate(&self, username: &str, password: &Password) -> bool {
+        self.users
+            .get(username)
+            .is_some_and(|expected| constant_time_eq(expected, password))
+    }
+}
+
+impl<F> Authenticator for F
+where
+    F: Fn(&str, &str) -> bool + Send + Sync,
+{
+    fn authenticate(&self, username: &str, password: &Password) -> bool {
+        self(username, password.as_str())
+    }
+}
+
+impl<A: Authenticator + ?Sized> Authenticator for Arc<A> {
+    fn authenticate(&self, username: &str, password: &Password) -> bool {
+        (**self).authenticate(username, password)
+    }
+}
+
+/// Compare passwords without leaking the length of the common prefix through timing
+fn constant_time_eq(a: &Password, b: &Password) -> bool {
+    let (a, b) = (a.as_str().as_bytes(), b.as_str().as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authenticators() {
+        let password = Password::new("secret");
+        let wrong = Password::new("secreT");
+
+        let single = StaticPassword::new("secret");
+        assert!(single.authenticate(DEFAULT_USER, &password));
+        assert!(!single.authenticate(DEFAULT_USER, &wrong));
+        assert!(!single.authenticate("admin", &password));
+
+        let table: UserTable = [("admin", "secret")].into_iter().collect();
+        assert!(table.authenticate("admin", &password));
+        assert!(!table.authenticate(DEFAULT_USER, &password));
+
+        let callback = |username: &str, _: &str| username == "anyone";
+        assert!(callback.authenticate("anyone", &wrong));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::commands::parse::Request;
+use bytes::Bytes;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Key in one of the numbered databases
+type DbKey = (usize, Bytes);
+
+/// Connections blocked by `BLPOP`, `BRPOP`, `BLMOVE` and `BLMPOP`, queued per key.
+///
+/// The server runner adds a connection to the queue of every key its command waits for and
+/// retries the command whenever it is woken up. After every write, the runner calls
+/// [`BlockingRegistry::wake`] for the keys written, which wakes only the connection that has
+/// waited longest. Once that connection leaves the queue, whether it was served, timed out or
+/// disconnected, the next one is woken in turn. This way waiters are served first come, first
+/// served, like in Redis.
+#[derive(Debug, Default)]
+pub struct BlockingRegistry {
+    queues: Mutex<HashMap<DbKey, VecDeque<Arc<Notify>>>>,
+}
+
+/// Place of a connection in the queues of [`BlockingRegistry`], left when dropped
+#[derive(Debug)]
+pub struct Blocked {
+    registry: Arc<BlockingRegistry>,
+    keys: Vec<DbKey>,
+    notify: Arc<Notify>,
+}
+
+impl BlockingRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a connection on `keys` of database `db`, behind every connection already waiting.
+    pub fn block(self: &Arc<Self>, db: usize, keys: &[&Bytes]) -> Blocked {
+        let notify = Arc::new(Notify::new());
+        let keys: Vec<DbKey> = keys.iter().map(|key| (db, (*key).clone())).collect();
+
+        let mut queues = self.queues.lock().unwrap();
+        for key in &keys {
+            queues
+                .entry(key.clone())
+                .or_default()
+                .push_back(notify.clone());
+        }
+
+        Blocked {
+            registry: self.clone(),
+            keys,
+            notify,
+        }
+    }
+
+    /// Wake the connection that waits longest on `key` of database `db`, if any
+    pub fn wake(&self, db: usize, key: &Bytes) {
+        let queues = self.queues.lock().unwrap();
+        if let Some(first) = queues.get(&(db, key.clone())).and_then(VecDeque::front) {
+            first.notify_one();
+        }
+    }
+
+    /// Wake the connection that waits longest on every key of database `db`, e.g. after the
+    /// contents of the database were replaced by `SWAPDB`
+    pub fn wake_all(&self, db: usize) {
+        let queues = self.queues.lock().unwrap();
+        for ((index, _), queue) in queues.iter() {
+            if let Some(first) = queue.front().filter(|_| *index == db) {
+                first.notify_one();
+            }
+        }
+    }
+
+    /// Number of connections waiting on `key` of database `db`
+    pub fn waiting(&self, db: usize, key: &Bytes) -> usize {
+        self.queues
+            .lock()
+            .unwrap()
+            .get(&(db, key.clone()))
+            .map_or(0, VecDeque::len)
+    }
+}
+
+impl Blocked {
+    /// Wait until [`BlockingRegistry::wake`] selects this connection
+    pub async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
+
+impl Drop for Blocked {
+    /// Leave every queue and wake the next connection, which may be served now instead
+    fn drop(&mut self) {
+        let mut queues = self.registry.queues.lock().unwrap();
+        for key in &self.keys {
+            let Some(queue) = queues.get_mut(key) else {
+                continue;
+            };
+            queue.retain(|notify| !Arc::ptr_eq(notify, &self.notify));
+            match queue.front() {
+                Some(next) => next.notify_one(),
+                None => {
+                    queues.remove(key);
+                }
+            }
+        }
+    }
+}
+
+/// Keys a blocking command waits for and how long it waits, `None` for every other command.
+///
+/// A timeout of zero blocks indefinitely.
+pub fn blocked_on(request: &Request) -> Option<(Vec<&Bytes>, Duration)> {
+    match request {
+        Request::BLPOP { keys, timeout }
+        | Request::BRPOP { keys, timeout }
+        | Request::BLMPOP { keys, timeout, .. } => Some((keys.iter().collect(), *timeout)),
+        Request::BLMOVE {
+            source, timeout, ..
+        } => Some((vec![source], *timeout)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn wake_in_order() {
+        let registry = Arc::new(BlockingRegistry::new());
+        let key = Bytes::from_static(b"queue");
+
+        let first = registry.block(0, &[&key]);
+        let second = registry.block(0, &[&key]);
+        assert_eq!(registry.waiting(0, &key), 2);
+
+        /* Another database does not wake anyone */
+        registry.wake(1, &key);
+        registry.wake(0, &key);
+        first.notified().await;
+        let pending = tokio::time::timeout(Duration::from_millis(10), second.notified());
+        assert!(pending.await.is_err());
+
+        /* Leaving passes the turn on */
+        drop(first);
+        second.notified().await;
+        drop(second);
+        assert_eq!(registry.waiting(0, &key), 0);
+    }
+
+    #[tokio::test]
+    async fn wake_all_keys_of_a_database() {
+        let registry = Arc::new(BlockingRegistry::new());
+        let (a, b) = (Bytes::from_static(b"a"), Bytes::from_static(b"b"));
+
+        let first = registry.block(1, &[&a]);
+        let second = registry.block(1, &[&b]);
+        let other = registry.block(0, &[&a]);
+
+        registry.wake_all(1);
+        first.notified().await;
+        second.notified().await;
+        let pending = tokio::time::timeout(Duration::from_millis(10), other.notified());
+        assert!(pending.await.is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::commands::client::PauseMode;
+use crate::commands::parse::Request;
+use crate::server::session::Session;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Notify};
+
+/// A registered connection
+#[derive(Debug)]
+struct Registered {
+    /// Copy of the [`Session`], updated after every command
+    session: Mutex<Session>,
+    /// Set to `true` to close the connection
+    kill: watch::Sender<bool>,
+}
+
+/// Deadlines set by `CLIENT PAUSE`
+#[derive(Debug, Default)]
+struct Pause {
+    all: Option<Instant>,
+    write: Option<Instant>,
+}
+
+/// Every open connection of a server, used by `CLIENT LIST` and `CLIENT KILL`.
+///
+/// The server runner registers each accepted connection and removes it once closed. The
+/// [`Dispatcher`](crate::server::dispatcher::Dispatcher) keeps the registered copy of each
+/// [`Session`] up to date. The registry also tracks whether clients are paused by
+/// `CLIENT PAUSE`.
+#[derive(Debug, Default)]
+pub struct ClientRegistry {
+    clients: RwLock<HashMap<u64, Registered>>,
+    pause: Mutex<Pause>,
+    unpaused: Notify,
+}
+
+impl ClientRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the connection of `session`.
+    ///
+    /// # Returns
+    /// A receiver whose value changes to `true` once the connection is killed
+    pub fn register(&self, session: &Session) -> watch::Receiver<bool> {
+        let (kill, killed) = watch::channel(false);
+        let registered = Registered {
+            session: Mutex::new(session.clone()),
+            kill,
+        };
+        self.clients
+            .write()
+            .unwrap()
+            .insert(session.id(), registered);
+        killed
+    }
+
+    /// Remove the connection with id `id`
+    pub fn unregister(&self, id: u64) {
+        self.clients.write().unwrap().remove(&id);
+    }
+
+    /// Replace the registered copy of `session`, if its connection is registered
+    pub fn update(&self, session: &Session) {
+        if let Some(registered) = self.clients.read().unwrap().get(&session.id()) {
+            *registered.session.lock().unwrap() = session.clone();
+        }
+    }
+
+    /// Copies of all registered sessions, ordered by id
+    pub fn sessions(&self) -> Vec<Session> {
+        let mut sessions: Vec<Session> = self
+            .clients
+            .read()
+            .unwrap()
+            .values()
+            .map(|registered| registered.session.lock().unwrap().clone())
+            .collect();
+        sessions.sort_by_key(Session::id);
+        sessions
+    }
+
+    /// Close the connection with id `id`
+    ///
+    /// # Returns
+    /// Whether such a connection is registered
+    pub fn kill(&self, id: u64) -> bool {
+        match self.clients.read().unwrap().get(&id) {
+            Some(registered) => {
+                registered.kill.send_replace(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hold back commands for `timeout`. A running pause is only ever extended, never shortened.
+    pub fn pause(&self, timeout: Duration, mode: PauseMode) {
+        let until = Instant::now() + timeout;
+        let mut pause = self.pause.lock().unwrap();
+        let deadline = match mode {
+            PauseMode::All => &mut pause.all,
+            PauseMode::Write => &mut pause.write,
+        };
+        *deadline = Some(deadline.map_or(until, |deadline| deadline.max(until)));
+    }
+
+    /// End any pause and resume held back commands
+    pub fn unpause(&self) {
+        *self.pause.lock().unwrap() = Pause::default();
+        self.unpaused.notify_waiters();
+    }
+
+    /// Point in time `request` may run at, if it is currently paused
+    pub fn paused_until(&self, request: &Request) -> Option<Instant> {
+        let now = Instant::now();
+        let pause = self.pause.lock().unwrap();
+        let write = request.info().categories.contains(&"write");
+
+        [pause.all, pause.write.filter(|_| write)]
+            .into_iter()
+            .flatten()
+            .filter(|deadline| *deadline > now)
+            .max()
+    }
+
+    /// Wait until `request` is no longer paused
+    pub async fn wait_unpaused(&self, request: &Request) {
+        loop {
+            /* Register for notifications first, so an unpause in between is not missed */
+            let unpaused = self.unpaused.notified();
+            let Some(deadline) = self.paused_until(request) else {
+                return;
+            };
+
+            tokio::select! {
+                _ = unpaused => {}
+                _ = tokio::time::sleep_until(deadline.into()) => {}
+            }
+        }
+    }
+}
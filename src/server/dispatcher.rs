@@ -0,0 +1,525 @@
+use crate::commands::auth::Password;
+use crate::commands::parse::Request;
+use crate::commands::{acl, client, hello, parse};
+use crate::server::acl::AccessControl;
+use crate::server::auth::{Authenticator, DEFAULT_USER};
+use crate::server::clients::ClientRegistry;
+use crate::server::handler::CommandHandler;
+use crate::server::session::Session;
+use crate::util::convert::AsFrame;
+use crate::util::errors::{error_noauth, error_wrongpass};
+use bytes::Bytes;
+use log::{debug, warn};
+use redis_protocol::error::{RedisProtocolError, RedisProtocolErrorKind};
+use redis_protocol::resp3::types::OwnedFrame;
+use std::sync::Arc;
+
+/// Parse incoming queries and pass them to the matching method of a [`CommandHandler`].
+///
+/// If an [`Authenticator`] is set, sessions have to authenticate with `AUTH` or `HELLO AUTH`
+/// first. Until then, every other command is rejected with `NOAUTH`. Authentication is handled
+/// by the dispatcher itself and can't be overridden by the [`CommandHandler`].
+///
+/// With an [`AccessControl`] list, sessions authenticate as one of its users and every request
+/// is checked against the commands and keys the user may access before it is dispatched. The
+/// `ACL` command is handled by the dispatcher as well.
+///
+/// `CLIENT` is answered using the [`ClientRegistry`] of the dispatcher, which is kept up to date
+/// with the [`Session`] of every dispatched request.
+pub struct Dispatcher<H: CommandHandler> {
+    handler: H,
+    authenticator: Option<Box<dyn Authenticator>>,
+    acl: Option<Arc<AccessControl>>,
+    clients: ClientRegistry,
+}
+
+impl<H: CommandHandler> Dispatcher<H> {
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            authenticator: None,
+            acl: None,
+            clients: ClientRegistry::new(),
+        }
+    }
+
+    /// Create a dispatcher that requires sessions to authenticate using `authenticator`
+    pub fn with_authenticator(handler: H, authenticator: impl Authenticator + 'static) -> Self {
+        let mut dispatcher = Self::new(handler);
+        dispatcher.set_authenticator(authenticator);
+        dispatcher
+    }
+
+    /// Require sessions to authenticate using `authenticator`
+    pub fn set_authenticator(&mut self, authenticator: impl Authenticator + 'static) {
+        self.authenticator = Some(Box::new(authenticator));
+    }
+
+    /// Create a dispatcher that enforces the permissions of `acl`
+    pub fn with_acl(handler: H, acl: Arc<AccessControl>) -> Self {
+        let mut dispatcher = Self::new(handler);
+        dispatcher.set_acl(acl);
+        dispatcher
+    }
+
+    /// Authenticate sessions against the users of `acl` and enforce their permissions. This
+    /// replaces any [`Authenticator`] set before.
+    pub fn set_acl(&mut self, acl: Arc<AccessControl>) {
+        self.set_authenticator(acl.clone());
+        self.acl = Some(acl);
+    }
+
+    /// Return the [`AccessControl`] list, if any
+    pub fn acl(&self) -> Option<&Arc<AccessControl>> {
+        self.acl.as_ref()
+    }
+
+    /// Return the registry of connections
+    pub fn clients(&self) -> &ClientRegistry {
+        &self.clients
+    }
+
+    /// Return the wrapped [`CommandHandler`]
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Parse `query` and dispatch the resulting [`Request`].
+    ///
+    /// # Returns
+    /// The reply to send to the client. Parse and handler errors are turned into
+    /// [`OwnedFrame::SimpleError`]s.
+    pub fn dispatch(&self, session: &mut Session, query: Vec<Bytes>) -> OwnedFrame {
+        let reply =
+            parse::parse(query).and_then(|request| self.dispatch_request(session, &request));
+
+        reply.unwrap_or_else(|err| error_frame(&err))
+    }
+
+    /// Call the [`CommandHandler`] method responsible for `request`.
+    ///
+    /// After a `QUIT` the session is marked as closing, so the connection is closed once
+    /// the reply has been sent. A successful `HELLO` switches the session to the requested
+    /// protocol, which already applies to the reply of `HELLO` itself, and sets the connection
+    /// name if `SETNAME` was given.
+    pub fn dispatch_request(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        debug!("{:?}", request);
+        session.record(request.name());
+        let reply = self.dispatch_checked(session, request);
+        self.clients.update(session);
+        reply
+    }
+
+    fn dispatch_checked(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        if let Request::QUIT = request {
+            session.close();
+        }
+
+        /* Like Redis, check the version before authenticating, so a HELLO that fails never
+         * leaves the session authenticated */
+        if let Request::HELLO {
+            version,
+            clientname,
+            ..
+        } = request
+        {
+            if version
+                .as_deref()
+                .is_some_and(|v| hello::protocol(v).is_none())
+            {
+                return Err(hello::error_noproto());
+            }
+            if let Some(name) = clientname {
+                client::validate_name(name)?;
+            }
+        }
+
+        match request {
+            Request::HELLO {
+                auth: Some((username, password)),
+                ..
+            } => self.authenticate(session, username, password)?,
+            Request::HELLO { .. } if self.rejects(session) => {
+                return Err(RedisProtocolError::new(
+                    RedisProtocolErrorKind::Parse,
+                    "NOAUTH HELLO must be called with the client already authenticated, \
+                     otherwise the HELLO <proto> AUTH <user> <pass> option can be used to \
+                     authenticate the client and select the RESP protocol version at the same time",
+                ))
+            }
+            Request::AUTH { .. } | Request::QUIT => {}
+            _ if self.rejects(session) => return Err(error_noauth()),
+            _ => {
+                if let Some(acl) = &self.acl {
+                    acl.check(session.user().unwrap_or(DEFAULT_USER), request)?;
+                }
+            }
+        }
+
+        let reply = match request {
+            Request::HELLO { .. } => self.handler.hello(session, request),
+            Request::ACL(_) => acl::handle(self.acl.as_deref(), session, request),
+            Request::CLIENT(_) => client::handle(&self.clients, session, request),
+            Request::AUTH { username, password } => {
+                if self.authenticator.is_none() {
+                    return Err(RedisProtocolError::new(
+                        RedisProtocolErrorKind::Parse,
+                        "ERR AUTH <password> called without any password configured for the \
+                         default user. Are you sure your configuration is correct?",
+                    ));
+                }
+                let username = username.as_deref().unwrap_or(DEFAULT_USER);
+                self.authenticate(session, username, password)
+                    .map(|_| "OK".as_frame())
+            }
+            Request::GET { .. } => self.handler.get(session, request),
+            Request::SET { .. } => self.handler.set(session, request),
+            Request::COMMAND(_) => self.handler.command(session, request),
+            Request::INFO(_) => self.handler.info(session, request),
+            Request::PING(_) => self.handler.ping(session, request),
+            Request::SELECT(_) => self.handler.select(session, request),
+            Request::QUIT => self.handler.quit(session, request),
+            Request::CLUSTER(_) => self.handler.cluster(session, request),
+            Request::CONFIG(_) => self.handler.config(session, request),
+            Request::EXPIRE { .. } => self.handler.expire(session, request),
+            Request::PEXPIRE { .. } => self.handler.pexpire(session, request),
+            Request::EXPIREAT { .. } => self.handler.expireat(session, request),
+            Request::PEXPIREAT { .. } => self.handler.pexpireat(session, request),
+            Request::TTL { .. } => self.handler.ttl(session, request),
+            Request::PTTL { .. } => self.handler.pttl(session, request),
+            Request::PERSIST { .. } => self.handler.persist(session, request),
+            Request::EXPIRETIME { .. } => self.handler.expiretime(session, request),
+            Request::MOVE { .. } => self.handler.r#move(session, request),
+            Request::SWAPDB { .. } => self.handler.swapdb(session, request),
+            Request::FLUSHDB(_) => self.handler.flushdb(session, request),
+            Request::FLUSHALL(_) => self.handler.flushall(session, request),
+            Request::DBSIZE => self.handler.dbsize(session, request),
+            Request::DEL { .. } => self.handler.del(session, request),
+            Request::UNLINK { .. } => self.handler.unlink(session, request),
+            Request::EXISTS { .. } => self.handler.exists(session, request),
+            Request::TOUCH { .. } => self.handler.touch(session, request),
+            Request::TYPE { .. } => self.handler.r#type(session, request),
+            Request::RENAME { .. } => self.handler.rename(session, request),
+            Request::RENAMENX { .. } => self.handler.renamenx(session, request),
+            Request::COPY { .. } => self.handler.copy(session, request),
+            Request::SCAN { .. } => self.handler.scan(session, request),
+            Request::KEYS { .. } => self.handler.keys(session, request),
+            Request::APPEND { .. } => self.handler.append(session, request),
+            Request::STRLEN { .. } => self.handler.strlen(session, request),
+            Request::GETRANGE { .. } => self.handler.getrange(session, request),
+            Request::SETRANGE { .. } => self.handler.setrange(session, request),
+            Request::GETDEL { .. } => self.handler.getdel(session, request),
+            Request::GETEX { .. } => self.handler.getex(session, request),
+            Request::GETSET { .. } => self.handler.getset(session, request),
+            Request::INCR { .. } => self.handler.incr(session, request),
+            Request::DECR { .. } => self.handler.decr(session, request),
+            Request::INCRBY { .. } => self.handler.incrby(session, request),
+            Request::DECRBY { .. } => self.handler.decrby(session, request),
+            Request::INCRBYFLOAT { .. } => self.handler.incrbyfloat(session, request),
+            Request::MGET { .. } => self.handler.mget(session, request),
+            Request::MSET { .. } => self.handler.mset(session, request),
+            Request::MSETNX { .. } => self.handler.msetnx(session, request),
+            Request::HSET { .. } => self.handler.hset(session, request),
+            Request::HMSET { .. } => self.handler.hmset(session, request),
+            Request::HSETNX { .. } => self.handler.hsetnx(session, request),
+            Request::HGET { .. } => self.handler.hget(session, request),
+            Request::HMGET { .. } => self.handler.hmget(session, request),
+            Request::HDEL { .. } => self.handler.hdel(session, request),
+            Request::HLEN { .. } => self.handler.hlen(session, request),
+            Request::HSTRLEN { .. } => self.handler.hstrlen(session, request),
+            Request::HEXISTS { .. } => self.handler.hexists(session, request),
+            Request::HKEYS { .. } => self.handler.hkeys(session, request),
+            Request::HVALS { .. } => self.handler.hvals(session, request),
+            Request::HGETALL { .. } => self.handler.hgetall(session, request),
+            Request::HINCRBY { .. } => self.handler.hincrby(session, request),
+            Request::HINCRBYFLOAT { .. } => self.handler.hincrbyfloat(session, request),
+            Request::HSCAN { .. } => self.handler.hscan(session, request),
+            Request::HEXPIRE { .. } => self.handler.hexpire(session, request),
+            Request::HPEXPIRE { .. } => self.handler.hpexpire(session, request),
+            Request::HEXPIREAT { .. } => self.handler.hexpireat(session, request),
+            Request::HPEXPIREAT { .. } => self.handler.hpexpireat(session, request),
+            Request::HTTL { .. } => self.handler.httl(session, request),
+            Request::HPTTL { .. } => self.handler.hpttl(session, request),
+            Request::HEXPIRETIME { .. } => self.handler.hexpiretime(session, request),
+            Request::HPEXPIRETIME { .. } => self.handler.hpexpiretime(session, request),
+            Request::HPERSIST { .. } => self.handler.hpersist(session, request),
+            Request::LPUSH { .. } => self.handler.lpush(session, request),
+            Request::RPUSH { .. } => self.handler.rpush(session, request),
+            Request::LPUSHX { .. } => self.handler.lpushx(session, request),
+            Request::RPUSHX { .. } => self.handler.rpushx(session, request),
+            Request::LPOP { .. } => self.handler.lpop(session, request),
+            Request::RPOP { .. } => self.handler.rpop(session, request),
+            Request::LLEN { .. } => self.handler.llen(session, request),
+            Request::LRANGE { .. } => self.handler.lrange(session, request),
+            Request::LINDEX { .. } => self.handler.lindex(session, request),
+            Request::LSET { .. } => self.handler.lset(session, request),
+            Request::LINSERT { .. } => self.handler.linsert(session, request),
+            Request::LREM { .. } => self.handler.lrem(session, request),
+            Request::LTRIM { .. } => self.handler.ltrim(session, request),
+            Request::LMOVE { .. } => self.handler.lmove(session, request),
+            Request::RPOPLPUSH { .. } => self.handler.rpoplpush(session, request),
+            Request::LPOS { .. } => self.handler.lpos(session, request),
+            Request::LMPOP { .. } => self.handler.lmpop(session, request),
+            Request::BLPOP { .. } => self.handler.blpop(session, request),
+            Request::BRPOP { .. } => self.handler.brpop(session, request),
+            Request::BLMOVE { .. } => self.handler.blmove(session, request),
+            Request::BLMPOP { .. } => self.handler.blmpop(session, request),
+            Request::SADD { .. } => self.handler.sadd(session, request),
+            Request::SREM { .. } => self.handler.srem(session, request),
+            Request::SMEMBERS { .. } => self.handler.smembers(session, request),
+            Request::SISMEMBER { .. } => self.handler.sismember(session, request),
+            Request::SMISMEMBER { .. } => self.handler.smismember(session, request),
+            Request::SCARD { .. } => self.handler.scard(session, request),
+            Request::SINTER { .. } => self.handler.sinter(session, request),
+            Request::SUNION { .. } => self.handler.sunion(session, request),
+            Request::SDIFF { .. } => self.handler.sdiff(session, request),
+            Request::SINTERSTORE { .. } => self.handler.sinterstore(session, request),
+            Request::SUNIONSTORE { .. } => self.handler.sunionstore(session, request),
+            Request::SDIFFSTORE { .. } => self.handler.sdiffstore(session, request),
+            Request::SINTERCARD { .. } => self.handler.sintercard(session, request),
+            Request::SRANDMEMBER { .. } => self.handler.srandmember(session, request),
+            Request::SPOP { .. } => self.handler.spop(session, request),
+            Request::SMOVE { .. } => self.handler.smove(session, request),
+            Request::SSCAN { .. } => self.handler.sscan(session, request),
+            Request::ZADD { .. } => self.handler.zadd(session, request),
+            Request::ZCARD { .. } => self.handler.zcard(session, request),
+            Request::ZSCORE { .. } => self.handler.zscore(session, request),
+            Request::ZINCRBY { .. } => self.handler.zincrby(session, request),
+            Request::ZREM { .. } => self.handler.zrem(session, request),
+            Request::ZCOUNT { .. } => self.handler.zcount(session, request),
+            Request::ZRANGE { .. } => self.handler.zrange(session, request),
+            Request::ZRANK { .. } => self.handler.zrank(session, request),
+            Request::ZREVRANK { .. } => self.handler.zrevrank(session, request),
+            Request::ZPOPMIN { .. } => self.handler.zpopmin(session, request),
+            Request::ZPOPMAX { .. } => self.handler.zpopmax(session, request),
+            Request::ZUNIONSTORE { .. } => self.handler.zunionstore(session, request),
+            Request::ZINTERSTORE { .. } => self.handler.zinterstore(session, request),
+        };
+
+        if let (
+            Request::HELLO {
+                version,
+                clientname,
+                ..
+            },
+            Ok(_),
+        ) = (request, &reply)
+        {
+            if let Some(protocol) = version.as_deref().and_then(hello::protocol) {
+                session.set_protocol(protocol);
+            }
+            if let Some(name) = clientname {
+                session.set_name(Some(name.clone()).filter(|name| !name.is_empty()));
+            }
+        }
+
+        reply
+    }
+}
+
+impl<H: CommandHandler> Dispatcher<H> {
+    /// Whether `session` has to authenticate before sending commands
+    fn rejects(&self, session: &Session) -> bool {
+        self.authenticator.is_some()
+            && !session.is_authenticated()
+            && !self.acl.as_ref().is_some_and(|acl| acl.allows_anonymous())
+    }
+
+    /// Authenticate `session` as `username`. Without an [`Authenticator`] every user is accepted.
+    fn authenticate(
+        &self,
+        session: &mut Session,
+        username: &str,
+        password: &Password,
+    ) -> Result<(), RedisProtocolError> {
+        let accepted = self
+            .authenticator
+            .as_ref()
+            .is_none_or(|authenticator| authenticator.authenticate(username, password));
+
+        if accepted {
+            session.authenticate(username);
+            Ok(())
+        } else {
+            warn!(
+                "Failed authentication of user {} from {}",
+                username,
+                session.addr()
+            );
+            Err(error_wrongpass())
+        }
+    }
+}
+
+/// Turn a [`RedisProtocolError`] into the error reply sent to clients
+pub fn error_frame(err: &RedisProtocolError) -> OwnedFrame {
+    OwnedFrame::SimpleError {
+        data: err.details().to_string(),
+        attributes: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::Protocol;
+    use crate::server::handler::DefaultHandler;
+
+    struct Store;
+
+    impl CommandHandler for Store {
+        fn get(
+            &self,
+            _session: &mut Session,
+            _request: &Request,
+        ) -> Result<OwnedFrame, RedisProtocolError> {
+            Ok("overridden".as_frame())
+        }
+    }
+
+    fn session() -> Session {
+        Session::new(1, "127.0.0.1:6379".parse().unwrap())
+    }
+
+    fn query(args: &[&'static str]) -> Vec<Bytes> {
+        args.iter()
+            .map(|arg| Bytes::from_static(arg.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn dispatch_to_overridden_method() {
+        let dispatcher = Dispatcher::new(Store);
+        let mut session = session();
+
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["GET", "key"])),
+            "overridden".as_frame()
+        );
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["PING"])),
+            "PONG".as_frame()
+        );
+    }
+
+    #[test]
+    fn dispatch_parse_error() {
+        let dispatcher = Dispatcher::new(DefaultHandler);
+
+        assert!(matches!(
+            dispatcher.dispatch(&mut session(), query(&["NOSUCHCOMMAND"])),
+            OwnedFrame::SimpleError { .. }
+        ));
+    }
+
+    #[test]
+    fn dispatch_hello_switches_protocol() {
+        let dispatcher = Dispatcher::new(DefaultHandler);
+        let mut session = session();
+        assert_eq!(session.protocol(), Protocol::Resp2);
+
+        dispatcher.dispatch(&mut session, query(&["HELLO", "3"]));
+        assert_eq!(session.protocol(), Protocol::Resp3);
+
+        let reply = dispatcher.dispatch(&mut session, query(&["HELLO", "4"]));
+        assert!(matches!(reply, OwnedFrame::SimpleError { .. }));
+        assert_eq!(session.protocol(), Protocol::Resp3);
+
+        dispatcher.dispatch(&mut session, query(&["HELLO", "2"]));
+        assert_eq!(session.protocol(), Protocol::Resp2);
+    }
+
+    #[test]
+    fn dispatch_requires_auth() {
+        let dispatcher = Dispatcher::with_authenticator(
+            DefaultHandler,
+            crate::server::auth::StaticPassword::new("secret"),
+        );
+        let mut session = session();
+        let noauth = error_frame(&error_noauth());
+
+        assert_eq!(dispatcher.dispatch(&mut session, query(&["PING"])), noauth);
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["AUTH", "wrong"])),
+            error_frame(&error_wrongpass())
+        );
+        assert_eq!(dispatcher.dispatch(&mut session, query(&["PING"])), noauth);
+        assert!(matches!(
+            dispatcher.dispatch(&mut session, query(&["HELLO", "3"])),
+            OwnedFrame::SimpleError { .. }
+        ));
+        assert_eq!(
+            dispatcher.dispatch(
+                &mut session,
+                query(&["HELLO", "4", "AUTH", "default", "secret"])
+            ),
+            error_frame(&hello::error_noproto())
+        );
+        assert!(!session.is_authenticated());
+
+        assert!(matches!(
+            dispatcher.dispatch(
+                &mut session,
+                query(&["HELLO", "3", "AUTH", "default", "secret"])
+            ),
+            OwnedFrame::Map { .. }
+        ));
+        assert_eq!(session.user(), Some(DEFAULT_USER));
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["PING"])),
+            "PONG".as_frame()
+        );
+    }
+
+    #[test]
+    fn dispatch_checks_acl() {
+        let acl = Arc::new(AccessControl::new());
+        let dispatcher = Dispatcher::with_acl(DefaultHandler, acl.clone());
+        let mut session = session();
+
+        let setuser = query(&["ACL", "SETUSER", "reader", "on", ">pw", "~*", "+@read"]);
+        assert_eq!(dispatcher.dispatch(&mut session, setuser), "OK".as_frame());
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["ACL", "WHOAMI"])),
+            "default".as_frame()
+        );
+
+        dispatcher.dispatch(&mut session, query(&["AUTH", "reader", "pw"]));
+        assert_eq!(session.user(), Some("reader"));
+        assert_eq!(
+            dispatcher.dispatch(&mut session, query(&["GET", "key"])),
+            OwnedFrame::Null
+        );
+        assert!(matches!(
+            dispatcher.dispatch(&mut session, query(&["SET", "key", "value"])),
+            OwnedFrame::SimpleError { .. }
+        ));
+
+        /* Once the default user requires a password, new sessions have to authenticate */
+        acl.set_user(DEFAULT_USER, &["resetpass".into()]).unwrap();
+        assert_eq!(
+            dispatcher.dispatch(&mut super::tests::session(), query(&["GET", "key"])),
+            error_frame(&error_noauth())
+        );
+    }
+
+    #[test]
+    fn dispatch_quit_closes_session() {
+        let dispatcher = Dispatcher::new(DefaultHandler);
+        let mut session = session();
+
+        dispatcher.dispatch(&mut session, query(&["PING"]));
+        assert!(!session.is_closing());
+
+        dispatcher.dispatch(&mut session, query(&["QUIT"]));
+        assert!(session.is_closing());
+    }
+}
@@ -0,0 +1,1037 @@
+use crate::commands::parse::Request;
+use crate::commands::*;
+use crate::server::session::Session;
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+
+/// Handle parsed [`Request`]s.
+///
+/// Every [`Request`] variant has its own method, defaulting to the `default_handle` function of
+/// the respective command. Implementors only override the commands they actually want to serve,
+/// e.g. `GET` and `SET`, and inherit a minimal reply for everything else.
+///
+/// Each method is only ever called with the variant it is named after, together with the
+/// [`Session`] of the connection the request was received on.
+///
+/// # Example
+///
+/// ```
+/// use redis_protocol::error::RedisProtocolError;
+/// use redis_protocol::resp3::types::OwnedFrame;
+/// use redis_protocol_bridge::commands::parse::Request;
+/// use redis_protocol_bridge::server::handler::CommandHandler;
+/// use redis_protocol_bridge::server::session::Session;
+/// use redis_protocol_bridge::util::convert::AsFrame;
+///
+/// struct Constant;
+///
+/// impl CommandHandler for Constant {
+///     fn get(&self, _session: &mut Session, _request: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+///         Ok("always the same".as_frame())
+///     }
+/// }
+/// ```
+pub trait CommandHandler {
+    fn hello(
+        &self,
+        session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hello::default_handle(session, request)
+    }
+
+    fn get(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        get::default_handle(request)
+    }
+
+    fn set(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        set::default_handle(request)
+    }
+
+    fn command(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        command::default_handle(request)
+    }
+
+    fn info(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        info::default_handle(request)
+    }
+
+    fn ping(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        ping::default_handle(request)
+    }
+
+    fn select(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        select::default_handle(request)
+    }
+
+    fn quit(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        quit::default_handle(request)
+    }
+
+    fn cluster(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        cluster::default_handle(request)
+    }
+
+    fn config(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        config::default_handle(request)
+    }
+
+    fn expire(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn pexpire(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn expireat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn pexpireat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn ttl(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn pttl(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn persist(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn expiretime(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        expire::default_handle(request)
+    }
+
+    fn r#move(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::default_handle(request)
+    }
+
+    fn swapdb(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::default_handle(request)
+    }
+
+    fn flushdb(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::default_handle(request)
+    }
+
+    fn flushall(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::default_handle(request)
+    }
+
+    fn dbsize(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        db::default_handle(request)
+    }
+
+    fn del(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn unlink(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn exists(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn touch(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn r#type(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn rename(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn renamenx(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn copy(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        keys::default_handle(request)
+    }
+
+    fn scan(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        scan::default_handle(request)
+    }
+
+    fn keys(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        scan::default_handle(request)
+    }
+
+    fn append(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn strlen(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn getrange(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn setrange(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn getdel(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn getex(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn getset(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        string::default_handle(request)
+    }
+
+    fn incr(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::default_handle(request)
+    }
+
+    fn decr(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::default_handle(request)
+    }
+
+    fn incrby(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::default_handle(request)
+    }
+
+    fn decrby(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::default_handle(request)
+    }
+
+    fn incrbyfloat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        incr::default_handle(request)
+    }
+
+    fn mget(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::default_handle(request)
+    }
+
+    fn mset(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::default_handle(request)
+    }
+
+    fn msetnx(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        mget::default_handle(request)
+    }
+
+    fn hset(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hmset(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hsetnx(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hget(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hmget(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hdel(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hlen(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hstrlen(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hexists(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hkeys(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hvals(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hgetall(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hincrby(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hincrbyfloat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hscan(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hexpire(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hpexpire(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hexpireat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hpexpireat(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn httl(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hpttl(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hexpiretime(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hpexpiretime(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn hpersist(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        hash::default_handle(request)
+    }
+
+    fn lpush(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn rpush(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lpushx(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn rpushx(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn rpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn llen(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lrange(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lindex(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lset(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn linsert(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lrem(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn ltrim(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lmove(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn rpoplpush(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lpos(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn lmpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn blpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn brpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn blmove(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn blmpop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        list::default_handle(request)
+    }
+
+    fn sadd(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn srem(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn smembers(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sismember(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn smismember(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn scard(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sinter(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sunion(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sdiff(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sinterstore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sunionstore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sdiffstore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sintercard(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn srandmember(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn spop(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn smove(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn sscan(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        sets::default_handle(request)
+    }
+
+    fn zadd(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zcard(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zscore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zincrby(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zrem(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zcount(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zrange(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zrank(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zrevrank(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zpopmin(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zpopmax(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zunionstore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    fn zinterstore(
+        &self,
+        _session: &mut Session,
+        request: &Request,
+    ) -> Result<OwnedFrame, RedisProtocolError> {
+        zset::default_handle(request)
+    }
+
+    /// Actively remove up to `limit` expired keys.
+    ///
+    /// Called periodically by the [`Server`](crate::server::runner::Server), so keys that are
+    /// never accessed again do not occupy memory forever. Handlers that keep their keys in a
+    /// [`KeyValueStore`](crate::storage::KeyValueStore) should forward this to
+    /// [`KeyValueStore::evict_expired`](crate::storage::KeyValueStore::evict_expired).
+    ///
+    /// # Returns
+    /// The number of keys removed
+    fn active_expire(&self, _limit: usize) -> usize {
+        0
+    }
+}
+
+/// [`CommandHandler`] that uses the default handle of every command.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultHandler;
+
+impl CommandHandler for DefaultHandler {}
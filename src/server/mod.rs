@@ -0,0 +1,24 @@
+/// [`handler::CommandHandler`] trait with one overridable method per command
+pub mod handler;
+
+/// Route parsed requests to a [`handler::CommandHandler`]
+pub mod dispatcher;
+
+/// Per-connection state passed to every handler call
+pub mod session;
+
+/// Accept connections and answer their commands
+pub mod runner;
+
+/// [`auth::Authenticator`] trait deciding which clients may connect
+pub mod auth;
+
+/// [`acl::AccessControl`] list of users and their permissions
+pub mod acl;
+
+/// [`clients::ClientRegistry`] of open connections, used by `CLIENT`
+pub mod clients;
+
+/// [`blocking::BlockingRegistry`] of connections waiting in `BLPOP` and the other blocking
+/// list commands
+pub mod blocking;
@@ -0,0 +1,775 @@
+use crate::codec::{is_null_array, RespCodec, DEFAULT_MAX_FRAME_SIZE};
+use crate::commands::parse;
+use crate::commands::parse::Request;
+use crate::parse_owned_frame;
+use crate::server::acl::AccessControl;
+use crate::server::auth::Authenticator;
+use crate::server::blocking::{blocked_on, BlockingRegistry};
+use crate::server::dispatcher::{error_frame, Dispatcher};
+use crate::server::handler::CommandHandler;
+use crate::server::session::Session;
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::types::OwnedFrame;
+use std::env;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tokio_util::codec::Framed;
+
+/// Settings for [`Server`]
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Maximum number of simultaneously connected clients. Further connections receive an
+    /// error and are closed immediately, like Redis does once `maxclients` is reached.
+    pub max_connections: usize,
+    /// Maximum size of a single incoming frame, see [`RespCodec`]
+    pub max_frame_size: usize,
+    /// How often [`CommandHandler::active_expire`] is called to remove expired keys. `None`
+    /// disables active expiration, leaving only lazy expiration on access.
+    pub active_expire_interval: Option<Duration>,
+}
+
+/// Number of keys removed per [`CommandHandler::active_expire`] call. While a whole batch is
+/// removed, the sweeper keeps going, yielding to connections in between.
+const ACTIVE_EXPIRE_BATCH: usize = 20;
+
+impl Default for ServerConfig {
+    /// Return the defaults of Redis: 10000 clients, frames of up to 512MB and 10 active
+    /// expiration cycles per second.
+    fn default() -> Self {
+        Self {
+            max_connections: 10000,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            active_expire_interval: Some(Duration::from_millis(100)),
+        }
+    }
+}
+
+/// TCP server that accepts connections and answers their commands using a [`CommandHandler`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use redis_protocol_bridge::server::handler::DefaultHandler;
+/// use redis_protocol_bridge::server::runner::{Server, ServerConfig};
+/// use tokio::net::TcpListener;
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:6379").await?;
+///     let config = ServerConfig {
+///         max_connections: 100,
+///         ..ServerConfig::default()
+///     };
+///
+///     Server::with_config(DefaultHandler, config)
+///         .run(listener, async {
+///             tokio::signal::ctrl_c().await.ok();
+///         })
+///         .await
+/// }
+/// ```
+pub struct Server<H: CommandHandler> {
+    dispatcher: Dispatcher<H>,
+    config: ServerConfig,
+}
+
+impl<H> Server<H>
+where
+    H: CommandHandler + Send + Sync + 'static,
+{
+    pub fn new(handler: H) -> Self {
+        Self::with_config(handler, ServerConfig::default())
+    }
+
+    pub fn with_config(handler: H, config: ServerConfig) -> Self {
+        Self {
+            dispatcher: Dispatcher::new(handler),
+            config,
+        }
+    }
+
+    /// Require clients to authenticate using `authenticator`, see [`Dispatcher`]
+    pub fn authenticator(mut self, authenticator: impl Authenticator + 'static) -> Self {
+        self.dispatcher.set_authenticator(authenticator);
+        self
+    }
+
+    /// Enforce the users and permissions of `acl`, see [`Dispatcher`]
+    pub fn acl(mut self, acl: Arc<AccessControl>) -> Self {
+        self.dispatcher.set_acl(acl);
+        self
+    }
+
+    /// Accept connections on `listener` until `shutdown` completes.
+    ///
+    /// Once `shutdown` resolves, no new connections are accepted. Open connections finish the
+    /// command they are currently handling and are then closed. This function returns after
+    /// every connection has been closed.
+    pub async fn run<F>(self, listener: TcpListener, shutdown: F) -> std::io::Result<()>
+    where
+        F: Future<Output = ()>,
+    {
+        let dispatcher = Arc::new(self.dispatcher);
+        let blocking = Arc::new(BlockingRegistry::new());
+        let permits = Arc::new(Semaphore::new(self.config.max_connections));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut connections = JoinSet::new();
+        let mut next_id: u64 = 0;
+
+        info!("Listening on {}", listener.local_addr()?);
+        tokio::pin!(shutdown);
+
+        if let Some(interval) = self.config.active_expire_interval {
+            tokio::spawn(active_expire(
+                dispatcher.clone(),
+                interval,
+                shutdown_rx.clone(),
+            ));
+        }
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                accepted = listener.accept() => {
+                    let (stream, addr) = match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            /* E.g. running out of file descriptors must not stop the server */
+                            error!("Failed to accept connection: {}", e);
+                            continue;
+                        }
+                    };
+
+                    next_id += 1;
+                    let session = Session::new(next_id, addr);
+                    let connection = Connection {
+                        dispatcher: dispatcher.clone(),
+                        blocking: blocking.clone(),
+                        framed: Framed::new(
+                            stream,
+                            RespCodec::new(self.config.max_frame_size),
+                        ),
+                        session,
+                        shutdown: shutdown_rx.clone(),
+                    };
+
+                    match permits.clone().try_acquire_owned() {
+                        Ok(permit) => connections.spawn(connection.run(permit)),
+                        Err(_) => connections.spawn(connection.reject()),
+                    };
+                }
+                /* Reap finished connections so the set does not grow indefinitely */
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+
+        info!(
+            "Shutting down, waiting for {} connections",
+            connections.len()
+        );
+        drop(listener);
+        let _ = shutdown_tx.send(true);
+        while connections.join_next().await.is_some() {}
+
+        Ok(())
+    }
+}
+
+/// Serve `handler` on `listener` with the default [`ServerConfig`] until the process exits.
+///
+/// See [`Server::run`] for graceful shutdown.
+pub async fn serve<H>(listener: TcpListener, handler: H) -> std::io::Result<()>
+where
+    H: CommandHandler + Send + Sync + 'static,
+{
+    serve_with_shutdown(listener, handler, std::future::pending()).await
+}
+
+/// Serve `handler` on `listener` with the default [`ServerConfig`] until `shutdown` completes.
+pub async fn serve_with_shutdown<H, F>(
+    listener: TcpListener,
+    handler: H,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    H: CommandHandler + Send + Sync + 'static,
+    F: Future<Output = ()>,
+{
+    Server::new(handler).run(listener, shutdown).await
+}
+
+/// Periodically remove expired keys from the handler until the server shuts down.
+async fn active_expire<H: CommandHandler>(
+    dispatcher: Arc<Dispatcher<H>>,
+    period: Duration,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.changed() => break,
+        }
+
+        while dispatcher.handler().active_expire(ACTIVE_EXPIRE_BATCH) >= ACTIVE_EXPIRE_BATCH {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+/// A single client connection and everything needed to answer its commands
+struct Connection<H: CommandHandler> {
+    dispatcher: Arc<Dispatcher<H>>,
+    blocking: Arc<BlockingRegistry>,
+    framed: Framed<TcpStream, RespCodec>,
+    session: Session,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl<H: CommandHandler> Connection<H> {
+    /// Answer commands until the client disconnects, sends `QUIT`, is killed with `CLIENT KILL`
+    /// or the server shuts down.
+    ///
+    /// While clients are paused with `CLIENT PAUSE`, affected commands are held back until the
+    /// pause ends. Blocking commands like `BLPOP` hold the connection until they are served or
+    /// time out.
+    async fn run(mut self, _permit: OwnedSemaphorePermit) {
+        let addr: SocketAddr = self.session.addr();
+        info!("Incoming connection from: {}", addr);
+        let dispatcher = self.dispatcher.clone();
+        let clients = dispatcher.clients();
+        let mut killed = clients.register(&self.session);
+
+        while !self.session.is_closing() {
+            let result = tokio::select! {
+                result = self.framed.next() => result,
+                _ = self.shutdown.changed() => break,
+                _ = killed.changed() => break,
+            };
+
+            let reply = match result {
+                /* Not logged here, the query might contain credentials */
+                Some(Ok(frame)) => match parse::parse(parse_owned_frame(frame)) {
+                    Ok(request) => {
+                        tokio::select! {
+                            _ = clients.wait_unpaused(&request) => {}
+                            _ = self.shutdown.changed() => break,
+                            _ = killed.changed() => break,
+                        }
+                        let reply = match blocked_on(&request) {
+                            Some((keys, timeout)) => {
+                                match self.block(&request, &keys, timeout, &mut killed).await {
+                                    Some(reply) => reply,
+                                    None => break,
+                                }
+                            }
+                            None => self.dispatch(&request),
+                        };
+                        reply.unwrap_or_else(|e| error_frame(&e))
+                    }
+                    Err(e) => error_frame(&e),
+                },
+                Some(Err(e)) => {
+                    /* The stream can't be resynchronized after a protocol error, so close it */
+                    error!("Error: {}", e);
+                    self.session.close();
+                    error_frame(&e)
+                }
+                None => break,
+            };
+
+            debug!("Reply: {:#?}", reply);
+            self.framed
+                .codec_mut()
+                .set_protocol(self.session.protocol());
+            if let Err(e) = self.framed.send(reply).await {
+                error!("Failed to send reply: {}", e);
+                break;
+            }
+        }
+
+        clients.unregister(self.session.id());
+        info!("Closed connection to {}", addr);
+    }
+
+    /// Dispatch `request` and wake connections blocked on the keys it wrote
+    fn dispatch(&mut self, request: &Request) -> Result<OwnedFrame, RedisProtocolError> {
+        let reply = self.dispatcher.dispatch_request(&mut self.session, request);
+        self.wake(request);
+        reply
+    }
+
+    /// Wake the connections blocked on the keys of `request`, if it writes.
+    ///
+    /// Commands that write to another database wake the connections blocked there: `MOVE` and
+    /// `COPY ... DB` on their target key, `SWAPDB` on every key of both databases.
+    fn wake(&self, request: &Request) {
+        if !request.info().categories.contains(&"write") {
+            return;
+        }
+
+        let target = |db: i64| usize::try_from(db).ok();
+        match request {
+            Request::MOVE { key, db } => {
+                if let Some(db) = target(*db) {
+                    self.blocking.wake(db, key);
+                }
+            }
+            Request::COPY {
+                destination,
+                db: Some(db),
+                ..
+            } => {
+                if let Some(db) = target(*db) {
+                    self.blocking.wake(db, destination);
+                }
+            }
+            Request::SWAPDB { index1, index2 } => {
+                for db in [*index1, *index2].into_iter().filter_map(target) {
+                    self.blocking.wake_all(db);
+                }
+            }
+            _ => {
+                for key in request.keys() {
+                    self.blocking.wake(self.session.db(), key);
+                }
+            }
+        }
+    }
+
+    /// Dispatch the blocking `request` until it is served, waiting for one of `keys` to be
+    /// written in between. A [`OwnedFrame::Null`] or [`null_array`](crate::codec::null_array)
+    /// reply means that there was nothing to serve.
+    ///
+    /// # Returns
+    /// The reply, the empty reply of the last attempt once `timeout` has passed, or `None` if
+    /// the connection has to be closed first, e.g. because the client disconnected
+    async fn block(
+        &mut self,
+        request: &Request,
+        keys: &[&Bytes],
+        timeout: Duration,
+        killed: &mut watch::Receiver<bool>,
+    ) -> Option<Result<OwnedFrame, RedisProtocolError>> {
+        /* Queue up before the first attempt, so no write in between is missed */
+        let blocked = self.blocking.block(self.session.db(), keys);
+        let deadline = (!timeout.is_zero()).then(|| Instant::now() + timeout);
+
+        loop {
+            /* Attempts that served nothing wrote nothing, so they must not wake anyone */
+            let empty = match self.dispatcher.dispatch_request(&mut self.session, request) {
+                Ok(reply) if reply == OwnedFrame::Null || is_null_array(&reply) => reply,
+                reply => {
+                    self.wake(request);
+                    return Some(reply);
+                }
+            };
+
+            tokio::select! {
+                _ = blocked.notified() => {}
+                _ = sleep_until(deadline) => return Some(Ok(empty)),
+                /* Leave the queue right away, so a write is not served to a dead socket */
+                _ = closed(self.framed.get_ref()) => return None,
+                _ = self.shutdown.changed() => return None,
+                _ = killed.changed() => return None,
+            }
+        }
+    }
+
+    /// Tell the client that the connection limit is reached and close the connection.
+    async fn reject(mut self) {
+        warn!(
+            "Rejecting connection from {}: too many clients",
+            self.session.addr()
+        );
+        let reply = OwnedFrame::SimpleError {
+            data: "ERR max number of clients reached".into(),
+            attributes: None,
+        };
+        let _ = self.framed.send(reply).await;
+    }
+}
+
+/// Wait until the client closes `stream` or it fails, without consuming what the client sent.
+///
+/// Once the client sends more data, e.g. the next pipelined command, this waits forever and
+/// leaves the data to be read after the current command.
+async fn closed(stream: &TcpStream) {
+    let mut buf = [0; 1];
+    if let Ok(1..) = stream.peek(&mut buf).await {
+        std::future::pending().await
+    }
+}
+
+/// Sleep until `deadline`, or forever without one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Initialize [`env_logger`], logging at `debug` level in debug builds and at `info` level in
+/// release builds unless `RUST_LOG` is set.
+pub fn setup_logging() {
+    // Set default RUST_LOG level if not already set
+    if env::var("RUST_LOG").is_err() {
+        let log_level = if cfg!(debug_assertions) {
+            "debug" // dev profile
+        } else {
+            "info" // release profile
+        };
+        env::set_var("RUST_LOG", log_level);
+    }
+
+    // Initialize env_logger
+    env_logger::init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::list;
+    use crate::server::handler::DefaultHandler;
+    use crate::storage::sharded::ShardedStore;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    async fn start(config: ServerConfig) -> (SocketAddr, oneshot::Sender<()>) {
+        start_with(DefaultHandler, config).await
+    }
+
+    async fn start_with<H>(handler: H, config: ServerConfig) -> (SocketAddr, oneshot::Sender<()>)
+    where
+        H: CommandHandler + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            Server::with_config(handler, config)
+                .run(listener, async {
+                    rx.await.ok();
+                })
+                .await
+                .unwrap();
+        });
+
+        (addr, tx)
+    }
+
+    async fn request(stream: &mut TcpStream, command: &[u8]) -> Vec<u8> {
+        stream.write_all(command).await.unwrap();
+        let mut buf = vec![0; 512];
+        let n = stream.read(&mut buf).await.unwrap();
+        buf.truncate(n);
+        buf
+    }
+
+    /// Lists in a single database, enough to block with `BLPOP` and wake with `RPUSH`
+    #[derive(Default)]
+    struct Lists(ShardedStore);
+
+    impl CommandHandler for Lists {
+        fn rpush(
+            &self,
+            _session: &mut Session,
+            request: &Request,
+        ) -> Result<OwnedFrame, RedisProtocolError> {
+            list::handle(&mut &self.0, request)
+        }
+
+        fn blpop(
+            &self,
+            _session: &mut Session,
+            request: &Request,
+        ) -> Result<OwnedFrame, RedisProtocolError> {
+            list::handle(&mut &self.0, request)
+        }
+    }
+
+    #[tokio::test]
+    async fn ping_and_quit() {
+        let (addr, _shutdown) = start(ServerConfig::default()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        assert_eq!(
+            request(&mut stream, b"*1\r\n$4\r\nPING\r\n").await,
+            b"$4\r\nPONG\r\n"
+        );
+        assert_eq!(
+            request(&mut stream, b"*1\r\n$4\r\nQUIT\r\n").await,
+            b"$2\r\nOK\r\n"
+        );
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn resp2_until_hello_3() {
+        let (addr, _shutdown) = start(ServerConfig::default()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let get = b"*2\r\n$3\r\nGET\r\n$7\r\nmissing\r\n";
+
+        assert_eq!(request(&mut stream, get).await, b"$-1\r\n");
+        assert!(request(&mut stream, b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n")
+            .await
+            .starts_with(b"%"));
+        assert_eq!(request(&mut stream, get).await, b"_\r\n");
+    }
+
+    #[tokio::test]
+    async fn reject_connections_over_limit() {
+        let config = ServerConfig {
+            max_connections: 1,
+            ..ServerConfig::default()
+        };
+        let (addr, _shutdown) = start(config).await;
+
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(
+            request(&mut first, b"*1\r\n$4\r\nPING\r\n").await,
+            b"$4\r\nPONG\r\n"
+        );
+
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let mut buf = vec![0; 64];
+        let n = second.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"-ERR max number of clients reached\r\n");
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_connections() {
+        let (addr, shutdown) = start(ServerConfig::default()).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        request(&mut stream, b"*1\r\n$4\r\nPING\r\n").await;
+
+        shutdown.send(()).unwrap();
+
+        let mut buf = [0; 16];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn client_pause_and_kill() {
+        let (addr, _shutdown) = start(ServerConfig::default()).await;
+        let mut admin = TcpStream::connect(addr).await.unwrap();
+        let mut client = TcpStream::connect(addr).await.unwrap();
+
+        let id = request(&mut client, b"CLIENT ID\r\n").await;
+        assert_eq!(
+            request(&mut admin, b"CLIENT PAUSE 10000 WRITE\r\n").await,
+            b"$2\r\nOK\r\n"
+        );
+
+        /* Reads pass, writes are held back until the pause ends */
+        assert_eq!(request(&mut client, b"GET key\r\n").await, b"$-1\r\n");
+        client.write_all(b"SET key value\r\n").await.unwrap();
+        let mut buf = vec![0; 64];
+        let pending = tokio::time::timeout(Duration::from_millis(50), client.read(&mut buf));
+        assert!(pending.await.is_err());
+
+        request(&mut admin, b"CLIENT UNPAUSE\r\n").await;
+        assert!(client.read(&mut buf).await.unwrap() > 0);
+
+        let id = std::str::from_utf8(&id[1..id.len() - 2]).unwrap();
+        let kill = format!("CLIENT KILL ID {id}\r\n");
+        assert_eq!(request(&mut admin, kill.as_bytes()).await, b":1\r\n");
+        assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn blocked_clients_are_served_in_order() {
+        let (addr, _shutdown) = start_with(Lists::default(), ServerConfig::default()).await;
+        let mut first = TcpStream::connect(addr).await.unwrap();
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let mut producer = TcpStream::connect(addr).await.unwrap();
+
+        first.write_all(b"BLPOP other queue 0\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        second.write_all(b"BLPOP queue 0\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(
+            request(&mut producer, b"RPUSH queue a b\r\n").await,
+            b":2\r\n"
+        );
+        let mut buf = vec![0; 64];
+        let n = first.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"*2\r\n$5\r\nqueue\r\n$1\r\na\r\n");
+        let n = second.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"*2\r\n$5\r\nqueue\r\n$1\r\nb\r\n");
+
+        assert_eq!(
+            request(&mut first, b"BLPOP queue 0.05\r\n").await,
+            b"*-1\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn disconnected_clients_stop_blocking() {
+        let (addr, _shutdown) = start_with(Lists::default(), ServerConfig::default()).await;
+        let mut gone = TcpStream::connect(addr).await.unwrap();
+        let mut consumer = TcpStream::connect(addr).await.unwrap();
+        let mut producer = TcpStream::connect(addr).await.unwrap();
+
+        gone.write_all(b"BLPOP queue 0\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(gone);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(
+            request(&mut producer, b"RPUSH queue job\r\n").await,
+            b":1\r\n"
+        );
+        assert_eq!(
+            request(&mut consumer, b"BLPOP queue 1\r\n").await,
+            b"*2\r\n$5\r\nqueue\r\n$3\r\njob\r\n"
+        );
+
+        /* Commands sent while blocked are answered afterward */
+        consumer.write_all(b"BLPOP queue 0\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        consumer.write_all(b"PING\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        request(&mut producer, b"RPUSH queue next\r\n").await;
+        let mut reply = Vec::new();
+        while !reply.ends_with(b"PONG\r\n") {
+            let mut buf = vec![0; 64];
+            let n = consumer.read(&mut buf).await.unwrap();
+            assert_ne!(n, 0);
+            reply.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(reply, b"*2\r\n$5\r\nqueue\r\n$4\r\nnext\r\n$4\r\nPONG\r\n");
+    }
+
+    #[tokio::test]
+    async fn writes_to_other_databases_wake_blocked_clients() {
+        use crate::commands::{db, select};
+        use crate::storage::databases::Databases;
+
+        struct Lists(Databases);
+
+        impl Lists {
+            fn store(&self, session: &Session) -> Arc<crate::storage::sharded::ShardedStore> {
+                self.0.get(session.db()).unwrap()
+            }
+        }
+
+        impl CommandHandler for Lists {
+            fn select(
+                &self,
+                session: &mut Session,
+                request: &Request,
+            ) -> Result<OwnedFrame, RedisProtocolError> {
+                select::handle(session, 2, request)
+            }
+
+            fn r#move(
+                &self,
+                session: &mut Session,
+                request: &Request,
+            ) -> Result<OwnedFrame, RedisProtocolError> {
+                db::handle(&self.0, session, request)
+            }
+
+            fn swapdb(
+                &self,
+                session: &mut Session,
+                request: &Request,
+            ) -> Result<OwnedFrame, RedisProtocolError> {
+                db::handle(&self.0, session, request)
+            }
+
+            fn rpush(
+                &self,
+                session: &mut Session,
+                request: &Request,
+            ) -> Result<OwnedFrame, RedisProtocolError> {
+                list::handle(&mut self.store(session).as_ref(), request)
+            }
+
+            fn blpop(
+                &self,
+                session: &mut Session,
+                request: &Request,
+            ) -> Result<OwnedFrame, RedisProtocolError> {
+                list::handle(&mut self.store(session).as_ref(), request)
+            }
+        }
+
+        let (addr, _shutdown) = start_with(Lists(Databases::new(2)), ServerConfig::default()).await;
+        let mut consumer = TcpStream::connect(addr).await.unwrap();
+        let mut producer = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(
+            request(&mut consumer, b"SELECT 1\r\n").await,
+            b"$2\r\nOK\r\n"
+        );
+
+        consumer.write_all(b"BLPOP queue 5\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            request(&mut producer, b"RPUSH queue a\r\n").await,
+            b":1\r\n"
+        );
+        assert_eq!(request(&mut producer, b"MOVE queue 1\r\n").await, b":1\r\n");
+        let mut buf = vec![0; 64];
+        let n = consumer.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"*2\r\n$5\r\nqueue\r\n$1\r\na\r\n");
+
+        consumer.write_all(b"BLPOP queue 5\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            request(&mut producer, b"RPUSH queue b\r\n").await,
+            b":1\r\n"
+        );
+        assert_eq!(
+            request(&mut producer, b"SWAPDB 0 1\r\n").await,
+            b"$2\r\nOK\r\n"
+        );
+        let n = consumer.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"*2\r\n$5\r\nqueue\r\n$1\r\nb\r\n");
+    }
+
+    #[tokio::test]
+    async fn active_expire_removes_keys() {
+        use std::time::SystemTime;
+
+        struct Expiring(Arc<ShardedStore>);
+
+        impl CommandHandler for Expiring {
+            fn active_expire(&self, limit: usize) -> usize {
+                self.0.evict_expired(limit)
+            }
+        }
+
+        let store = Arc::new(ShardedStore::new());
+        for i in 0..50 {
+            let key = Bytes::from(format!("{i}"));
+            store.set(key.clone(), Bytes::new());
+            store.expire(&key, Some(SystemTime::now()));
+        }
+
+        let config = ServerConfig {
+            active_expire_interval: Some(Duration::from_millis(10)),
+            ..ServerConfig::default()
+        };
+        let (_addr, _shutdown) = start_with(Expiring(store.clone()), config).await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::codec::Protocol;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+/// State of a single client connection.
+///
+/// A [`Session`] is created by the server runner for every accepted connection and passed to
+/// each [`CommandHandler`](crate::server::handler::CommandHandler) call made on its behalf.
+#[derive(Debug, Clone)]
+pub struct Session {
+    id: u64,
+    addr: SocketAddr,
+    created: Instant,
+    closing: bool,
+    db: usize,
+    protocol: Protocol,
+    user: Option<String>,
+    name: Option<String>,
+    lib_name: Option<String>,
+    lib_version: Option<String>,
+    no_evict: bool,
+    last_command: Option<&'static str>,
+    last_interaction: Instant,
+}
+
+impl Session {
+    pub fn new(id: u64, addr: SocketAddr) -> Self {
+        let now = Instant::now();
+        Self {
+            id,
+            addr,
+            created: now,
+            closing: false,
+            db: 0,
+            protocol: Protocol::default(),
+            user: None,
+            name: None,
+            lib_name: None,
+            lib_version: None,
+            no_evict: false,
+            last_command: None,
+            last_interaction: now,
+        }
+    }
+
+    /// Unique id of this connection
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Address of the connected client
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Point in time the connection was accepted
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    /// Index of the database selected with `SELECT`, `0` by default
+    pub fn db(&self) -> usize {
+        self.db
+    }
+
+    /// Use database `db` for all following commands of this connection
+    pub fn select(&mut self, db: usize) {
+        self.db = db;
+    }
+
+    /// Protocol replies to this connection are encoded in, [`Protocol::Resp2`] until the client
+    /// negotiates another version with `HELLO`
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// Name of the user this connection authenticated as, if any
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Mark the connection as authenticated as `user`
+    pub fn authenticate(&mut self, user: impl Into<String>) {
+        self.user = Some(user.into());
+    }
+
+    /// Whether [`Session::authenticate`] was called
+    pub fn is_authenticated(&self) -> bool {
+        self.user.is_some()
+    }
+
+    /// Name set with `CLIENT SETNAME` or `HELLO SETNAME`
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set or, with `None`, clear the name of the connection
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// Client library name set with `CLIENT SETINFO LIB-NAME`
+    pub fn lib_name(&self) -> Option<&str> {
+        self.lib_name.as_deref()
+    }
+
+    pub fn set_lib_name(&mut self, lib_name: Option<String>) {
+        self.lib_name = lib_name;
+    }
+
+    /// Client library version set with `CLIENT SETINFO LIB-VER`
+    pub fn lib_version(&self) -> Option<&str> {
+        self.lib_version.as_deref()
+    }
+
+    pub fn set_lib_version(&mut self, lib_version: Option<String>) {
+        self.lib_version = lib_version;
+    }
+
+    /// Whether the client asked to be excluded from eviction with `CLIENT NO-EVICT on`
+    pub fn no_evict(&self) -> bool {
+        self.no_evict
+    }
+
+    pub fn set_no_evict(&mut self, no_evict: bool) {
+        self.no_evict = no_evict;
+    }
+
+    /// Name of the last command received, see [`Request::name`](crate::commands::parse::Request::name)
+    pub fn last_command(&self) -> Option<&'static str> {
+        self.last_command
+    }
+
+    /// Point in time the last command was received
+    pub fn last_interaction(&self) -> Instant {
+        self.last_interaction
+    }
+
+    /// Remember that `command` was just received
+    pub fn record(&mut self, command: &'static str) {
+        self.last_command = Some(command);
+        self.last_interaction = Instant::now();
+    }
+
+    /// Close the connection once the reply to the current command has been sent
+    pub fn close(&mut self) {
+        self.closing = true;
+    }
+
+    /// Whether [`Session::close`] was called
+    pub fn is_closing(&self) -> bool {
+        self.closing
+    }
+}
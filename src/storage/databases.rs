@@ -0,0 +1,109 @@
+use crate::storage::sharded::ShardedStore;
+use std::sync::{Arc, RwLock};
+
+/// Number of databases used by [`Databases::default`], matching the default of Redis
+pub const DEFAULT_DATABASES: usize = 16;
+
+/// A fixed number of numbered [`ShardedStore`]s, the databases clients switch between with
+/// `SELECT`.
+///
+/// Databases are handed out as [`Arc`]s, so `SWAPDB` can exchange two of them without waiting
+/// for connections currently using either. Commands that fetch a database after the swap see
+/// the exchanged contents.
+///
+/// ```
+/// use bytes::Bytes;
+/// use redis_protocol_bridge::storage::databases::Databases;
+///
+/// let databases = Databases::new(2);
+/// databases.get(0).unwrap().set(Bytes::from_static(b"key"), Bytes::new());
+///
+/// assert!(databases.swap(0, 1));
+/// assert!(databases.get(1).unwrap().exists(b"key"));
+/// ```
+#[derive(Debug)]
+pub struct Databases {
+    databases: RwLock<Vec<Arc<ShardedStore>>>,
+}
+
+impl Databases {
+    /// Create `count` empty databases.
+    ///
+    /// # Panics
+    /// If `count` is 0
+    pub fn new(count: usize) -> Self {
+        assert!(count > 0, "Databases needs at least one database");
+        Self {
+            databases: RwLock::new((0..count).map(|_| Arc::default()).collect()),
+        }
+    }
+
+    /// Return the database with index `index`, if it exists
+    pub fn get(&self, index: usize) -> Option<Arc<ShardedStore>> {
+        self.databases
+            .read()
+            .expect("Databases lock poisoned")
+            .get(index)
+            .cloned()
+    }
+
+    /// Exchange the contents of the databases `a` and `b`.
+    ///
+    /// # Returns
+    /// `false` if either index is out of range
+    pub fn swap(&self, a: usize, b: usize) -> bool {
+        let mut databases = self.databases.write().expect("Databases lock poisoned");
+        if a >= databases.len() || b >= databases.len() {
+            return false;
+        }
+
+        databases.swap(a, b);
+        true
+    }
+
+    /// Remove every key of every database
+    pub fn clear(&self) {
+        for database in self.all() {
+            database.clear();
+        }
+    }
+
+    /// Remove up to `limit` expired keys across all databases, see
+    /// [`KeyValueStore::evict_expired`](crate::storage::KeyValueStore::evict_expired)
+    pub fn evict_expired(&self, limit: usize) -> usize {
+        let mut evicted = 0;
+        for database in self.all() {
+            if evicted >= limit {
+                break;
+            }
+            evicted += database.evict_expired(limit - evicted);
+        }
+        evicted
+    }
+
+    /// Number of databases
+    pub fn len(&self) -> usize {
+        self.databases
+            .read()
+            .expect("Databases lock poisoned")
+            .len()
+    }
+
+    /// Always `false`, there is at least one database
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn all(&self) -> Vec<Arc<ShardedStore>> {
+        self.databases
+            .read()
+            .expect("Databases lock poisoned")
+            .clone()
+    }
+}
+
+impl Default for Databases {
+    fn default() -> Self {
+        Self::new(DEFAULT_DATABASES)
+    }
+}
@@ -0,0 +1,117 @@
+use bytes::Bytes;
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+/// Deadlines of keys with a time to live, ordered by the time they expire.
+///
+/// Stores consult the index on access to hide expired keys and use [`ExpiryIndex::pop_expired`]
+/// to find keys to remove actively, without scanning the whole keyspace.
+#[derive(Debug, Default, Clone)]
+pub struct ExpiryIndex {
+    deadlines: HashMap<Bytes, SystemTime>,
+    queue: BTreeSet<(SystemTime, Bytes)>,
+}
+
+impl ExpiryIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the deadline of `key`, if it has one
+    pub fn get(&self, key: &[u8]) -> Option<SystemTime> {
+        self.deadlines.get(key).copied()
+    }
+
+    /// Set the deadline of `key`, replacing any previous deadline.
+    ///
+    /// # Returns
+    /// The previous deadline, if any
+    pub fn set(&mut self, key: Bytes, deadline: SystemTime) -> Option<SystemTime> {
+        let previous = self.remove(&key);
+        self.queue.insert((deadline, key.clone()));
+        self.deadlines.insert(key, deadline);
+        previous
+    }
+
+    /// Remove the deadline of `key`.
+    ///
+    /// # Returns
+    /// The removed deadline, if any
+    pub fn remove(&mut self, key: &[u8]) -> Option<SystemTime> {
+        let (key, deadline) = self.deadlines.remove_entry(key)?;
+        self.queue.remove(&(deadline, key));
+        Some(deadline)
+    }
+
+    /// Whether the deadline of `key` is at or before `now`
+    pub fn is_expired(&self, key: &[u8], now: SystemTime) -> bool {
+        self.get(key).is_some_and(|deadline| deadline <= now)
+    }
+
+    /// Remove and return up to `limit` keys whose deadline is at or before `now`, the ones
+    /// that expired first come first.
+    pub fn pop_expired(&mut self, now: SystemTime, limit: usize) -> Vec<Bytes> {
+        let mut expired = Vec::new();
+        while expired.len() < limit {
+            match self.queue.first() {
+                Some((deadline, _)) if *deadline <= now => {
+                    let (_, key) = self.queue.pop_first().expect("Queue is not empty");
+                    self.deadlines.remove(&key);
+                    expired.push(key);
+                }
+                _ => break,
+            }
+        }
+        expired
+    }
+
+    /// Number of keys with a deadline
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    /// Remove all deadlines
+    pub fn clear(&mut self) {
+        self.deadlines.clear();
+        self.queue.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn pop_expired_in_deadline_order() {
+        let now = SystemTime::now();
+        let mut index = ExpiryIndex::new();
+
+        index.set(Bytes::from_static(b"late"), now - Duration::from_secs(1));
+        index.set(Bytes::from_static(b"early"), now - Duration::from_secs(2));
+        index.set(Bytes::from_static(b"future"), now + Duration::from_secs(60));
+        /* Replacing a deadline must not leave the old one behind */
+        index.set(Bytes::from_static(b"moved"), now - Duration::from_secs(3));
+        index.set(Bytes::from_static(b"moved"), now + Duration::from_secs(60));
+
+        assert!(index.is_expired(b"late", now));
+        assert!(!index.is_expired(b"moved", now));
+        assert_eq!(
+            index.pop_expired(now, 1),
+            vec![Bytes::from_static(b"early")]
+        );
+        assert_eq!(
+            index.pop_expired(now, 10),
+            vec![Bytes::from_static(b"late")]
+        );
+        assert_eq!(index.len(), 2);
+
+        assert!(index.remove(b"future").is_some());
+        assert!(index.remove(b"future").is_none());
+        assert_eq!(index.len(), 1);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::storage::memory::{scan_positions, CURSOR_BITS};
+use bytes::Bytes;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+use std::time::SystemTime;
+
+/// Fields of a hash, each with an optional time to live set by `HEXPIRE` and its variants.
+///
+/// Fields whose time to live has passed behave as if they did not exist. They are removed on
+/// access or by [`Hash::evict_expired`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hash {
+    fields: HashMap<Bytes, Field>,
+    /// Every field with a time to live, earliest deadline first. Hashes without any skip the
+    /// expiry checks.
+    deadlines: BTreeSet<(SystemTime, Bytes)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+    value: Bytes,
+    expires: Option<SystemTime>,
+}
+
+impl Field {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|deadline| deadline <= now)
+    }
+}
+
+impl Hash {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn live(&self, field: &[u8]) -> Option<&Field> {
+        self.fields
+            .get(field)
+            .filter(|field| self.deadlines.is_empty() || !field.is_expired(SystemTime::now()))
+    }
+
+    /// Number of fields, not counting expired ones
+    pub fn len(&self) -> usize {
+        let now = SystemTime::now();
+        let expired = self
+            .deadlines
+            .iter()
+            .take_while(|(deadline, _)| *deadline <= now)
+            .count();
+        self.fields.len() - expired
+    }
+
+    /// Whether there are no fields, or all of them have expired
+    pub fn is_empty(&self) -> bool {
+        /* Every field has expired if all of them have a deadline and the latest one has passed */
+        self.fields.len() == self.deadlines.len()
+            && self
+                .deadlines
+                .last()
+                .is_none_or(|(deadline, _)| *deadline <= SystemTime::now())
+    }
+
+    /// Return the value of `field`, if it exists
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        self.live(field).map(|field| &field.value)
+    }
+
+    /// Return the value of `field` for modification, keeping its time to live
+    pub fn get_mut(&mut self, field: &[u8]) -> Option<&mut Bytes> {
+        self.evict_if_expired(field);
+        self.fields.get_mut(field).map(|field| &mut field.value)
+    }
+
+    /// Check whether `field` exists
+    pub fn contains(&self, field: &[u8]) -> bool {
+        self.live(field).is_some()
+    }
+
+    /// Set `field` to `value`, removing its time to live like `HSET`.
+    ///
+    /// # Returns
+    /// `true` if the field is new
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        self.evict_if_expired(&field);
+        let previous = self.fields.insert(
+            field.clone(),
+            Field {
+                value,
+                expires: None,
+            },
+        );
+        if let Some(deadline) = previous.as_ref().and_then(|f| f.expires) {
+            self.deadlines.remove(&(deadline, field));
+        }
+        previous.is_none()
+    }
+
+    /// Remove `field`.
+    ///
+    /// # Returns
+    /// `true` if the field existed
+    pub fn remove(&mut self, field: &[u8]) -> bool {
+        if self.evict_if_expired(field) {
+            return false;
+        }
+        match self.fields.remove_entry(field) {
+            Some((name, removed)) => {
+                if let Some(deadline) = removed.expires {
+                    self.deadlines.remove(&(deadline, name));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterate over the fields and their values, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        let now = SystemTime::now();
+        self.fields
+            .iter()
+            .filter(move |(_, field)| self.deadlines.is_empty() || !field.is_expired(now))
+            .map(|(name, field)| (name, &field.value))
+    }
+
+    /// Return the time to live of `field`.
+    ///
+    /// # Returns
+    /// `None` if the field does not exist, `Some(None)` if it exists without time to live
+    pub fn expires(&self, field: &[u8]) -> Option<Option<SystemTime>> {
+        self.live(field).map(|field| field.expires)
+    }
+
+    /// Remove `field` once `deadline` has passed. `None` removes an existing time to live.
+    ///
+    /// # Returns
+    /// `true` if the field exists
+    pub fn expire(&mut self, field: &[u8], deadline: Option<SystemTime>) -> bool {
+        self.evict_if_expired(field);
+        let Some((name, field)) = self.fields.get_key_value(field) else {
+            return false;
+        };
+
+        let name = name.clone();
+        if let Some(previous) = field.expires {
+            self.deadlines.remove(&(previous, name.clone()));
+        }
+        if let Some(deadline) = deadline {
+            self.deadlines.insert((deadline, name.clone()));
+        }
+        self.fields.get_mut(&name).expect("Field exists").expires = deadline;
+        true
+    }
+
+    /// Remove every field whose time to live has passed.
+    ///
+    /// # Returns
+    /// The number of fields removed
+    pub fn evict_expired(&mut self) -> usize {
+        let now = SystemTime::now();
+        let mut evicted = 0;
+        while self
+            .deadlines
+            .first()
+            .is_some_and(|(deadline, _)| *deadline <= now)
+        {
+            let (_, field) = self.deadlines.pop_first().expect("Deadline exists");
+            self.fields.remove(&field);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// Iterate the fields in batches of roughly `count`, starting at `cursor`, with the same
+    /// guarantees as [`KeyValueStore::scan`](crate::storage::KeyValueStore::scan).
+    ///
+    /// # Returns
+    /// The cursor to continue with, `0` once the iteration is complete, and the fields found
+    /// together with their values
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(Bytes, Bytes)>) {
+        /* Positions must not change between calls, so the hasher is not randomized */
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        let (next, batch) = scan_positions(
+            self.iter().map(|(field, value)| {
+                let position = hasher.hash_one(field) >> (u64::BITS - CURSOR_BITS);
+                (position, (field, value))
+            }),
+            cursor,
+            count,
+        );
+        let batch = batch
+            .into_iter()
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect();
+        (next, batch)
+    }
+
+    fn evict_if_expired(&mut self, field: &[u8]) -> bool {
+        let expired = self
+            .fields
+            .get(field)
+            .is_some_and(|field| field.is_expired(SystemTime::now()));
+        if expired {
+            let (name, removed) = self.fields.remove_entry(field).expect("Field exists");
+            let deadline = removed.expires.expect("Expired fields have a deadline");
+            self.deadlines.remove(&(deadline, name));
+        }
+        expired
+    }
+}
+
+impl FromIterator<(Bytes, Bytes)> for Hash {
+    fn from_iter<I: IntoIterator<Item = (Bytes, Bytes)>>(iter: I) -> Self {
+        let mut hash = Hash::new();
+        for (field, value) in iter {
+            hash.insert(field, value);
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn field_expiry() {
+        let mut hash: Hash = (0..20)
+            .map(|i| (Bytes::from(format!("{i}")), Bytes::new()))
+            .collect();
+        let past = SystemTime::now() - Duration::from_secs(1);
+        let future = SystemTime::now() + Duration::from_secs(60);
+
+        assert!(hash.expire(b"1", Some(past)));
+        assert!(hash.expire(b"2", Some(future)));
+        assert_eq!(hash.len(), 19);
+        assert!(!hash.contains(b"1"));
+        assert!(!hash.remove(b"1"));
+        assert_eq!(hash.expires(b"2"), Some(Some(future)));
+
+        assert!(!hash.insert(Bytes::from_static(b"2"), Bytes::new()));
+        assert_eq!(hash.expires(b"2"), Some(None));
+
+        hash.expire(b"3", Some(past));
+        assert_eq!(hash.evict_expired(), 1);
+        assert!(hash.deadlines.is_empty());
+
+        let mut expiring: Hash = [("a", "1"), ("b", "2")]
+            .map(|(field, value)| (Bytes::from(field), Bytes::from(value)))
+            .into_iter()
+            .collect();
+        expiring.expire(b"a", Some(past));
+        assert!(!expiring.is_empty());
+        expiring.expire(b"b", Some(future));
+        assert!(!expiring.is_empty());
+        expiring.expire(b"b", Some(past));
+        assert!(expiring.is_empty());
+        assert_eq!(expiring.len(), 0);
+
+        let mut cursor = 0;
+        let mut seen = 0;
+        loop {
+            let (next, batch) = hash.scan(cursor, 5);
+            seen += batch.len();
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(seen, 18);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::storage::expiry::ExpiryIndex;
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use bytes::Bytes;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::BuildHasher;
+use std::time::SystemTime;
+
+/// Number of bits used by [`MemoryStore`] scan cursors. The upper bits are always zero, so
+/// stores wrapping several [`MemoryStore`]s can use them to encode which one to continue with.
+pub const CURSOR_BITS: u32 = 48;
+
+/// Single-threaded [`KeyValueStore`] backed by a [`HashMap`].
+///
+/// Expired keys are removed lazily whenever they are accessed, and actively by
+/// [`KeyValueStore::evict_expired`].
+///
+/// [`KeyValueStore::scan`] visits keys in the order of a hash of the key that stays fixed for
+/// the lifetime of the store. The cursor is the position to continue at, so a key's position
+/// never depends on other keys being added or removed. Keys are indexed by position, so a
+/// batch of `count` keys costs `O(count log n)`.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    data: HashMap<Bytes, Value>,
+    expires: ExpiryIndex,
+    positions: RandomState,
+    /// Every key in `data` with its position, in scan order
+    order: BTreeSet<(u64, Bytes)>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove `key` if its time to live has passed.
+    ///
+    /// # Returns
+    /// `true` if the key was expired and removed
+    fn evict_if_expired(&mut self, key: &[u8]) -> bool {
+        if !self.is_expired(key) {
+            return false;
+        }
+
+        self.expires.remove(key);
+        self.remove(key);
+        true
+    }
+
+    /// Insert `value` into `data`, keeping the scan order up to date
+    fn insert(&mut self, key: Bytes, value: Value) -> Option<Value> {
+        self.order.insert((self.position(&key), key.clone()));
+        self.data.insert(key, value)
+    }
+
+    /// Remove `key` from `data`, keeping the scan order up to date
+    fn remove(&mut self, key: &[u8]) -> Option<Value> {
+        let (key, value) = self.data.remove_entry(key)?;
+        self.order.remove(&(self.position(&key), key));
+        Some(value)
+    }
+
+    /// Whether the time to live of `key` has passed, or all fields of the hash stored at `key`
+    /// have expired
+    fn is_expired(&self, key: &[u8]) -> bool {
+        self.expires.is_expired(key, SystemTime::now())
+            || self.data.get(key).is_some_and(Value::is_empty)
+    }
+
+    /// Position of `key` in the order [`KeyValueStore::scan`] visits keys in
+    fn position(&self, key: &[u8]) -> u64 {
+        self.positions.hash_one(key) >> (u64::BITS - CURSOR_BITS)
+    }
+}
+
+/// Select the next batch of a scan over `items` with fixed positions below `1 << CURSOR_BITS`.
+///
+/// Returns roughly `count` items with the lowest positions at or after `cursor`, and the cursor
+/// to continue with, `0` once every item was returned. Items sharing the last position are
+/// returned together, so none is skipped.
+pub(crate) fn scan_positions<T>(
+    items: impl Iterator<Item = (u64, T)>,
+    cursor: u64,
+    count: usize,
+) -> (u64, Vec<T>) {
+    let count = count.max(1);
+    let mut batch: Vec<(u64, T)> = items.filter(|(position, _)| *position >= cursor).collect();
+
+    let mut next = 0;
+    if batch.len() > count {
+        batch.select_nth_unstable_by_key(count - 1, |(position, _)| *position);
+        let last = batch[count - 1].0;
+        batch.retain(|(position, _)| *position <= last);
+        if last < (1 << CURSOR_BITS) - 1 {
+            next = last + 1;
+        }
+    }
+
+    (next, batch.into_iter().map(|(_, item)| item).collect())
+}
+
+impl From<HashMap<Bytes, Bytes>> for MemoryStore {
+    fn from(data: HashMap<Bytes, Bytes>) -> Self {
+        let mut store = Self::new();
+        for (key, value) in data {
+            store.insert(key, Value::String(value));
+        }
+        store
+    }
+}
+
+impl KeyValueStore for MemoryStore {
+    fn get(&self, key: &[u8]) -> Option<Value> {
+        if self.is_expired(key) {
+            return None;
+        }
+
+        self.data.get(key).cloned()
+    }
+
+    fn entry(&self, key: &[u8]) -> Option<Entry> {
+        Some(Entry {
+            value: self.get(key)?,
+            expires: self.expires.get(key),
+        })
+    }
+
+    fn set(&mut self, key: Bytes, value: impl Into<Value>) -> Option<Value> {
+        let expired = self.evict_if_expired(&key);
+        self.expires.remove(&key);
+        let previous = self.insert(key, value.into());
+        if expired {
+            None
+        } else {
+            previous
+        }
+    }
+
+    fn del(&mut self, key: &[u8]) -> bool {
+        if self.evict_if_expired(key) {
+            return false;
+        }
+
+        self.expires.remove(key);
+        self.remove(key).is_some()
+    }
+
+    fn exists(&self, key: &[u8]) -> bool {
+        !self.is_expired(key) && self.data.contains_key(key)
+    }
+
+    fn read<R>(&self, key: &[u8], f: impl FnOnce(Option<&Value>) -> R) -> R {
+        if self.is_expired(key) {
+            return f(None);
+        }
+        f(self.data.get(key))
+    }
+
+    fn expire(&mut self, key: &[u8], deadline: Option<SystemTime>) -> bool {
+        if self.evict_if_expired(key) {
+            return false;
+        }
+
+        match self.data.get_key_value(key) {
+            Some((key, _)) => {
+                match deadline {
+                    Some(deadline) => self.expires.set(key.clone(), deadline),
+                    None => self.expires.remove(key),
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update<R>(&mut self, key: Bytes, f: impl FnOnce(&mut Option<Entry>) -> R) -> R {
+        self.evict_if_expired(&key);
+        let mut entry = self.remove(&key).map(|value| Entry {
+            value,
+            expires: self.expires.remove(&key),
+        });
+
+        let result = f(&mut entry);
+
+        if let Some(Entry { value, expires }) = entry.filter(|entry| !entry.value.is_empty()) {
+            if let Some(deadline) = expires {
+                self.expires.set(key.clone(), deadline);
+            }
+            self.insert(key, value);
+        }
+
+        result
+    }
+
+    fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        let count = count.max(1);
+        let mut order = self.order.range((cursor, Bytes::new())..).peekable();
+        let mut visited = 0;
+        let mut last = None;
+        let mut keys = Vec::new();
+
+        /* Keys sharing the last position are returned together, so none is skipped */
+        while let Some((position, key)) =
+            order.next_if(|(position, _)| visited < count || Some(*position) == last)
+        {
+            visited += 1;
+            last = Some(*position);
+            if !self.is_expired(key) {
+                keys.push(key.clone());
+            }
+        }
+
+        let next = match (order.peek(), last) {
+            (Some(_), Some(last)) => last + 1,
+            _ => 0,
+        };
+        (next, keys)
+    }
+
+    fn evict_expired(&mut self, limit: usize) -> usize {
+        let expired = self.expires.pop_expired(SystemTime::now(), limit);
+        for key in &expired {
+            self.remove(key);
+        }
+        expired.len()
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+        self.expires.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
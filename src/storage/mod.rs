@@ -0,0 +1,395 @@
+use crate::storage::value::Value;
+use bytes::Bytes;
+use std::future::Future;
+use std::time::SystemTime;
+
+/// [`expiry::ExpiryIndex`] of keys ordered by the time they expire
+pub mod expiry;
+
+/// [`memory::MemoryStore`], a single-threaded store backed by a `HashMap`
+pub mod memory;
+
+/// [`sharded::ShardedStore`], a store that can be shared between connections
+pub mod sharded;
+
+/// [`databases::Databases`], numbered keyspaces selected with `SELECT`
+pub mod databases;
+
+/// [`value::Value`], the data types that can be stored at a key
+pub mod value;
+
+/// [`hash::Hash`], fields and values with their own time to live
+pub mod hash;
+
+/// [`set::Set`], unordered members that can be picked at random
+pub mod set;
+
+/// [`sorted_set::SortedSet`], members ordered by score
+pub mod sorted_set;
+
+/// Value stored at a key together with its time to live
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub value: Value,
+    /// Point in time the key is removed, `None` if it is kept forever
+    pub expires: Option<SystemTime>,
+}
+
+impl Entry {
+    /// Create an entry that never expires
+    pub fn new(value: impl Into<Value>) -> Self {
+        Self {
+            value: value.into(),
+            expires: None,
+        }
+    }
+}
+
+/// Keyspace the keyspace command handlers read from and write to.
+///
+/// Reads take `&self`, writes take `&mut self`. Stores that synchronize internally, like
+/// [`sharded::ShardedStore`], implement this trait for a shared reference instead, so they can
+/// be used from many connections at once.
+///
+/// Keys whose deadline has passed must behave as if they did not exist.
+pub trait KeyValueStore {
+    /// Return the value stored at `key`, if any
+    fn get(&self, key: &[u8]) -> Option<Value>;
+
+    /// Return the value stored at `key` together with its time to live, if any
+    fn entry(&self, key: &[u8]) -> Option<Entry>;
+
+    /// Store `value` at `key`, discarding any time to live of a previous value.
+    ///
+    /// # Returns
+    /// The previously stored value, if any
+    fn set(&mut self, key: Bytes, value: impl Into<Value>) -> Option<Value>;
+
+    /// Remove `key`.
+    ///
+    /// # Returns
+    /// `true` if the key existed
+    fn del(&mut self, key: &[u8]) -> bool;
+
+    /// Check whether a value is stored at `key`
+    fn exists(&self, key: &[u8]) -> bool {
+        self.read(key, |entry| entry.is_some())
+    }
+
+    /// Inspect the value at `key` without copying it, e.g. to read a single field of a hash.
+    ///
+    /// `f` receives `None` if the key does not exist or has expired.
+    ///
+    /// # Returns
+    /// The result of `f`
+    fn read<R>(&self, key: &[u8], f: impl FnOnce(Option<&Value>) -> R) -> R;
+
+    /// Remove `key` once `deadline` has passed. `None` removes an existing time to live.
+    ///
+    /// # Returns
+    /// `true` if the key exists
+    fn expire(&mut self, key: &[u8], deadline: Option<SystemTime>) -> bool;
+
+    /// Atomically read and modify the entry at `key`.
+    ///
+    /// `f` receives `None` if the key does not exist or has expired. Whatever `f` leaves in the
+    /// option is stored afterward, so setting it to `None` deletes the key. Like in Redis,
+    /// collections left without elements are deleted as well.
+    ///
+    /// # Returns
+    /// The result of `f`
+    fn update<R>(&mut self, key: Bytes, f: impl FnOnce(&mut Option<Entry>) -> R) -> R;
+
+    /// Atomically read and modify the entries at several distinct `keys`, like
+    /// [`KeyValueStore::update`].
+    ///
+    /// `f` receives the entries in the order of `keys`. The default takes the entries out one
+    /// by one and puts them back afterward, which is only atomic for stores that are not shared.
+    /// Stores that synchronize internally have to override it.
+    ///
+    /// # Returns
+    /// The result of `f`
+    fn update_many<R>(&mut self, keys: &[Bytes], f: impl FnOnce(&mut [Option<Entry>]) -> R) -> R {
+        let mut entries: Vec<Option<Entry>> = keys
+            .iter()
+            .map(|key| self.update(key.clone(), Option::take))
+            .collect();
+
+        let result = f(&mut entries);
+
+        for (key, entry) in keys.iter().zip(entries) {
+            self.update(key.clone(), |existing| *existing = entry);
+        }
+        result
+    }
+
+    /// Iterate the keyspace in batches of roughly `count` keys, starting at `cursor`.
+    ///
+    /// Like in Redis, a full iteration returns every key that exists during the whole
+    /// iteration at least once, even if other keys are added or removed in between. Keys may be
+    /// returned more than once.
+    ///
+    /// # Returns
+    /// The cursor to continue with, `0` once the iteration is complete, and the keys found
+    fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>);
+
+    /// Remove up to `limit` keys whose time to live has passed.
+    ///
+    /// Stores that only expire keys lazily on access can keep the default, which removes
+    /// nothing.
+    ///
+    /// # Returns
+    /// The number of keys removed
+    fn evict_expired(&mut self, _limit: usize) -> usize {
+        0
+    }
+
+    /// Remove every key.
+    ///
+    /// The default collects all keys with [`KeyValueStore::scan`] and removes them one by one.
+    fn clear(&mut self) {
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, mut batch) = self.scan(cursor, 100);
+            keys.append(&mut batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        for key in keys {
+            self.del(&key);
+        }
+    }
+
+    /// Return the number of keys, possibly including expired keys not yet removed
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Asynchronous variant of [`KeyValueStore`] for keyspaces that live behind I/O, e.g. a
+/// remote database.
+///
+/// Every [`KeyValueStore`] that can be shared between threads implements this trait as well.
+pub trait AsyncKeyValueStore {
+    /// See [`KeyValueStore::get`]
+    fn get(&self, key: &[u8]) -> impl Future<Output = Option<Value>> + Send;
+
+    /// See [`KeyValueStore::set`]
+    fn set(&mut self, key: Bytes, value: Value) -> impl Future<Output = Option<Value>> + Send;
+
+    /// See [`KeyValueStore::del`]
+    fn del(&mut self, key: &[u8]) -> impl Future<Output = bool> + Send;
+
+    /// See [`KeyValueStore::exists`]
+    fn exists(&self, key: &[u8]) -> impl Future<Output = bool> + Send;
+
+    /// See [`KeyValueStore::expire`]
+    fn expire(
+        &mut self,
+        key: &[u8],
+        deadline: Option<SystemTime>,
+    ) -> impl Future<Output = bool> + Send;
+
+    /// See [`KeyValueStore::scan`]
+    fn scan(&self, cursor: u64, count: usize) -> impl Future<Output = (u64, Vec<Bytes>)> + Send;
+}
+
+impl<S: KeyValueStore + Send + Sync> AsyncKeyValueStore for S {
+    async fn get(&self, key: &[u8]) -> Option<Value> {
+        KeyValueStore::get(self, key)
+    }
+
+    async fn set(&mut self, key: Bytes, value: Value) -> Option<Value> {
+        KeyValueStore::set(self, key, value)
+    }
+
+    async fn del(&mut self, key: &[u8]) -> bool {
+        KeyValueStore::del(self, key)
+    }
+
+    async fn exists(&self, key: &[u8]) -> bool {
+        KeyValueStore::exists(self, key)
+    }
+
+    async fn expire(&mut self, key: &[u8], deadline: Option<SystemTime>) -> bool {
+        KeyValueStore::expire(self, key, deadline)
+    }
+
+    async fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        KeyValueStore::scan(self, cursor, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::memory::MemoryStore;
+    use super::sharded::ShardedStore;
+    use super::{Entry, KeyValueStore};
+    use bytes::Bytes;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    fn exercise(mut store: impl KeyValueStore) {
+        let key = Bytes::from_static(b"key");
+
+        assert_eq!(store.set(key.clone(), Bytes::from_static(b"a")), None);
+        assert_eq!(
+            store.set(key.clone(), Bytes::from_static(b"b")),
+            Some(Bytes::from_static(b"a").into())
+        );
+        assert_eq!(store.get(b"key"), Some(Bytes::from_static(b"b").into()));
+        assert!(store.exists(b"key"));
+        assert!(!store.exists(b"missing"));
+
+        assert!(store.expire(b"key", Some(SystemTime::now() - Duration::from_secs(1))));
+        assert_eq!(store.get(b"key"), None);
+        assert!(!store.del(b"key"));
+
+        store.set(key.clone(), Bytes::from_static(b"c"));
+        assert!(store.expire(b"key", Some(SystemTime::now() + Duration::from_secs(60))));
+        assert!(store.expire(b"key", None));
+        assert!(store.del(b"key"));
+        assert!(!store.expire(b"key", None));
+    }
+
+    fn scan_all(store: &impl KeyValueStore) -> Vec<Bytes> {
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, mut batch) = store.scan(cursor, 3);
+            keys.append(&mut batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        keys.sort();
+        keys
+    }
+
+    fn update_many(mut store: impl KeyValueStore) {
+        let keys: Vec<Bytes> = (0..20).map(|i| Bytes::from(format!("{i}"))).collect();
+        store.set(keys[3].clone(), Bytes::from_static(b"existing"));
+
+        let existing = store.update_many(&keys, |entries| {
+            let existing = entries.iter().filter(|entry| entry.is_some()).count();
+            for (i, entry) in entries.iter_mut().enumerate() {
+                *entry = (i % 2 == 1).then(|| Entry::new(Bytes::from_static(b"new")));
+            }
+            existing
+        });
+
+        assert_eq!(existing, 1);
+        assert_eq!(store.len(), 10);
+        assert_eq!(store.get(b"3"), Some(Bytes::from_static(b"new").into()));
+        assert!(!store.exists(b"4"));
+    }
+
+    #[test]
+    fn memory_store() {
+        exercise(MemoryStore::new());
+        update_many(MemoryStore::new());
+    }
+
+    #[test]
+    fn sharded_store() {
+        exercise(&ShardedStore::new());
+        update_many(&ShardedStore::with_shards(4));
+    }
+
+    #[test]
+    fn evict_expired_keys() {
+        let sharded = ShardedStore::with_shards(4);
+        let past = Some(SystemTime::now() - Duration::from_secs(1));
+
+        for i in 0..10 {
+            let key = Bytes::from(format!("{i}"));
+            sharded.set(key.clone(), Bytes::new());
+            if i % 2 == 0 {
+                sharded.expire(&key, past);
+            }
+        }
+
+        assert_eq!(sharded.len(), 10);
+        assert_eq!(sharded.evict_expired(3), 3);
+        assert_eq!(sharded.evict_expired(10), 2);
+        assert_eq!(sharded.len(), 5);
+    }
+
+    #[test]
+    fn scan_visits_every_key() {
+        let mut memory = MemoryStore::new();
+        let sharded = ShardedStore::with_shards(4);
+        let mut expected: Vec<Bytes> = (0..10).map(|i| Bytes::from(format!("{i}"))).collect();
+        expected.sort();
+
+        for key in &expected {
+            memory.set(key.clone(), Bytes::new());
+            sharded.set(key.clone(), Bytes::new());
+        }
+
+        assert_eq!(scan_all(&memory), expected);
+        assert_eq!(scan_all(&&sharded), expected);
+    }
+
+    #[test]
+    fn scan_tolerates_modification() {
+        let mut store = MemoryStore::new();
+        for i in 0..100 {
+            store.set(Bytes::from(format!("{i}")), Bytes::new());
+        }
+
+        let mut seen = Vec::new();
+        let (mut cursor, mut batch) = store.scan(0, 10);
+        seen.append(&mut batch);
+        for i in 100..200 {
+            store.set(Bytes::from(format!("{i}")), Bytes::new());
+        }
+        for key in seen.clone() {
+            store.del(&key);
+        }
+
+        while cursor != 0 {
+            let (next, mut batch) = store.scan(cursor, 10);
+            seen.append(&mut batch);
+            cursor = next;
+        }
+        for i in 0..100 {
+            assert!(
+                seen.contains(&Bytes::from(format!("{i}"))),
+                "{i} was skipped"
+            );
+        }
+    }
+
+    #[test]
+    fn scan_order_follows_removals() {
+        let mut store = MemoryStore::new();
+        for i in 0..50 {
+            store.set(Bytes::from(format!("{i}")), Bytes::new());
+        }
+
+        let (cursor, batch) = store.scan(0, 20);
+        assert_eq!(batch.len(), 20);
+        assert_ne!(cursor, 0);
+
+        store.del(b"0");
+        store.update(Bytes::from_static(b"1"), Option::take);
+        store.expire(b"2", Some(SystemTime::now() - Duration::from_secs(1)));
+        store.evict_expired(10);
+        let mut expected: Vec<Bytes> = (3..50).map(|i| Bytes::from(format!("{i}"))).collect();
+        expected.sort();
+        assert_eq!(scan_all(&store), expected);
+
+        store.clear();
+        assert_eq!(store.scan(0, 10), (0, Vec::new()));
+        store.set(Bytes::from_static(b"0"), Bytes::new());
+        assert_eq!(scan_all(&store), vec![Bytes::from_static(b"0")]);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::storage::memory::{scan_positions, CURSOR_BITS};
+use crate::util::convert::AsFrame;
+use bytes::Bytes;
+use redis_protocol::resp3::types::OwnedFrame;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+/// Unordered members of a set.
+///
+/// Besides the lookup table, members are kept in a dense array so that commands like
+/// `SRANDMEMBER` and `SPOP` can pick one by index in constant time. The position of a member
+/// only depends on the order of insertions and removals, which keeps random picks reproducible
+/// for a seeded [`Random`](crate::util::random::Random).
+#[derive(Debug, Clone, Default)]
+pub struct Set {
+    members: Vec<Bytes>,
+    /// Index of every member in `members`
+    positions: HashMap<Bytes, usize>,
+}
+
+impl Set {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of members
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Check whether `member` is part of the set
+    pub fn contains(&self, member: &[u8]) -> bool {
+        self.positions.contains_key(member)
+    }
+
+    /// Add `member`.
+    ///
+    /// # Returns
+    /// `true` if the member is new
+    pub fn insert(&mut self, member: Bytes) -> bool {
+        if self.contains(&member) {
+            return false;
+        }
+        self.positions.insert(member.clone(), self.members.len());
+        self.members.push(member);
+        true
+    }
+
+    /// Remove `member`.
+    ///
+    /// # Returns
+    /// `true` if the member was part of the set
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        match self.positions.get(member) {
+            Some(&index) => {
+                self.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the member at `index`, between `0` and [`Set::len`]
+    pub fn get(&self, index: usize) -> Option<&Bytes> {
+        self.members.get(index)
+    }
+
+    /// Remove the member at `index`, between `0` and [`Set::len`]. The last member takes its
+    /// place.
+    pub fn swap_remove(&mut self, index: usize) -> Bytes {
+        let member = self.members.swap_remove(index);
+        self.positions.remove(&member);
+        if let Some(moved) = self.members.get(index) {
+            self.positions.insert(moved.clone(), index);
+        }
+        member
+    }
+
+    /// Iterate over the members, in no particular order
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Bytes> {
+        self.members.iter()
+    }
+
+    /// Iterate the members in batches of roughly `count`, starting at `cursor`, with the same
+    /// guarantees as [`KeyValueStore::scan`](crate::storage::KeyValueStore::scan).
+    ///
+    /// # Returns
+    /// The cursor to continue with, `0` once the iteration is complete, and the members found
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        /* Positions must not change between calls, so the hasher is not randomized */
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        scan_positions(
+            self.iter().map(|member| {
+                let position = hasher.hash_one(member) >> (u64::BITS - CURSOR_BITS);
+                (position, member.clone())
+            }),
+            cursor,
+            count,
+        )
+    }
+}
+
+impl PartialEq for Set {
+    /// Sets are equal if they have the same members, in whatever order
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|member| other.contains(member))
+    }
+}
+
+impl Eq for Set {}
+
+impl FromIterator<Bytes> for Set {
+    fn from_iter<I: IntoIterator<Item = Bytes>>(iter: I) -> Self {
+        let mut set = Set::new();
+        for member in iter {
+            set.insert(member);
+        }
+        set
+    }
+}
+
+/// Convert to a RESP3 set of the members, which becomes an array for RESP2 clients
+impl AsFrame for Set {
+    fn as_frame(&self) -> OwnedFrame {
+        OwnedFrame::Set {
+            data: self.iter().map(AsFrame::as_frame).collect(),
+            attributes: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members() {
+        let mut set: Set = ["a", "b", "c", "a"].map(Bytes::from).into_iter().collect();
+        assert_eq!(set.len(), 3);
+
+        assert!(set.remove(b"a"));
+        assert!(!set.remove(b"a"));
+        assert_eq!(set.get(0), Some(&Bytes::from_static(b"c")));
+        assert_eq!(set.swap_remove(0), Bytes::from_static(b"c"));
+        assert!(set.contains(b"b"));
+        assert!(set.insert(Bytes::from_static(b"a")));
+        assert!(!set.insert(Bytes::from_static(b"b")));
+
+        let other: Set = ["a", "b"].map(Bytes::from).into_iter().collect();
+        assert_eq!(set, other);
+        let (cursor, batch) = set.scan(0, 10);
+        assert_eq!(cursor, 0);
+        assert_eq!(batch.len(), 2);
+    }
+}
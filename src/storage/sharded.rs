@@ -0,0 +1,265 @@
+use crate::storage::memory::{MemoryStore, CURSOR_BITS};
+use crate::storage::value::Value;
+use crate::storage::{Entry, KeyValueStore};
+use bytes::Bytes;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+/// Number of shards used by [`ShardedStore::new`]
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// Concurrent [`KeyValueStore`] that splits the keyspace into independently locked shards,
+/// similar to `DashMap`.
+///
+/// [`KeyValueStore`] is implemented for `&ShardedStore`, so a single store can be shared
+/// between connections without additional locking:
+///
+/// ```
+/// use bytes::Bytes;
+/// use redis_protocol_bridge::storage::sharded::ShardedStore;
+/// use redis_protocol_bridge::storage::KeyValueStore;
+///
+/// let store = ShardedStore::new();
+/// let mut handle = &store;
+/// handle.set(Bytes::from_static(b"key"), Bytes::from_static(b"value"));
+/// assert!(store.exists(b"key"));
+/// ```
+#[derive(Debug)]
+pub struct ShardedStore {
+    shards: Vec<RwLock<MemoryStore>>,
+    hasher: RandomState,
+}
+
+impl ShardedStore {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// Create a store with `shards` independently locked shards.
+    ///
+    /// # Panics
+    /// If `shards` is 0
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "ShardedStore needs at least one shard");
+        Self {
+            shards: (0..shards).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    fn index(&self, key: &[u8]) -> usize {
+        (self.hasher.hash_one(key) % self.shards.len() as u64) as usize
+    }
+
+    fn read_shard(&self, key: &[u8]) -> RwLockReadGuard<'_, MemoryStore> {
+        self.shards[self.index(key)]
+            .read()
+            .expect("Shard lock poisoned")
+    }
+
+    fn write(&self, key: &[u8]) -> RwLockWriteGuard<'_, MemoryStore> {
+        self.shards[self.index(key)]
+            .write()
+            .expect("Shard lock poisoned")
+    }
+
+    /// See [`KeyValueStore::get`]
+    pub fn get(&self, key: &[u8]) -> Option<Value> {
+        self.read_shard(key).get(key)
+    }
+
+    /// See [`KeyValueStore::entry`]
+    pub fn entry(&self, key: &[u8]) -> Option<Entry> {
+        self.read_shard(key).entry(key)
+    }
+
+    /// See [`KeyValueStore::set`]
+    pub fn set(&self, key: Bytes, value: impl Into<Value>) -> Option<Value> {
+        self.write(&key).set(key, value)
+    }
+
+    /// See [`KeyValueStore::del`]
+    pub fn del(&self, key: &[u8]) -> bool {
+        self.write(key).del(key)
+    }
+
+    /// See [`KeyValueStore::exists`]
+    pub fn exists(&self, key: &[u8]) -> bool {
+        self.read_shard(key).exists(key)
+    }
+
+    /// See [`KeyValueStore::read`]. The shard holding `key` stays read-locked while `f` runs.
+    pub fn read<R>(&self, key: &[u8], f: impl FnOnce(Option<&Value>) -> R) -> R {
+        self.read_shard(key).read(key, f)
+    }
+
+    /// See [`KeyValueStore::expire`]
+    pub fn expire(&self, key: &[u8], deadline: Option<SystemTime>) -> bool {
+        self.write(key).expire(key, deadline)
+    }
+
+    /// See [`KeyValueStore::update`]. The shard holding `key` stays locked while `f` runs.
+    pub fn update<R>(&self, key: Bytes, f: impl FnOnce(&mut Option<Entry>) -> R) -> R {
+        self.write(&key).update(key, f)
+    }
+
+    /// See [`KeyValueStore::update_many`]. The shards holding `keys` stay locked while `f` runs.
+    pub fn update_many<R>(&self, keys: &[Bytes], f: impl FnOnce(&mut [Option<Entry>]) -> R) -> R {
+        let mut indices: Vec<usize> = keys.iter().map(|key| self.index(key)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        /* Shards are always locked in ascending order, so concurrent calls can't deadlock */
+        let mut shards: Vec<RwLockWriteGuard<'_, MemoryStore>> = indices
+            .iter()
+            .map(|index| self.shards[*index].write().expect("Shard lock poisoned"))
+            .collect();
+        let shard_of = |key: &[u8]| {
+            indices
+                .binary_search(&self.index(key))
+                .expect("Shard of every key is locked")
+        };
+
+        let mut entries: Vec<Option<Entry>> = keys
+            .iter()
+            .map(|key| shards[shard_of(key)].update(key.clone(), Option::take))
+            .collect();
+
+        let result = f(&mut entries);
+
+        for (key, entry) in keys.iter().zip(entries) {
+            shards[shard_of(key)].update(key.clone(), |existing| *existing = entry);
+        }
+        result
+    }
+
+    /// See [`KeyValueStore::scan`].
+    ///
+    /// The upper 16 bits of the cursor select the shard, the remaining bits are the cursor
+    /// within that shard.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        let mut shard = (cursor >> CURSOR_BITS) as usize;
+        let mut inner = cursor & ((1 << CURSOR_BITS) - 1);
+        let mut keys = Vec::new();
+
+        while shard < self.shards.len() && keys.len() < count {
+            let (next, mut batch) = self.shards[shard]
+                .read()
+                .expect("Shard lock poisoned")
+                .scan(inner, count - keys.len());
+            keys.append(&mut batch);
+
+            if next == 0 {
+                shard += 1;
+                inner = 0;
+            } else {
+                inner = next;
+            }
+        }
+
+        if shard >= self.shards.len() {
+            (0, keys)
+        } else {
+            (((shard as u64) << CURSOR_BITS) | inner, keys)
+        }
+    }
+
+    /// See [`KeyValueStore::evict_expired`]. Shards are locked one after another, so
+    /// connections are only blocked for the duration of a single shard.
+    pub fn evict_expired(&self, limit: usize) -> usize {
+        let mut evicted = 0;
+        for shard in &self.shards {
+            if evicted >= limit {
+                break;
+            }
+            evicted += shard
+                .write()
+                .expect("Shard lock poisoned")
+                .evict_expired(limit - evicted);
+        }
+        evicted
+    }
+
+    /// See [`KeyValueStore::clear`]. Shards are cleared one after another, so keys written
+    /// concurrently may survive.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.write().expect("Shard lock poisoned").clear();
+        }
+    }
+
+    /// See [`KeyValueStore::len`]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().expect("Shard lock poisoned").len())
+            .sum()
+    }
+
+    /// See [`KeyValueStore::is_empty`]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ShardedStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyValueStore for &ShardedStore {
+    fn get(&self, key: &[u8]) -> Option<Value> {
+        ShardedStore::get(self, key)
+    }
+
+    fn entry(&self, key: &[u8]) -> Option<Entry> {
+        ShardedStore::entry(self, key)
+    }
+
+    fn set(&mut self, key: Bytes, value: impl Into<Value>) -> Option<Value> {
+        ShardedStore::set(self, key, value)
+    }
+
+    fn del(&mut self, key: &[u8]) -> bool {
+        ShardedStore::del(self, key)
+    }
+
+    fn exists(&self, key: &[u8]) -> bool {
+        ShardedStore::exists(self, key)
+    }
+
+    fn read<R>(&self, key: &[u8], f: impl FnOnce(Option<&Value>) -> R) -> R {
+        ShardedStore::read(self, key, f)
+    }
+
+    fn expire(&mut self, key: &[u8], deadline: Option<SystemTime>) -> bool {
+        ShardedStore::expire(self, key, deadline)
+    }
+
+    fn update<R>(&mut self, key: Bytes, f: impl FnOnce(&mut Option<Entry>) -> R) -> R {
+        ShardedStore::update(self, key, f)
+    }
+
+    fn update_many<R>(&mut self, keys: &[Bytes], f: impl FnOnce(&mut [Option<Entry>]) -> R) -> R {
+        ShardedStore::update_many(self, keys, f)
+    }
+
+    fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<Bytes>) {
+        ShardedStore::scan(self, cursor, count)
+    }
+
+    fn evict_expired(&mut self, limit: usize) -> usize {
+        ShardedStore::evict_expired(self, limit)
+    }
+
+    fn clear(&mut self) {
+        ShardedStore::clear(self)
+    }
+
+    fn len(&self) -> usize {
+        ShardedStore::len(self)
+    }
+}
@@ -0,0 +1,203 @@
+use bytes::Bytes;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
+
+/// Members of a sorted set, ordered by score and then lexicographically by member.
+///
+/// Scores are never NaN, and `-0` is stored as `0` so both sort the same like in Redis.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SortedSet {
+    scores: HashMap<Bytes, f64>,
+    order: BTreeSet<(Score, Bytes)>,
+}
+
+/// Score with a total order, so it can be used as a key of [`BTreeSet`]
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of members
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Return the score of `member`, if it is part of the set
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Add `member` with `score`, or update its score.
+    ///
+    /// # Returns
+    /// The previous score, if the member was already part of the set
+    pub fn insert(&mut self, member: Bytes, score: f64) -> Option<f64> {
+        debug_assert!(!score.is_nan(), "Scores must not be NaN");
+        let score = normalize(score);
+        let previous = self.scores.insert(member.clone(), score);
+        if let Some(previous) = previous {
+            self.order.remove(&(Score(previous), member.clone()));
+        }
+        self.order.insert((Score(score), member));
+        previous
+    }
+
+    /// Remove `member`.
+    ///
+    /// # Returns
+    /// Its score, if it was part of the set
+    pub fn remove(&mut self, member: &[u8]) -> Option<f64> {
+        let (member, score) = self.scores.remove_entry(member)?;
+        self.order.remove(&(Score(score), member));
+        Some(score)
+    }
+
+    /// Iterate over members and their scores, from the lowest to the highest score
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&Bytes, f64)> {
+        self.order.iter().map(|(score, member)| (member, score.0))
+    }
+
+    /// Return the rank of `member`, its position counted from the lowest score
+    pub fn rank(&self, member: &[u8]) -> Option<usize> {
+        let (member, score) = self.scores.get_key_value(member)?;
+        Some(self.order.range(..(Score(*score), member.clone())).count())
+    }
+
+    /// Iterate over members with a score between `min` and `max`, from the lowest to the
+    /// highest score
+    pub fn range_by_score(
+        &self,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    ) -> impl DoubleEndedIterator<Item = (&Bytes, f64)> {
+        let (min, max) = (min.map(normalize), max.map(normalize));
+        let empty = match (min, max) {
+            (Bound::Included(min), Bound::Included(max)) => min > max,
+            (Bound::Included(min) | Bound::Excluded(min), Bound::Excluded(max))
+            | (Bound::Excluded(min), Bound::Included(max)) => min >= max,
+            (Bound::Excluded(min), Bound::Unbounded) => min == f64::INFINITY,
+            (Bound::Unbounded, Bound::Excluded(max)) => max == f64::NEG_INFINITY,
+            _ => false,
+        };
+
+        /* The smallest member of a score is the empty one, so the bounds of the members can be
+         * expressed through the first member of this or the next larger score. Nothing sorts
+         * before the lowest score, which makes for an empty range. */
+        let start = match min {
+            _ if empty => Bound::Unbounded,
+            Bound::Included(min) => Bound::Included((Score(min), Bytes::new())),
+            Bound::Excluded(min) => Bound::Included((Score(min.next_up()), Bytes::new())),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let end = match max {
+            _ if empty => Bound::Excluded((Score(f64::NEG_INFINITY), Bytes::new())),
+            Bound::Included(f64::INFINITY) | Bound::Unbounded => Bound::Unbounded,
+            Bound::Included(max) => Bound::Excluded((Score(max.next_up()), Bytes::new())),
+            Bound::Excluded(max) => Bound::Excluded((Score(max), Bytes::new())),
+        };
+
+        self.order
+            .range((start, end))
+            .map(|(score, member)| (member, score.0))
+    }
+
+    /// Remove the member with the lowest score
+    pub fn pop_first(&mut self) -> Option<(Bytes, f64)> {
+        let (score, member) = self.order.pop_first()?;
+        self.scores.remove(&member);
+        Some((member, score.0))
+    }
+
+    /// Remove the member with the highest score
+    pub fn pop_last(&mut self) -> Option<(Bytes, f64)> {
+        let (score, member) = self.order.pop_last()?;
+        self.scores.remove(&member);
+        Some((member, score.0))
+    }
+}
+
+impl FromIterator<(Bytes, f64)> for SortedSet {
+    fn from_iter<I: IntoIterator<Item = (Bytes, f64)>>(iter: I) -> Self {
+        let mut zset = SortedSet::new();
+        for (member, score) in iter {
+            zset.insert(member, score);
+        }
+        zset
+    }
+}
+
+/// Turn `-0` into `0`, which [`f64::total_cmp`] would sort before it
+fn normalize(score: f64) -> f64 {
+    if score == 0.0 {
+        0.0
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        let mut zset: SortedSet = [("b", 1.0), ("a", 1.0), ("c", 2.5), ("z", -0.0)]
+            .map(|(member, score)| (Bytes::from(member), score))
+            .into_iter()
+            .collect();
+        zset.insert(Bytes::from("inf"), f64::INFINITY);
+
+        assert_eq!(zset.rank(b"z"), Some(0));
+        assert_eq!(zset.rank(b"b"), Some(2));
+        assert_eq!(zset.score(b"z").map(f64::is_sign_positive), Some(true));
+
+        let members = |min, max| -> Vec<&Bytes> {
+            zset.range_by_score(min, max)
+                .map(|(member, _)| member)
+                .collect()
+        };
+        assert_eq!(
+            members(Bound::Excluded(0.0), Bound::Included(2.5)),
+            ["a", "b", "c"]
+        );
+        assert_eq!(members(Bound::Included(-0.0), Bound::Excluded(1.0)), ["z"]);
+        assert_eq!(
+            members(Bound::Excluded(2.5), Bound::Included(f64::INFINITY)),
+            ["inf"]
+        );
+        assert!(members(Bound::Included(3.0), Bound::Included(2.0)).is_empty());
+        assert!(members(Bound::Excluded(1.0), Bound::Excluded(1.0)).is_empty());
+
+        assert_eq!(zset.pop_last(), Some((Bytes::from("inf"), f64::INFINITY)));
+        assert_eq!(zset.pop_first(), Some((Bytes::from("z"), 0.0)));
+        assert_eq!(zset.len(), 3);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::storage::hash::Hash;
+use crate::storage::set::Set;
+use crate::storage::sorted_set::SortedSet;
+use bytes::Bytes;
+use std::collections::VecDeque;
+
+/// Value stored at a key, one of the data types of Redis
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(Bytes),
+    Hash(Hash),
+    List(VecDeque<Bytes>),
+    Set(Set),
+    ZSet(SortedSet),
+}
+
+impl Value {
+    /// Name of the type, as reported by `TYPE`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+        }
+    }
+
+    /// Whether the value is a collection without elements.
+    ///
+    /// Like in Redis, keys are removed once their collection becomes empty. Strings never count
+    /// as empty, an empty string is a value of its own.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::Hash(hash) => hash.is_empty(),
+            Value::List(list) => list.is_empty(),
+            Value::Set(set) => set.is_empty(),
+            Value::ZSet(zset) => zset.is_empty(),
+        }
+    }
+
+    /// Return the string, if the value is one
+    pub fn as_string(&self) -> Option<&Bytes> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the hash, if the value is one
+    pub fn as_hash(&self) -> Option<&Hash> {
+        match self {
+            Value::Hash(hash) => Some(hash),
+            _ => None,
+        }
+    }
+
+    /// Return the hash for modification, if the value is one
+    pub fn as_hash_mut(&mut self) -> Option<&mut Hash> {
+        match self {
+            Value::Hash(hash) => Some(hash),
+            _ => None,
+        }
+    }
+
+    /// Return the list, if the value is one
+    pub fn as_list(&self) -> Option<&VecDeque<Bytes>> {
+        match self {
+            Value::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Return the list for modification, if the value is one
+    pub fn as_list_mut(&mut self) -> Option<&mut VecDeque<Bytes>> {
+        match self {
+            Value::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Return the set, if the value is one
+    pub fn as_set(&self) -> Option<&Set> {
+        match self {
+            Value::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    /// Return the set for modification, if the value is one
+    pub fn as_set_mut(&mut self) -> Option<&mut Set> {
+        match self {
+            Value::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    /// Return the sorted set, if the value is one
+    pub fn as_zset(&self) -> Option<&SortedSet> {
+        match self {
+            Value::ZSet(zset) => Some(zset),
+            _ => None,
+        }
+    }
+
+    /// Return the sorted set for modification, if the value is one
+    pub fn as_zset_mut(&mut self) -> Option<&mut SortedSet> {
+        match self {
+            Value::ZSet(zset) => Some(zset),
+            _ => None,
+        }
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(value: Bytes) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<Hash> for Value {
+    fn from(hash: Hash) -> Self {
+        Value::Hash(hash)
+    }
+}
+
+impl From<VecDeque<Bytes>> for Value {
+    fn from(list: VecDeque<Bytes>) -> Self {
+        Value::List(list)
+    }
+}
+
+impl From<Set> for Value {
+    fn from(set: Set) -> Self {
+        Value::Set(set)
+    }
+}
+
+impl From<SortedSet> for Value {
+    fn from(zset: SortedSet) -> Self {
+        Value::ZSet(zset)
+    }
+}
@@ -1,3 +1,4 @@
+use bytes::Bytes;
 use redis_protocol::resp3::types::OwnedFrame;
 use std::collections::HashMap;
 
@@ -24,7 +25,7 @@ impl Serialize for SerializableFrame {
     {
         let mut encoded: Vec<u8> = vec![0u8; self.0.encode_len(false)];
         encode::complete::encode(&mut encoded, &self.0, false).expect("Failed to encode");
-        serializer.serialize_bytes(&*encoded)
+        serializer.serialize_bytes(&encoded)
     }
 }
 
@@ -43,7 +44,7 @@ impl<'de> Visitor<'de> for FrameVisitor {
     where
         E: Error,
     {
-        let bytes = decode::complete::decode(&v);
+        let bytes = decode::complete::decode(v);
         if let Ok(Some((frame, _))) = bytes {
             Ok(SerializableFrame(frame))
         } else {
@@ -123,6 +124,17 @@ impl AsFrame for u16 {
     }
 }
 
+/// Convert to a RESP3 double, which becomes a bulk string for RESP2 clients, see
+/// [`downgrade`](crate::codec::downgrade)
+impl AsFrame for f64 {
+    fn as_frame(&self) -> OwnedFrame {
+        OwnedFrame::Double {
+            data: *self,
+            attributes: None,
+        }
+    }
+}
+
 impl AsFrame for String {
     fn as_frame(&self) -> OwnedFrame {
         OwnedFrame::BlobString {
@@ -153,6 +165,36 @@ impl AsFrame for &str {
     }
 }
 
+impl AsFrame for Bytes {
+    fn as_frame(&self) -> OwnedFrame {
+        OwnedFrame::BlobString {
+            data: self.to_vec(),
+            attributes: None,
+        }
+    }
+}
+
+impl AsFrame for [u8] {
+    fn as_frame(&self) -> OwnedFrame {
+        OwnedFrame::BlobString {
+            data: self.to_vec(),
+            attributes: None,
+        }
+    }
+}
+
+impl AsFrame for &[u8] {
+    fn as_frame(&self) -> OwnedFrame {
+        (*self).as_frame()
+    }
+}
+
+impl AsFrame for Vec<u8> {
+    fn as_frame(&self) -> OwnedFrame {
+        self.as_slice().as_frame()
+    }
+}
+
 impl AsFrame for RedisProtocolError {
     fn as_frame(&self) -> OwnedFrame {
         OwnedFrame::SimpleError {
@@ -199,6 +241,16 @@ impl<V: AsFrame> AsFrame for Vec<V> {
     }
 }
 
+/// Convert to the value, or to [`OwnedFrame::Null`] for `None`, e.g. for missing keys
+impl<V: AsFrame> AsFrame for Option<V> {
+    fn as_frame(&self) -> OwnedFrame {
+        match self {
+            Some(value) => value.as_frame(),
+            None => OwnedFrame::Null,
+        }
+    }
+}
+
 /// Convert to flattened list of tuple contents.
 impl<T: AsFrame + Clone> AsFrame for Vec<(T, T)> {
     fn as_frame(&self) -> OwnedFrame {
@@ -217,6 +269,107 @@ pub fn map_to_array<T: AsFrame + Clone>(map: HashMap<T, T>) -> OwnedFrame {
     arr.as_frame()
 }
 
+/// Lossily convert a binary argument to a [`String`], e.g. to match it against keywords
+/// or to include it in an error message.
+pub fn bytes_to_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Parse a binary argument or value as a signed 64-bit integer like Redis' `string2ll`.
+///
+/// Stricter than [`str::parse`]: signs other than a single `-`, leading zeros and whitespace
+/// are rejected, so only strings the integer would be formatted as again are accepted.
+pub fn bytes_to_i64(bytes: &[u8]) -> Option<i64> {
+    if bytes == b"0" {
+        return Some(0);
+    }
+
+    let (negative, digits) = match bytes {
+        [b'-', digits @ ..] => (true, digits),
+        digits => (false, digits),
+    };
+    if !matches!(digits.first(), Some(b'1'..=b'9')) || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let magnitude: u64 = std::str::from_utf8(digits).ok()?.parse().ok()?;
+    if negative {
+        0i64.checked_sub_unsigned(magnitude)
+    } else {
+        i64::try_from(magnitude).ok()
+    }
+}
+
+/// Parse a binary argument or value as a floating point number, rejecting whitespace and `NaN`
+/// like Redis' `string2ld`
+pub fn bytes_to_f64(bytes: &[u8]) -> Option<f64> {
+    let value: f64 = std::str::from_utf8(bytes).ok()?.parse().ok()?;
+    (!value.is_nan()).then_some(value)
+}
+
+/// Format a floating point number the way `INCRBYFLOAT` stores it: like Redis' `%.17Lf`, in
+/// plain decimal notation rounded to 17 decimal places, without trailing zeros, and `-0` as `0`.
+///
+/// Redis formats a `long double`, whose extra precision hides the rounding errors of binary
+/// fractions, e.g. `10.5 + 0.1` becomes `10.6`. To get the same results, the shortest
+/// representation of `value` is rounded instead of its exact binary value.
+pub fn f64_to_string(value: f64) -> String {
+    const PRECISION: usize = 17;
+    debug_assert!(
+        value.is_finite(),
+        "Cannot format {value} in decimal notation"
+    );
+
+    let shortest = value.abs().to_string();
+    let (integer, fraction) = shortest.split_once('.').unwrap_or((&shortest, ""));
+    let mut digits: Vec<u8> = integer
+        .bytes()
+        .chain(
+            fraction
+                .bytes()
+                .chain(std::iter::repeat(b'0'))
+                .take(PRECISION),
+        )
+        .collect();
+
+    if fraction
+        .as_bytes()
+        .get(PRECISION)
+        .is_some_and(|digit| *digit >= b'5')
+    {
+        /* Round up, `all` stops at the first digit that takes the carry */
+        let carried = digits.iter_mut().rev().all(|digit| {
+            if *digit == b'9' {
+                *digit = b'0';
+                true
+            } else {
+                *digit += 1;
+                false
+            }
+        });
+        if carried {
+            digits.insert(0, b'1');
+        }
+    }
+
+    let (integer, fraction) = digits.split_at(digits.len() - PRECISION);
+    let fraction = match fraction.iter().rposition(|digit| *digit != b'0') {
+        Some(last) => &fraction[..=last],
+        None => &[],
+    };
+
+    let mut formatted = String::with_capacity(digits.len() + 2);
+    if value.is_sign_negative() && (integer != b"0" || !fraction.is_empty()) {
+        formatted.push('-');
+    }
+    formatted.push_str(std::str::from_utf8(integer).expect("Digits are ASCII"));
+    if !fraction.is_empty() {
+        formatted.push('.');
+        formatted.push_str(std::str::from_utf8(fraction).expect("Digits are ASCII"));
+    }
+    formatted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,6 +394,56 @@ mod tests {
         // Check
         assert_eq!(deserialized.0, frame);
     }
+
+    #[test]
+    fn test_binary_as_frame() {
+        let value = Bytes::from_static(&[0xff, 0x00, 0xfe]);
+        let expected = OwnedFrame::BlobString {
+            data: vec![0xff, 0x00, 0xfe],
+            attributes: None,
+        };
+
+        assert_eq!(value.as_frame(), expected);
+        assert_eq!(value.to_vec().as_frame(), expected);
+        assert_eq!(value[..].as_frame(), expected);
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(bytes_to_i64(b"0"), Some(0));
+        assert_eq!(bytes_to_i64(b"-42"), Some(-42));
+        assert_eq!(bytes_to_i64(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(bytes_to_i64(b"-9223372036854775808"), Some(i64::MIN));
+        for invalid in [
+            &b""[..],
+            b"-",
+            b"-0",
+            b"007",
+            b"+1",
+            b" 1",
+            b"1 ",
+            b"1.0",
+            b"9223372036854775808",
+        ] {
+            assert_eq!(bytes_to_i64(invalid), None);
+        }
+
+        assert_eq!(bytes_to_f64(b"10.50"), Some(10.5));
+        assert_eq!(bytes_to_f64(b"5.0e3"), Some(5000.0));
+        assert_eq!(bytes_to_f64(b" 1"), None);
+        assert_eq!(bytes_to_f64(b"nan"), None);
+
+        assert_eq!(f64_to_string(10.5 + 0.1), "10.6");
+        assert_eq!(f64_to_string(5200.0), "5200");
+        assert_eq!(f64_to_string(-0.0), "0");
+        assert_eq!(f64_to_string(1e-20), "0");
+        assert_eq!(f64_to_string(-1e-20), "0");
+        assert_eq!(f64_to_string(1e-17), "0.00000000000000001");
+        assert_eq!(f64_to_string(5e-18), "0.00000000000000001");
+        assert_eq!(f64_to_string(-0.999999999999999999), "-1");
+        assert_eq!(f64_to_string(1.5e20), "150000000000000000000");
+        assert_eq!(f64_to_string(-2.25), "-2.25");
+    }
 }
 
 /*
@@ -27,3 +27,109 @@ pub fn error_unsupported_command(command: &str) -> RedisProtocolError {
         format!("Unsupported command: {command}"),
     )
 }
+
+/// Shorthand for the error Redis returns if a command received too few or too many arguments
+pub fn error_wrong_arity(command: &str) -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        format!(
+            "ERR wrong number of arguments for '{}' command",
+            command.to_lowercase()
+        ),
+    )
+}
+
+/// Shorthand for the error Redis returns if a database index does not exist
+pub fn error_db_index_out_of_range() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR DB index is out of range",
+    )
+}
+
+/// Shorthand for the error Redis returns if a command would move or copy a key onto itself
+pub fn error_same_object() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR source and destination objects are the same",
+    )
+}
+
+/// Shorthand for the error Redis returns if a time to live is out of range
+pub fn error_invalid_expire(command: &str) -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        format!(
+            "ERR invalid expire time in '{}' command",
+            command.to_lowercase()
+        ),
+    )
+}
+
+/// Shorthand for the error Redis returns if a command needs a key that does not exist
+pub fn error_no_such_key() -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR no such key")
+}
+
+/// Shorthand for the error Redis returns if a timeout is below zero
+pub fn error_negative_timeout() -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR timeout is negative")
+}
+
+/// Shorthand for the error Redis returns if a `SCAN` cursor is not an unsigned integer
+pub fn error_invalid_cursor() -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR invalid cursor")
+}
+
+/// Shorthand for the error Redis returns if options are malformed or contradict each other
+pub fn error_syntax() -> RedisProtocolError {
+    RedisProtocolError::new(RedisProtocolErrorKind::Parse, "ERR syntax error")
+}
+
+/// Shorthand for the error Redis returns if an argument is expected to be an integer
+pub fn error_not_integer() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR value is not an integer or out of range",
+    )
+}
+
+/// Shorthand for the error Redis returns if an integer increment leaves the range of `i64`
+pub fn error_increment_overflow() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR increment or decrement would overflow",
+    )
+}
+
+/// Shorthand for the error Redis returns if a float increment is not a finite number
+pub fn error_increment_not_finite() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "ERR increment would produce NaN or Infinity",
+    )
+}
+
+/// Shorthand for the error Redis returns to unauthenticated clients
+pub fn error_noauth() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "NOAUTH Authentication required.",
+    )
+}
+
+/// Shorthand for the error Redis returns if authentication failed
+pub fn error_wrongpass() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "WRONGPASS invalid username-password pair or user is disabled.",
+    )
+}
+
+/// Shorthand for the error Redis returns if a command does not support the type of a key
+pub fn error_wrong_type() -> RedisProtocolError {
+    RedisProtocolError::new(
+        RedisProtocolErrorKind::Parse,
+        "WRONGTYPE Operation against a key holding the wrong kind of value",
+    )
+}
@@ -0,0 +1,262 @@
+/// Recursion depth after which a pattern is considered abusive and never matches, like in Redis
+const MAX_NESTING: usize = 1000;
+
+/// Check whether `string` matches the glob-style `pattern`, exactly like Redis'
+/// `stringmatchlen`.
+///
+/// Both are treated as raw bytes.
+///
+///  * `*` matches any sequence of bytes
+///  * `?` matches a single byte
+///  * `[abc]`, `[a-z]` and `[^a]` match a single byte in, or with `^` not in, the set. Ranges may
+///    be given in either order, a class that is never closed extends to the end of the pattern.
+///  * `\` matches the following byte literally, also within `[]`
+///
+/// Like Redis, the empty string never matches a non-empty pattern, not even `*`. Redis
+/// commands treat the pattern `*` as "all keys" before matching, callers should do the same.
+pub fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    stringmatch(pattern, string, false, &mut false, 0)
+}
+
+/// Like [`matches`], but ASCII letters match regardless of their case. Bytes escaped with `\`
+/// within `[]` are still compared case-sensitively, as in Redis.
+pub fn matches_nocase(pattern: &[u8], string: &[u8]) -> bool {
+    stringmatch(pattern, string, true, &mut false, 0)
+}
+
+/// Port of `stringmatchlen_impl`.
+///
+/// `skip_longer` is set once the rest of the pattern after a `*` can't match anywhere in the
+/// rest of the string. Earlier `*`s then don't need to try longer matches either, which keeps
+/// patterns like `a*a*a*a*b` from taking exponential time.
+fn stringmatch(
+    mut pattern: &[u8],
+    mut string: &[u8],
+    nocase: bool,
+    skip_longer: &mut bool,
+    nesting: usize,
+) -> bool {
+    if nesting > MAX_NESTING {
+        return false;
+    }
+
+    let eq = |a: u8, b: u8| {
+        if nocase {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+
+    while !pattern.is_empty() && !string.is_empty() {
+        match pattern[0] {
+            b'*' => {
+                while pattern.get(1) == Some(&b'*') {
+                    pattern = &pattern[1..];
+                }
+                if pattern.len() == 1 {
+                    return true;
+                }
+                while !string.is_empty() {
+                    if stringmatch(&pattern[1..], string, nocase, skip_longer, nesting + 1) {
+                        return true;
+                    }
+                    if *skip_longer {
+                        return false;
+                    }
+                    string = &string[1..];
+                }
+                *skip_longer = true;
+                return false;
+            }
+            b'?' => string = &string[1..],
+            b'[' => {
+                pattern = &pattern[1..];
+                let negate = pattern.first() == Some(&b'^');
+                if negate {
+                    pattern = &pattern[1..];
+                }
+
+                let c = string[0];
+                let mut matched = false;
+                loop {
+                    match *pattern {
+                        [b'\\', escaped, ..] => {
+                            pattern = &pattern[1..];
+                            matched |= escaped == c;
+                        }
+                        [b']', ..] => break,
+                        /* Unterminated class, it ends with the pattern */
+                        [] => break,
+                        [start, b'-', end, ..] => {
+                            let (mut start, mut end, mut c) = (start.min(end), start.max(end), c);
+                            if nocase {
+                                start = start.to_ascii_lowercase();
+                                end = end.to_ascii_lowercase();
+                                c = c.to_ascii_lowercase();
+                            }
+                            pattern = &pattern[2..];
+                            matched |= (start..=end).contains(&c);
+                        }
+                        [literal, ..] => matched |= eq(literal, c),
+                    }
+                    pattern = &pattern[1..];
+                }
+
+                if matched == negate {
+                    return false;
+                }
+                string = &string[1..];
+            }
+            first => {
+                /* A trailing backslash matches itself */
+                let literal = match (first, pattern.get(1)) {
+                    (b'\\', Some(&escaped)) => {
+                        pattern = &pattern[1..];
+                        escaped
+                    }
+                    _ => first,
+                };
+                if !eq(literal, string[0]) {
+                    return false;
+                }
+                string = &string[1..];
+            }
+        }
+
+        pattern = pattern.get(1..).unwrap_or_default();
+        if string.is_empty() {
+            while pattern.first() == Some(&b'*') {
+                pattern = &pattern[1..];
+            }
+            break;
+        }
+    }
+
+    pattern.is_empty() && string.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_and_wildcards() {
+        assert!(matches(b"hello", b"hello"));
+        assert!(!matches(b"hello", b"hell"));
+        assert!(!matches(b"hell", b"hello"));
+        assert!(matches(b"user:*", b"user:42"));
+        assert!(!matches(b"user:*", b"session:42"));
+        assert!(matches(b"*:42", b"user:42"));
+        assert!(matches(b"*", b"anything"));
+        assert!(matches(b"a**b", b"a-b"));
+        assert!(matches(b"a*", b"a"));
+        assert!(matches(b"a***", b"a"));
+        assert!(matches(b"*a", b"a"));
+        assert!(matches(b"h?llo", b"hello"));
+        assert!(!matches(b"h?llo", b"hllo"));
+        assert!(matches(b"??", b"ab"));
+        assert!(!matches(b"??", b"a"));
+        assert!(matches(b"*.txt", b"notes.txt"));
+        assert!(!matches(b"*.txt", b"notes.txt.bak"));
+    }
+
+    #[test]
+    fn empty_inputs() {
+        assert!(matches(b"", b""));
+        assert!(!matches(b"", b"a"));
+        /* Like stringmatchlen, nothing matches the empty string but the empty pattern */
+        assert!(!matches(b"*", b""));
+        assert!(!matches(b"?", b""));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(matches(b"h[ae]llo", b"hallo"));
+        assert!(matches(b"h[ae]llo", b"hello"));
+        assert!(!matches(b"h[ae]llo", b"hillo"));
+        assert!(!matches(b"h[^e]llo", b"hello"));
+        assert!(matches(b"h[^e]llo", b"hallo"));
+        assert!(matches(b"h[a-b]llo", b"hbllo"));
+        assert!(!matches(b"h[a-b]llo", b"hcllo"));
+        /* Reversed ranges are swapped */
+        assert!(matches(b"[z-a]", b"m"));
+        /* `]` right after `[` closes an empty class */
+        assert!(!matches(b"[]a]", b"a"));
+        assert!(!matches(b"[]", b"a"));
+        /* `-` followed by `]` forms a range up to `]` */
+        assert!(matches(b"[a-]", b"]"));
+        assert!(matches(b"[a-]", b"a"));
+        assert!(matches(b"[-a]", b"-"));
+        assert!(matches(b"[a-c-e]", b"-"));
+        assert!(matches(b"[a-c-e]", b"e"));
+    }
+
+    #[test]
+    fn unterminated_classes() {
+        assert!(matches(b"[abc", b"a"));
+        assert!(matches(b"[abc", b"c"));
+        assert!(!matches(b"[abc", b"d"));
+        assert!(!matches(b"[abc", b"ab"));
+        assert!(matches(b"[^", b"x"));
+        assert!(!matches(b"[", b"x"));
+        assert!(!matches(b"x[", b"x"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(matches(b"h\\*llo", b"h*llo"));
+        assert!(!matches(b"h\\*llo", b"hello"));
+        assert!(matches(b"\\?", b"?"));
+        assert!(!matches(b"\\?", b"a"));
+        assert!(matches(b"[\\]]", b"]"));
+        assert!(matches(b"[\\-]", b"-"));
+        assert!(!matches(b"[\\-]", b"\\"));
+        assert!(matches(b"[\\^a]", b"^"));
+        /* A trailing backslash matches itself */
+        assert!(matches(b"a\\", b"a\\"));
+        assert!(!matches(b"a\\", b"a"));
+    }
+
+    #[test]
+    fn nocase() {
+        assert!(matches_nocase(b"HeLLo", b"hello"));
+        assert!(!matches(b"HeLLo", b"hello"));
+        assert!(matches_nocase(b"[A-C]x", b"bX"));
+        assert!(matches_nocase(b"[abc]", b"B"));
+        assert!(!matches_nocase(b"[^abc]", b"B"));
+        /* Escaped bytes in classes are compared case-sensitively */
+        assert!(!matches_nocase(b"[\\a]", b"A"));
+        assert!(matches_nocase(b"\\a", b"A"));
+        assert!(matches_nocase(b"maxmemory*", b"MAXMEMORY-policy"));
+    }
+
+    #[test]
+    fn binary_keys() {
+        assert!(matches(b"\x00*\xff", b"\x00abc\xff"));
+        assert!(matches(b"?", b"\xff"));
+        assert!(matches(b"[\x00-\x10]", b"\x05"));
+        assert!(matches(b"[\xf0-\xff]", b"\xfe"));
+        assert!(!matches(b"[\xf0-\xff]", b"\x7f"));
+        assert!(matches(b"a\x00b", b"a\x00b"));
+        assert!(!matches(b"a\x00b", b"a"));
+    }
+
+    #[test]
+    fn pathological_patterns() {
+        let string = vec![b'a'; 64];
+        let mut pattern = b"a*".repeat(32);
+        pattern.push(b'b');
+        assert!(!matches(&pattern, &string));
+
+        pattern.pop();
+        assert!(matches(&pattern, &string));
+
+        /* Patterns nested too deeply never match */
+        let deep = b"*a".repeat(MAX_NESTING + 1);
+        let string = vec![b'a'; MAX_NESTING + 1];
+        assert!(!matches(&deep, &string));
+        let shallow = b"*a".repeat(10);
+        assert!(matches(&shallow, &string));
+    }
+}
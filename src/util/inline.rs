@@ -0,0 +1,131 @@
+/// Split an inline command line into arguments, following the rules of Redis' `sdssplitargs`.
+///
+/// Arguments are separated by whitespace. Double-quoted arguments support the escapes `\n`,
+/// `\r`, `\t`, `\b`, `\a`, `\\`, `\"` and `\xHH`. Single-quoted arguments only support `\'`.
+/// A closing quote must be followed by whitespace or the end of the line.
+///
+/// # Returns
+/// The arguments, or `None` if quotes are unbalanced
+///
+/// # Example
+/// ```
+/// use redis_protocol_bridge::util::inline::split_args;
+///
+/// let args = split_args(br#"SET key "hello world""#).unwrap();
+/// assert_eq!(args, vec![b"SET".to_vec(), b"key".to_vec(), b"hello world".to_vec()]);
+/// ```
+pub fn split_args(line: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            return Some(args);
+        }
+
+        let mut arg = Vec::new();
+        let mut in_double = false;
+        let mut in_single = false;
+
+        loop {
+            let c = line.get(i).copied();
+            if in_double {
+                match c? {
+                    b'\\'
+                        if line.get(i + 3).is_some()
+                            && line[i + 1] == b'x'
+                            && line[i + 2].is_ascii_hexdigit()
+                            && line[i + 3].is_ascii_hexdigit() =>
+                    {
+                        arg.push(hex(line[i + 2]) * 16 + hex(line[i + 3]));
+                        i += 3;
+                    }
+                    b'\\' if line.get(i + 1).is_some() => {
+                        i += 1;
+                        arg.push(match line[i] {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            other => other,
+                        });
+                    }
+                    b'"' => {
+                        /* The closing quote must be followed by a space or nothing at all */
+                        if line.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) {
+                            return None;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    other => arg.push(other),
+                }
+            } else if in_single {
+                match c? {
+                    b'\\' if line.get(i + 1) == Some(&b'\'') => {
+                        i += 1;
+                        arg.push(b'\'');
+                    }
+                    b'\'' => {
+                        if line.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) {
+                            return None;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    other => arg.push(other),
+                }
+            } else {
+                match c {
+                    None => break,
+                    Some(c) if c.is_ascii_whitespace() => break,
+                    Some(b'"') => in_double = true,
+                    Some(b'\'') => in_single = true,
+                    Some(other) => arg.push(other),
+                }
+            }
+            i += 1;
+        }
+
+        args.push(arg);
+    }
+}
+
+fn hex(c: u8) -> u8 {
+    (c as char).to_digit(16).expect("Checked to be a hex digit") as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Option<Vec<String>> {
+        split_args(line.as_bytes()).map(|args| {
+            args.into_iter()
+                .map(|arg| String::from_utf8(arg).unwrap())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn split_plain_and_quoted() {
+        assert_eq!(split("  PING  ").unwrap(), vec!["PING"]);
+        assert_eq!(split("").unwrap(), Vec::<String>::new());
+        assert_eq!(
+            split(r#"SET "a b" 'c d' "\x41\n\"" 'it\'s' """#).unwrap(),
+            vec!["SET", "a b", "c d", "A\n\"", "it's", ""]
+        );
+        assert_eq!(split(r#"SET 'a\n' x"#).unwrap(), vec!["SET", "a\\n", "x"]);
+    }
+
+    #[test]
+    fn split_unbalanced_quotes() {
+        assert_eq!(split(r#"SET "a"#), None);
+        assert_eq!(split("SET 'a"), None);
+        assert_eq!(split(r#"SET "a"b"#), None);
+    }
+}
@@ -1,2 +1,11 @@
 pub mod convert;
 pub mod errors;
+
+/// Split inline commands sent by clients like `telnet` or `nc`
+pub mod inline;
+
+/// Glob-style pattern matching on binary strings
+pub mod glob;
+
+/// [`random::Random`], a seedable generator for commands that pick random elements
+pub mod random;
@@ -0,0 +1,61 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Pseudo-random number generator for commands that pick random elements, like `SRANDMEMBER`
+/// and `SPOP`.
+///
+/// This is SplitMix64: fast, small and good enough to pick elements evenly, but not suitable
+/// for anything that has to be unpredictable. Generators created with [`Random::seeded`]
+/// produce the same sequence every time, so results can be reproduced in tests.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Generator producing the same sequence for the same `seed`
+    pub fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generator seeded differently every time, from the keys of a randomized hasher
+    pub fn new() -> Self {
+        Self::seeded(RandomState::new().hash_one(0u64))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Random index below `bound`, which must not be `0`
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Cannot pick a number below 0");
+        /* Multiply and shift instead of taking the remainder, which favors small numbers less */
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let mut a = Random::seeded(42);
+        let mut b = Random::seeded(42);
+        let picks: Vec<usize> = (0..100).map(|_| a.below(10)).collect();
+        assert_eq!(picks, (0..100).map(|_| b.below(10)).collect::<Vec<_>>());
+        assert!(picks.iter().all(|pick| *pick < 10));
+        assert!((0..10).all(|i| picks.contains(&i)));
+    }
+}